@@ -1,5 +1,10 @@
 export interface IInstrument {
     figi: string,
     ticker: string,
-    display_name: string
+    display_name: string,
+    instrument_type?: 'share' | 'bond' | 'etf' | 'future' | 'currency',
+    exchange?: string,
+    currency?: string,
+    lot?: number,
+    min_price_increment?: number
 }
@@ -0,0 +1,22 @@
+export type ReportSource = 'Backtest' | 'Orders';
+
+export interface IPerformanceReport {
+    source: ReportSource,
+    timeFrom: string,
+    timeTo: string,
+    initialEquity: number,
+    finalEquity: number,
+    totalReturn: number,
+    annualisedReturn: number | null,
+    volatility: number | null,
+    sharpe: number | null,
+    sortino: number | null,
+    maxDrawdown: number,
+    maxDrawdownDurationDays: number,
+    trades: number,
+    closedTrades: number,
+    winRate: number | null,
+    profitFactor: number | null,
+    exposure: number,
+    turnover: number
+}
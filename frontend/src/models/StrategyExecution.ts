@@ -0,0 +1,23 @@
+export type StrategyExecutionStatus = 'Running' | 'Paused' | 'Stopped' | 'Finished' | 'Failed';
+
+export interface IStatusTransition {
+    ts: string,
+    status: StrategyExecutionStatus,
+    reason: string | null
+}
+
+export type FailureKind = 'Critical' | 'Panic' | 'Timeout';
+
+export interface IExecutionFailure {
+    kind: FailureKind,
+    message: string,
+    ts: string,
+    candleTs: string | null
+}
+
+export interface IStrategyExecution {
+    status: StrategyExecutionStatus,
+    lastExecutionTimestamp: string,
+    history: IStatusTransition[],
+    failure: IExecutionFailure | null
+}
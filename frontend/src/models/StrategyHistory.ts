@@ -0,0 +1,14 @@
+export interface IHistoryPage<T> {
+    total: number,
+    points: T[]
+}
+
+export interface ISignalPoint {
+    ts: string,
+    signals: { [figi: string]: number }
+}
+
+export interface IIndicatorPoint {
+    ts: string,
+    indicators: { [indicatorId: string]: unknown }
+}
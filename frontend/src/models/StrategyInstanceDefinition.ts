@@ -4,6 +4,8 @@ export interface IStrategyInstanceDefinition {
     strategy_name: String | null,
     time_from: String | null,
     time_to: String | null,
-    resolution: 'OneMinute' | 'OneHour' | 'OneDay',
-    params: { [key: string]: ParamValue }
+    resolution: 'OneMinute' | 'TwoMinutes' | 'FiveMinutes' | 'TenMinutes' | 'FifteenMinutes' | 'ThirtyMinutes'
+        | 'OneHour' | 'FourHours' | 'OneDay' | 'OneWeek' | 'OneMonth',
+    params: { [key: string]: ParamValue },
+    priceAdjustment: { splits: boolean, dividends: boolean }
 }
@@ -11,3 +11,15 @@ const fetchInstruments = async () => {
 }
 
 fetchInstruments();
+
+export interface IInstrumentSearchResult {
+	total: number;
+	instruments: IInstrument[];
+}
+
+export const searchInstruments = async (query: string, limit = 10): Promise<IInstrumentSearchResult> => {
+	const params = new URLSearchParams({ query, limit: limit.toString() });
+	const response = await fetch(`http://127.0.0.1:27001/search-instruments?${params}`);
+
+	return await response.json();
+}
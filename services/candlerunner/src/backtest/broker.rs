@@ -0,0 +1,407 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::backtest::{
+    BacktestIncome, BacktestResult, BacktestSettings, BacktestTrade, EquityPoint, FillModel,
+    TradeReason,
+};
+use crate::models::corporate_actions::{CorporateAction, CorporateActionKind};
+use crate::models::instruments::Figi;
+use crate::models::market_data::{Candle, CandlePack};
+use crate::models::orders::{OrderDirection, StopOrderType};
+
+/// Stop orders protecting a buy. They are placed once the buy is filled,
+/// at the offsets from its fill price.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Bracket {
+    pub stop_loss_offset: Option<f64>,
+    pub take_profit_offset: Option<f64>,
+    pub expire_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingOrder {
+    figi: Figi,
+    direction: OrderDirection,
+    lots: i64,
+    #[serde(default)]
+    bracket: Option<Bracket>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StopOrder {
+    figi: Figi,
+    stop_order_type: StopOrderType,
+    stop_price: f64,
+    lots: i64,
+    expire_at: Option<DateTime<Utc>>,
+}
+
+/// Broker simulation over candles. Every order is filled in full
+/// unless there is not enough cash to buy all requested lots.
+///
+/// Trades, income and equity curve are not serialized, they are taken out
+/// with `take_result` and stored as they are produced.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedBroker {
+    settings: BacktestSettings,
+    lot_sizes: HashMap<Figi, i64>,
+    cash: f64,
+    positions: BTreeMap<Figi, i64>,
+    last_prices: HashMap<Figi, f64>,
+    pending_orders: Vec<PendingOrder>,
+    stop_orders: Vec<StopOrder>,
+    #[serde(skip)]
+    trades: Vec<BacktestTrade>,
+    #[serde(skip)]
+    income: Vec<BacktestIncome>,
+
+    /// Accrued income waiting for its payment date
+    receivables: Vec<(DateTime<Utc>, f64)>,
+    #[serde(skip)]
+    equity_curve: Vec<EquityPoint>,
+}
+
+impl SimulatedBroker {
+    pub fn new(settings: BacktestSettings, lot_sizes: HashMap<Figi, i64>) -> Self {
+        Self {
+            cash: settings.initial_cash(),
+            settings,
+            lot_sizes,
+            positions: Default::default(),
+            last_prices: Default::default(),
+            pending_orders: Default::default(),
+            stop_orders: Default::default(),
+            trades: Default::default(),
+            income: Default::default(),
+            receivables: Default::default(),
+            equity_curve: Default::default(),
+        }
+    }
+
+    /// Number of instrument units in one lot; unknown lot sizes default to 1
+    pub fn lot_size(&self, figi: &Figi) -> i64 {
+        match self.lot_sizes.get(figi) {
+            Some(lot) if *lot > 0 => *lot,
+            _ => 1,
+        }
+    }
+
+    pub fn position(&self, figi: &Figi) -> i64 {
+        self.positions.get(figi).cloned().unwrap_or_default()
+    }
+
+    /// Cash plus positions valued at last known prices plus income yet to be paid
+    pub fn equity(&self) -> f64 {
+        let securities: f64 = self
+            .positions
+            .iter()
+            .map(|(figi, lots)| {
+                let price = self.last_prices.get(figi).cloned().unwrap_or_default();
+                (*lots * self.lot_size(figi)) as f64 * price
+            })
+            .sum();
+
+        let receivables: f64 = self.receivables.iter().map(|(_, amount)| amount).sum();
+
+        self.cash + securities + receivables
+    }
+
+    /// Accrues dividend or coupon for the position held before the ex-date.
+    /// `split_ratio` converts the amount per unit to units of split-adjusted prices.
+    pub fn accrue_income(&mut self, action: &CorporateAction, split_ratio: f64) {
+        let amount = match action.kind {
+            CorporateActionKind::Dividend { amount, .. }
+            | CorporateActionKind::Coupon { amount } => amount,
+            CorporateActionKind::Split { .. } => return,
+        };
+
+        let quantity = self.position(&action.figi) * self.lot_size(&action.figi);
+        if quantity == 0 || split_ratio <= 0.0 {
+            return;
+        }
+
+        let amount = quantity as f64 * amount / split_ratio;
+        let payment_date = action.payment_date.unwrap_or(action.ex_date);
+
+        self.receivables.push((payment_date, amount));
+        self.income.push(BacktestIncome {
+            ex_date: action.ex_date,
+            payment_date,
+            figi: action.figi.clone(),
+            quantity,
+            amount,
+        });
+    }
+
+    /// Submits market order for the signal produced on `candle`,
+    /// `bracket` stop orders are placed once it is filled
+    pub fn place_order(
+        &mut self,
+        ts: DateTime<Utc>,
+        figi: &Figi,
+        direction: OrderDirection,
+        lots: i64,
+        candle: &Candle,
+        bracket: Option<Bracket>,
+    ) {
+        match self.settings.fill_model() {
+            FillModel::Close => {
+                let filled =
+                    self.fill(ts, figi, direction, lots, candle.close, TradeReason::Signal);
+
+                if let (Some((lots, price)), Some(bracket)) = (filled, bracket) {
+                    self.place_bracket(figi, lots, price, bracket);
+                }
+            }
+            FillModel::NextOpen | FillModel::HighLowTouch => {
+                self.pending_orders.push(PendingOrder {
+                    figi: figi.clone(),
+                    direction,
+                    lots,
+                    bracket,
+                });
+            }
+        }
+    }
+
+    /// Submits stop orders closing the filled lots of a long position
+    fn place_bracket(&mut self, figi: &Figi, lots: i64, fill_price: f64, bracket: Bracket) {
+        let stops = [
+            (
+                StopOrderType::StopLoss,
+                bracket.stop_loss_offset.map(|offset| fill_price - offset),
+            ),
+            (
+                StopOrderType::TakeProfit,
+                bracket.take_profit_offset.map(|offset| fill_price + offset),
+            ),
+        ];
+
+        for (stop_order_type, stop_price) in stops {
+            if let Some(stop_price) = stop_price {
+                self.stop_orders.push(StopOrder {
+                    figi: figi.clone(),
+                    stop_order_type,
+                    stop_price,
+                    lots,
+                    expire_at: bracket.expire_at,
+                });
+            }
+        }
+    }
+
+    pub fn cancel_stop_orders(&mut self, figi: &Figi) {
+        self.stop_orders
+            .retain(|stop_order| stop_order.figi != *figi);
+    }
+
+    /// Pays due income, fills orders pending since previous candle and triggers stop orders
+    pub fn process_candles(&mut self, ts: DateTime<Utc>, candles: &CandlePack) {
+        let mut paid = 0.0;
+        self.receivables.retain(|(payment_date, amount)| {
+            let is_due = *payment_date <= ts;
+            if is_due {
+                paid += amount;
+            }
+
+            !is_due
+        });
+        self.cash += paid;
+
+        let pending_orders = std::mem::take(&mut self.pending_orders);
+
+        for order in pending_orders {
+            let candle = match candles.get(&order.figi) {
+                Some(candle) => candle,
+                // No trades for the instrument in this candle, wait for the next one
+                None => {
+                    self.pending_orders.push(order);
+                    continue;
+                }
+            };
+
+            let filled = self.fill(
+                ts,
+                &order.figi,
+                order.direction,
+                order.lots,
+                candle.open,
+                TradeReason::Signal,
+            );
+
+            if let (Some((lots, price)), Some(bracket)) = (filled, order.bracket) {
+                self.place_bracket(&order.figi, lots, price, bracket);
+            }
+        }
+
+        self.stop_orders
+            .retain(|stop_order| match stop_order.expire_at {
+                Some(expire_at) => expire_at > ts,
+                None => true,
+            });
+
+        let stop_orders = std::mem::take(&mut self.stop_orders);
+        let mut remaining_stop_orders = Vec::with_capacity(stop_orders.len());
+
+        for stop_order in stop_orders {
+            let fill_price = candles
+                .get(&stop_order.figi)
+                .and_then(|candle| self.stop_fill_price(&stop_order, candle));
+
+            let fill_price = match fill_price {
+                Some(price) => price,
+                None => {
+                    remaining_stop_orders.push(stop_order);
+                    continue;
+                }
+            };
+
+            let lots = stop_order.lots.min(self.position(&stop_order.figi));
+            if lots <= 0 {
+                continue;
+            }
+
+            let reason = match stop_order.stop_order_type {
+                StopOrderType::StopLoss => TradeReason::StopLoss,
+                StopOrderType::TakeProfit => TradeReason::TakeProfit,
+            };
+
+            self.fill(
+                ts,
+                &stop_order.figi,
+                OrderDirection::Sell,
+                lots,
+                fill_price,
+                reason,
+            );
+        }
+
+        // Stop orders of closed positions are cancelled
+        remaining_stop_orders.retain(|stop_order| self.position(&stop_order.figi) > 0);
+        self.stop_orders = remaining_stop_orders;
+
+        for (figi, candle) in candles {
+            self.last_prices.insert(figi.clone(), candle.close);
+        }
+    }
+
+    /// Records equity at the end of the candle
+    pub fn mark_to_market(&mut self, ts: DateTime<Utc>) {
+        self.equity_curve.push(EquityPoint {
+            ts,
+            cash: self.cash,
+            equity: self.equity(),
+        });
+    }
+
+    pub fn take_result(&mut self, signals_up_to: DateTime<Utc>) -> BacktestResult {
+        BacktestResult {
+            generation: 0,
+            signals_up_to,
+            trades: std::mem::take(&mut self.trades),
+            equity_curve: std::mem::take(&mut self.equity_curve),
+            positions: self
+                .positions
+                .iter()
+                .filter(|(_, lots)| **lots != 0)
+                .map(|(figi, lots)| (figi.clone(), *lots))
+                .collect(),
+            cash: self.cash,
+            income: std::mem::take(&mut self.income),
+        }
+    }
+
+    fn stop_fill_price(&self, stop_order: &StopOrder, candle: &Candle) -> Option<f64> {
+        let stop_price = stop_order.stop_price;
+
+        match (self.settings.fill_model(), stop_order.stop_order_type) {
+            (FillModel::HighLowTouch, StopOrderType::StopLoss) => {
+                if candle.open <= stop_price {
+                    // Price gapped through the stop
+                    Some(candle.open)
+                } else if candle.low <= stop_price {
+                    Some(stop_price)
+                } else {
+                    None
+                }
+            }
+            (FillModel::HighLowTouch, StopOrderType::TakeProfit) => {
+                if candle.open >= stop_price {
+                    Some(candle.open)
+                } else if candle.high >= stop_price {
+                    Some(stop_price)
+                } else {
+                    None
+                }
+            }
+            (_, StopOrderType::StopLoss) if candle.close <= stop_price => Some(candle.close),
+            (_, StopOrderType::TakeProfit) if candle.close >= stop_price => Some(candle.close),
+            _ => None,
+        }
+    }
+
+    fn fill(
+        &mut self,
+        ts: DateTime<Utc>,
+        figi: &Figi,
+        direction: OrderDirection,
+        lots: i64,
+        price: f64,
+        reason: TradeReason,
+    ) -> Option<(i64, f64)> {
+        let lot_size = self.lot_size(figi);
+
+        let price = match direction {
+            OrderDirection::Buy => price * (1.0 + self.settings.slippage()),
+            OrderDirection::Sell => price * (1.0 - self.settings.slippage()),
+        };
+
+        let lots = match direction {
+            OrderDirection::Buy => {
+                let lot_cost = price * lot_size as f64 * (1.0 + self.settings.commission());
+                lots.min((self.cash / lot_cost).floor() as i64)
+            }
+            OrderDirection::Sell => lots,
+        };
+
+        if lots <= 0 {
+            return None;
+        }
+
+        let quantity = lots * lot_size;
+        let value = price * quantity as f64;
+        let commission = value * self.settings.commission();
+        let position = self.positions.entry(figi.clone()).or_default();
+
+        match direction {
+            OrderDirection::Buy => {
+                self.cash -= value + commission;
+                *position += lots;
+            }
+            OrderDirection::Sell => {
+                self.cash += value - commission;
+                *position -= lots;
+            }
+        }
+
+        self.trades.push(BacktestTrade {
+            ts,
+            figi: figi.clone(),
+            direction,
+            lots,
+            quantity,
+            price,
+            commission,
+            reason,
+        });
+
+        Some((lots, price))
+    }
+}
@@ -0,0 +1,644 @@
+mod broker;
+mod report;
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::backtest::{BacktestResult, BacktestSettings};
+use crate::models::corporate_actions::{split_ratio_after, CorporateAction, PriceAdjustment};
+use crate::models::instruments::{Figi, Instrument};
+use crate::models::market_data::{CandlePack, CandleResolution};
+use crate::models::orders::OrderDirection;
+use crate::models::position_manager::weights_to_lots;
+use crate::models::strategy::{PlaceOrderSettings, StrategyState};
+
+use broker::{Bracket, SimulatedBroker};
+pub use report::{build_report, replay_orders};
+
+/// Replays strategy signals over candles through a simulated broker.
+///
+/// When `place_order_settings` are given, signals are turned into orders the way
+/// the order executor does for live instances: stop orders of a buy are placed
+/// at offsets from its simulated fill price once it is filled.
+/// Otherwise each signal is treated as a target share of the portfolio equity;
+/// the position is rebalanced whenever the signal changes.
+///
+/// Candles are expected to be split-adjusted; dividends and coupons of
+/// corporate actions passed to `run` are credited for positions held before the ex-date.
+///
+/// The backtest is stored between runs and continues from the last processed candle,
+/// so that only new signals are replayed.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backtest {
+    broker: SimulatedBroker,
+
+    /// Last processed candle
+    candles_up_to: Option<DateTime<Utc>>,
+
+    /// Number of corporate actions prices were adjusted for. A new one changes all past prices,
+    /// so the backtest has to be started over.
+    #[serde(alias = "splits")]
+    adjustments: usize,
+
+    /// Signals of the previous candle, weights are rebalanced only when they change
+    last_signals: HashMap<Figi, f64>,
+}
+
+impl Backtest {
+    pub fn new(
+        settings: BacktestSettings,
+        instruments: &HashMap<Figi, Instrument>,
+        corporate_actions: &[CorporateAction],
+        adjustment: PriceAdjustment,
+    ) -> Self {
+        let lot_sizes = instruments
+            .iter()
+            .map(|(figi, instrument)| (figi.clone(), instrument.lot))
+            .collect();
+
+        Self {
+            broker: SimulatedBroker::new(settings, lot_sizes),
+            candles_up_to: None,
+            adjustments: count_adjustments(corporate_actions, adjustment),
+            last_signals: Default::default(),
+        }
+    }
+
+    pub fn candles_up_to(&self) -> Option<DateTime<Utc>> {
+        self.candles_up_to
+    }
+
+    /// Whether prices the backtest was run on are still adjusted for the same actions
+    pub fn is_adjusted_for(
+        &self,
+        corporate_actions: &[CorporateAction],
+        adjustment: PriceAdjustment,
+    ) -> bool {
+        self.adjustments == count_adjustments(corporate_actions, adjustment)
+    }
+
+    /// Processes candles after `candles_up_to` along with signals produced on them
+    pub fn run(
+        &mut self,
+        place_order_settings: Option<&PlaceOrderSettings>,
+        resolution: CandleResolution,
+        instruments: &HashMap<Figi, Instrument>,
+        candles: &BTreeMap<DateTime<Utc>, CandlePack>,
+        states: &BTreeMap<DateTime<Utc>, StrategyState>,
+        corporate_actions: &[CorporateAction],
+    ) {
+        let candles = match self.candles_up_to {
+            Some(candles_up_to) => candles.range(resolution.advance(candles_up_to, 1)..),
+            None => candles.range(..),
+        };
+
+        // Actions before the first candle do not affect an empty portfolio
+        let mut processed_up_to = self.candles_up_to;
+
+        for (ts, pack) in candles {
+            let processed_from = processed_up_to.unwrap_or(*ts);
+
+            let mut actions: Vec<_> = corporate_actions
+                .iter()
+                .filter(|action| action.ex_date > processed_from && action.ex_date <= *ts)
+                .collect();
+            actions.sort_by_key(|action| action.ex_date);
+
+            for action in actions {
+                let split_ratio =
+                    split_ratio_after(corporate_actions, &action.figi, action.ex_date);
+                self.broker.accrue_income(action, split_ratio);
+            }
+
+            self.broker.process_candles(*ts, pack);
+
+            if let Some(state) = states.get(ts) {
+                // Sorted for the fills to be reproducible when cash is short
+                let signals: BTreeMap<_, _> = state.signals().iter().collect();
+
+                match place_order_settings {
+                    Some(place_order_settings) => execute_thresholds(
+                        &mut self.broker,
+                        place_order_settings,
+                        resolution,
+                        instruments,
+                        *ts,
+                        pack,
+                        &signals,
+                    ),
+                    None => execute_weights(
+                        &mut self.broker,
+                        *ts,
+                        pack,
+                        &signals,
+                        &mut self.last_signals,
+                    ),
+                }
+            }
+
+            self.broker.mark_to_market(*ts);
+            processed_up_to = Some(*ts);
+        }
+
+        self.candles_up_to = processed_up_to;
+    }
+
+    /// Takes trades, equity points and income produced since the previous call
+    /// along with the current portfolio
+    pub fn take_result(&mut self, signals_up_to: DateTime<Utc>) -> BacktestResult {
+        self.broker.take_result(signals_up_to)
+    }
+}
+
+fn count_adjustments(corporate_actions: &[CorporateAction], adjustment: PriceAdjustment) -> usize {
+    corporate_actions
+        .iter()
+        .filter(|action| adjustment.applies_to(&action.kind))
+        .count()
+}
+
+fn execute_thresholds(
+    broker: &mut SimulatedBroker,
+    place_order_settings: &PlaceOrderSettings,
+    resolution: CandleResolution,
+    instruments: &HashMap<Figi, Instrument>,
+    ts: DateTime<Utc>,
+    pack: &CandlePack,
+    signals: &BTreeMap<&Figi, &f64>,
+) {
+    for (figi, signal) in signals {
+        let candle = match pack.get(*figi) {
+            Some(candle) => candle,
+            None => continue,
+        };
+
+        let (direction, lots) =
+            match place_order_settings.decide_order(**signal, broker.position(figi)) {
+                Some(order) => order,
+                None => continue,
+            };
+
+        if direction == OrderDirection::Sell {
+            broker.cancel_stop_orders(figi);
+        }
+
+        let bracket = if direction == OrderDirection::Buy {
+            let tick = instruments
+                .get(*figi)
+                .map(|instrument| instrument.min_price_increment)
+                .unwrap_or_default();
+
+            let offset = |offset: u32| Some(tick * offset as f64).filter(|offset| *offset > 0.0);
+
+            Some(Bracket {
+                stop_loss_offset: offset(place_order_settings.stop_loss_offset()),
+                take_profit_offset: offset(place_order_settings.take_profit_offset()),
+                expire_at: Some(
+                    resolution.advance(ts, place_order_settings.interval_length() as i32 + 1),
+                ),
+            })
+        } else {
+            None
+        };
+
+        broker.place_order(ts, figi, direction, lots, candle, bracket);
+    }
+}
+
+fn execute_weights(
+    broker: &mut SimulatedBroker,
+    ts: DateTime<Utc>,
+    pack: &CandlePack,
+    signals: &BTreeMap<&Figi, &f64>,
+    last_signals: &mut HashMap<Figi, f64>,
+) {
+    let weights = signals
+        .iter()
+        .map(|(figi, signal)| ((*figi).clone(), signal.clamp(-1.0, 1.0)))
+        .collect();
+
+    let prices = pack
+        .iter()
+        .map(|(figi, candle)| (figi.clone(), candle.close))
+        .collect();
+
+    let lot_sizes = signals
+        .keys()
+        .map(|figi| ((*figi).clone(), broker.lot_size(figi)))
+        .collect();
+
+    let target_lots = weights_to_lots(&weights, broker.equity(), &prices, &lot_sizes);
+
+    for figi in signals.keys() {
+        let candle = match pack.get(*figi) {
+            Some(candle) => candle,
+            None => continue,
+        };
+
+        let signal = *signals[figi];
+        if last_signals.insert((*figi).clone(), signal) == Some(signal) {
+            continue;
+        }
+
+        let target = target_lots.get(*figi).cloned().unwrap_or_default();
+        let delta = target - broker.position(figi);
+
+        match delta {
+            0 => continue,
+            delta if delta > 0 => {
+                broker.place_order(ts, figi, OrderDirection::Buy, delta, candle, None)
+            }
+            delta => broker.place_order(ts, figi, OrderDirection::Sell, -delta, candle, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::models::backtest::{BacktestTrade, TradeReason};
+    use crate::models::corporate_actions::CorporateActionKind;
+    use crate::models::instruments::Ticker;
+    use crate::models::market_data::Candle;
+
+    fn split_adjustment() -> PriceAdjustment {
+        PriceAdjustment {
+            splits: true,
+            dividends: false,
+        }
+    }
+
+    fn run_backtest(
+        settings: &BacktestSettings,
+        place_order_settings: Option<&PlaceOrderSettings>,
+        resolution: CandleResolution,
+        instruments: &HashMap<Figi, Instrument>,
+        candles: &BTreeMap<DateTime<Utc>, CandlePack>,
+        states: &BTreeMap<DateTime<Utc>, StrategyState>,
+        corporate_actions: &[CorporateAction],
+    ) -> Option<BacktestResult> {
+        let signals_up_to = *states.keys().next_back()?;
+
+        let mut backtest = Backtest::new(
+            settings.clone(),
+            instruments,
+            corporate_actions,
+            split_adjustment(),
+        );
+        backtest.run(
+            place_order_settings,
+            resolution,
+            instruments,
+            candles,
+            states,
+            corporate_actions,
+        );
+
+        Some(backtest.take_result(signals_up_to))
+    }
+
+    fn figi() -> Figi {
+        Figi("FIGI".to_owned())
+    }
+
+    fn instruments() -> HashMap<Figi, Instrument> {
+        [(
+            figi(),
+            Instrument {
+                figi: figi(),
+                ticker: Ticker("TICKER".to_owned()),
+                display_name: "Instrument".to_owned(),
+                exchange: "EXCHANGE".to_owned(),
+                lot: 10,
+                min_price_increment: 1.0,
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect()
+    }
+
+    fn candles(ohlc: &[(f64, f64, f64, f64)]) -> BTreeMap<DateTime<Utc>, CandlePack> {
+        ohlc.iter()
+            .enumerate()
+            .map(|(i, (open, high, low, close))| {
+                let ts = Utc.with_ymd_and_hms(2022, 1, 3, 10, i as u32, 0).unwrap();
+                let candle = Candle {
+                    open: *open,
+                    high: *high,
+                    low: *low,
+                    close: *close,
+                    volume: 100,
+                };
+
+                (ts, [(figi(), candle)].into_iter().collect())
+            })
+            .collect()
+    }
+
+    fn states(signals: &[f64]) -> BTreeMap<DateTime<Utc>, StrategyState> {
+        signals
+            .iter()
+            .enumerate()
+            .map(|(i, signal)| {
+                let mut state = StrategyState::default();
+                state.set_signal(figi(), *signal);
+
+                (
+                    Utc.with_ymd_and_hms(2022, 1, 3, 10, i as u32, 0).unwrap(),
+                    state,
+                )
+            })
+            .collect()
+    }
+
+    fn settings(fill_model: &str) -> BacktestSettings {
+        serde_json::from_value(serde_json::json!({
+            "initialCash": 10000.0,
+            "commission": 0.001,
+            "slippage": 0.0,
+            "fillModel": fill_model
+        }))
+        .unwrap()
+    }
+
+    fn place_order_settings() -> PlaceOrderSettings {
+        serde_json::from_value(serde_json::json!({
+            "accountId": "account",
+            "buyThreshold": 0.5,
+            "sellThreshold": -0.5,
+            "stopLossOffset": 5,
+            "takeProfitOffset": 0,
+            "intervalLength": 10
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_next_open_fills() {
+        let candles = candles(&[
+            (100.0, 101.0, 99.0, 100.0),
+            (102.0, 103.0, 101.0, 103.0),
+            (104.0, 106.0, 103.0, 105.0),
+            (106.0, 107.0, 105.0, 106.0),
+        ]);
+        let states = states(&[1.0, 0.0, -1.0, 0.0]);
+
+        let result = run_backtest(
+            &settings("nextOpen"),
+            Some(&place_order_settings()),
+            CandleResolution::OneMinute,
+            &instruments(),
+            &candles,
+            &states,
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(result.trades.len(), 2);
+        assert_eq!(result.trades[0].direction, OrderDirection::Buy);
+        assert_eq!(result.trades[0].price, 102.0);
+        assert_eq!(result.trades[1].direction, OrderDirection::Sell);
+        assert_eq!(result.trades[1].price, 106.0);
+        assert!(result.positions.is_empty());
+
+        let commission = (1020.0 + 1060.0) * 0.001;
+        assert!((result.cash - (10000.0 + 40.0 - commission)).abs() < 1e-9);
+        assert_eq!(result.equity_curve.len(), 4);
+    }
+
+    #[test]
+    fn test_stop_loss_touch() {
+        let candles = candles(&[
+            (100.0, 101.0, 99.0, 100.0),
+            (100.0, 101.0, 94.0, 99.0),
+            (99.0, 100.0, 98.0, 99.0),
+        ]);
+        let states = states(&[1.0, 0.0, 0.0]);
+
+        let touch = run_backtest(
+            &settings("highLowTouch"),
+            Some(&place_order_settings()),
+            CandleResolution::OneMinute,
+            &instruments(),
+            &candles,
+            &states,
+            &[],
+        )
+        .unwrap();
+
+        // Bought at 100 on the second candle open, stop at 95 touched by its low
+        assert_eq!(touch.trades.len(), 2);
+        assert_eq!(touch.trades[1].reason, TradeReason::StopLoss);
+        assert_eq!(touch.trades[1].price, 95.0);
+
+        let close = run_backtest(
+            &settings("close"),
+            Some(&place_order_settings()),
+            CandleResolution::OneMinute,
+            &instruments(),
+            &candles,
+            &states,
+            &[],
+        )
+        .unwrap();
+
+        // Closes never reach the stop
+        assert_eq!(close.trades.len(), 1);
+        assert_eq!(close.positions.get(&figi()), Some(&1));
+    }
+
+    #[test]
+    fn test_stop_loss_off_fill() {
+        let candles = candles(&[
+            (100.0, 101.0, 99.0, 100.0),
+            (110.0, 111.0, 104.0, 108.0),
+            (108.0, 109.0, 107.0, 108.0),
+        ]);
+        let states = states(&[1.0, 0.0, 0.0]);
+
+        let result = run_backtest(
+            &settings("highLowTouch"),
+            Some(&place_order_settings()),
+            CandleResolution::OneMinute,
+            &instruments(),
+            &candles,
+            &states,
+            &[],
+        )
+        .unwrap();
+
+        // Bought at 110 on the second candle open, stop at 105 rather than 95 off the signal close
+        assert_eq!(result.trades.len(), 2);
+        assert_eq!(result.trades[0].price, 110.0);
+        assert_eq!(result.trades[1].reason, TradeReason::StopLoss);
+        assert_eq!(result.trades[1].price, 105.0);
+    }
+
+    #[test]
+    fn test_resumed_backtest() {
+        let candles = candles(&[
+            (100.0, 101.0, 99.0, 100.0),
+            (102.0, 103.0, 101.0, 103.0),
+            (104.0, 106.0, 103.0, 105.0),
+            (106.0, 107.0, 105.0, 106.0),
+        ]);
+        let states = states(&[1.0, 0.0, -1.0, 0.0]);
+        let settings = settings("nextOpen");
+        let place_order_settings = place_order_settings();
+
+        let mut backtest = Backtest::new(settings.clone(), &instruments(), &[], split_adjustment());
+        let mut trades = vec![];
+
+        // Signals arrive one candle at a time, the stored backtest continues from the last one
+        for ts in states.keys() {
+            let mut stored: Backtest = bson::from_bson(bson::to_bson(&backtest).unwrap()).unwrap();
+            let available = |ts: &DateTime<Utc>| {
+                candles
+                    .range(..=*ts)
+                    .map(|(ts, pack)| (*ts, pack.clone()))
+                    .collect()
+            };
+
+            stored.run(
+                Some(&place_order_settings),
+                CandleResolution::OneMinute,
+                &instruments(),
+                &available(ts),
+                &states,
+                &[],
+            );
+            trades.extend(stored.take_result(*ts).trades);
+            backtest = stored;
+        }
+
+        let result = run_backtest(
+            &settings,
+            Some(&place_order_settings),
+            CandleResolution::OneMinute,
+            &instruments(),
+            &candles,
+            &states,
+            &[],
+        )
+        .unwrap();
+
+        let prices = |trades: &[BacktestTrade]| -> Vec<_> {
+            trades.iter().map(|trade| trade.price).collect()
+        };
+        assert_eq!(prices(&trades), prices(&result.trades));
+        assert_eq!(backtest.take_result(result.signals_up_to).cash, result.cash);
+    }
+
+    #[test]
+    fn test_target_weights() {
+        let candles = candles(&[(100.0, 100.0, 100.0, 100.0), (100.0, 100.0, 100.0, 100.0)]);
+        let states = states(&[0.5, 0.5]);
+
+        let result = run_backtest(
+            &settings("close"),
+            None,
+            CandleResolution::OneMinute,
+            &instruments(),
+            &candles,
+            &states,
+            &[],
+        )
+        .unwrap();
+
+        // Half of equity in lots of 10 units worth 100 each
+        assert_eq!(result.trades.len(), 1);
+        assert_eq!(result.positions.get(&figi()), Some(&5));
+    }
+
+    #[test]
+    fn test_dividend_income() {
+        let candles = candles(&[
+            (100.0, 100.0, 100.0, 100.0),
+            (100.0, 100.0, 100.0, 100.0),
+            (96.0, 96.0, 96.0, 96.0),
+            (96.0, 96.0, 96.0, 96.0),
+        ]);
+        let states = states(&[1.0, 0.0, 0.0, 0.0]);
+
+        let dividend = CorporateAction {
+            figi: figi(),
+            ex_date: Utc.with_ymd_and_hms(2022, 1, 3, 10, 2, 0).unwrap(),
+            payment_date: Some(Utc.with_ymd_and_hms(2022, 1, 3, 10, 3, 0).unwrap()),
+            kind: CorporateActionKind::Dividend {
+                amount: 4.0,
+                close_price: None,
+            },
+        };
+
+        let result = run_backtest(
+            &settings("close"),
+            Some(&place_order_settings()),
+            CandleResolution::OneMinute,
+            &instruments(),
+            &candles,
+            &states,
+            &[dividend],
+        )
+        .unwrap();
+
+        // One lot of 10 units bought at 100 receives 40
+        assert_eq!(result.income.len(), 1);
+        assert_eq!(result.income[0].quantity, 10);
+        assert_eq!(result.income[0].amount, 40.0);
+
+        let cash_after_buy = 10000.0 - 1000.0 * 1.001;
+        let curve = &result.equity_curve;
+
+        // Price drop on the ex-date is offset by the receivable until it is paid
+        assert!((curve[2].cash - cash_after_buy).abs() < 1e-9);
+        assert!((curve[2].equity - curve[1].equity).abs() < 1e-9);
+        assert!((curve[3].cash - (cash_after_buy + 40.0)).abs() < 1e-9);
+        assert!((curve[3].equity - curve[1].equity).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_is_adjusted_for() {
+        let action = |day: u32, kind: CorporateActionKind| CorporateAction {
+            figi: figi(),
+            ex_date: Utc.with_ymd_and_hms(2022, 1, day, 0, 0, 0).unwrap(),
+            payment_date: None,
+            kind,
+        };
+
+        let split = action(3, CorporateActionKind::Split { ratio: 2.0 });
+        let dividend = action(
+            4,
+            CorporateActionKind::Dividend {
+                amount: 4.0,
+                close_price: None,
+            },
+        );
+
+        let total_return = PriceAdjustment {
+            splits: true,
+            dividends: true,
+        };
+
+        let actions = [split, dividend];
+        let settings = settings("close");
+
+        let backtest = Backtest::new(
+            settings.clone(),
+            &instruments(),
+            &actions[..1],
+            split_adjustment(),
+        );
+        let total_return_backtest =
+            Backtest::new(settings, &instruments(), &actions[..1], total_return);
+
+        // A new dividend changes past prices only when they are adjusted for dividends
+        assert!(backtest.is_adjusted_for(&actions, split_adjustment()));
+        assert!(!total_return_backtest.is_adjusted_for(&actions, total_return));
+        assert!(!backtest.is_adjusted_for(&actions[1..], split_adjustment()));
+    }
+}
@@ -0,0 +1,586 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{prelude::*, Duration};
+
+use crate::models::backtest::{BacktestResult, BacktestTrade, EquityPoint, TradeReason};
+use crate::models::instruments::{Figi, Instrument};
+use crate::models::market_data::{CandlePack, CandleResolution};
+use crate::models::orders::{OrderDirection, OrderRecord};
+use crate::models::report::{PerformanceReport, ReportSource};
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+const DAYS_PER_YEAR: f64 = 365.25;
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Sample standard deviation
+fn std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+
+    let mean = mean(values);
+    let sum_sq: f64 = values.iter().map(|value| (value - mean).powi(2)).sum();
+
+    Some((sum_sq / (values.len() - 1) as f64).sqrt())
+}
+
+fn days(duration: Duration) -> f64 {
+    duration.num_seconds() as f64 / SECONDS_PER_DAY
+}
+
+/// Max drawdown and its duration in days
+fn max_drawdown(initial_equity: f64, equity_curve: &[EquityPoint]) -> (f64, f64) {
+    let (first, last) = match (equity_curve.first(), equity_curve.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return (0.0, 0.0),
+    };
+
+    let mut peak = initial_equity;
+    let mut peak_ts = first.ts;
+
+    let mut max_drawdown = 0.0;
+    // Peak preceding max drawdown and the moment equity got back to it
+    let mut max_drawdown_period: Option<(DateTime<Utc>, Option<DateTime<Utc>>)> = None;
+
+    for point in equity_curve {
+        if point.equity >= peak {
+            if let Some((start, end @ None)) = &mut max_drawdown_period {
+                if *start == peak_ts {
+                    *end = Some(point.ts);
+                }
+            }
+
+            peak = point.equity;
+            peak_ts = point.ts;
+            continue;
+        }
+
+        let drawdown = 1.0 - point.equity / peak;
+
+        if drawdown > max_drawdown {
+            max_drawdown = drawdown;
+            max_drawdown_period = Some((peak_ts, None));
+        }
+    }
+
+    let duration = match max_drawdown_period {
+        Some((start, end)) => days(end.unwrap_or(last.ts) - start),
+        None => 0.0,
+    };
+
+    (max_drawdown, duration)
+}
+
+/// Profits of trades reducing positions, commissions included.
+/// Cost of a position is tracked as average entry price.
+fn closed_trade_profits(trades: &[BacktestTrade]) -> Vec<f64> {
+    // Signed quantity and average entry price per instrument
+    let mut positions: HashMap<_, (i64, f64)> = HashMap::default();
+    let mut profits = Vec::default();
+
+    for trade in trades {
+        if trade.quantity <= 0 {
+            continue;
+        }
+
+        let sign = match trade.direction {
+            OrderDirection::Buy => 1,
+            OrderDirection::Sell => -1,
+        };
+
+        let commission_per_unit = trade.commission / trade.quantity as f64;
+        let (quantity, entry_price) = positions.entry(&trade.figi).or_insert((0, 0.0));
+        let mut remaining = trade.quantity;
+
+        if *quantity != 0 && quantity.signum() != sign {
+            let closed = remaining.min(quantity.abs());
+            let profit = quantity.signum() as f64 * (trade.price - *entry_price) * closed as f64
+                - commission_per_unit * closed as f64;
+
+            profits.push(profit);
+
+            *quantity += sign * closed;
+            remaining -= closed;
+        }
+
+        if remaining > 0 {
+            let price = trade.price + sign as f64 * commission_per_unit;
+            let total = quantity.abs() + remaining;
+
+            *entry_price =
+                (*entry_price * quantity.abs() as f64 + price * remaining as f64) / total as f64;
+            *quantity += sign * remaining;
+        }
+    }
+
+    profits
+}
+
+fn signed_quantity(trade: &BacktestTrade) -> i64 {
+    match trade.direction {
+        OrderDirection::Buy => trade.quantity,
+        OrderDirection::Sell => -trade.quantity,
+    }
+}
+
+/// Share of equity points with open positions.
+/// Positions are tracked from trades, since income receivable after an ex-date
+/// keeps equity apart from cash with no position held.
+fn exposure(trades: &[BacktestTrade], equity_curve: &[EquityPoint]) -> f64 {
+    let mut positions: HashMap<&Figi, i64> = HashMap::default();
+    let mut trades = trades.iter().peekable();
+    let mut exposed = 0;
+
+    for point in equity_curve {
+        while let Some(trade) = trades.next_if(|trade| trade.ts <= point.ts) {
+            *positions.entry(&trade.figi).or_default() += signed_quantity(trade);
+        }
+
+        if positions.values().any(|quantity| *quantity != 0) {
+            exposed += 1;
+        }
+    }
+
+    exposed as f64 / equity_curve.len() as f64
+}
+
+/// Replays orders executed for an instance against `candles`.
+/// Positions are valued at candle close, so equity points are stamped with candle end.
+/// Trades are priced at average fill price; commissions are not reported with orders.
+/// Live instances have no capital of their own, so initial equity is taken as the largest value
+/// their positions had at once. Returns it along with the result, `None` without executed orders.
+pub fn replay_orders(
+    records: &[OrderRecord],
+    instruments: &HashMap<Figi, Instrument>,
+    resolution: CandleResolution,
+    candles: &BTreeMap<DateTime<Utc>, CandlePack>,
+) -> Option<(f64, BacktestResult)> {
+    let trades: Vec<_> = records
+        .iter()
+        .filter_map(|record| {
+            let lot = instruments.get(&record.figi)?.lot;
+            let price = record.order.average_price(lot)?;
+
+            Some(BacktestTrade {
+                ts: record.ts,
+                figi: record.figi.clone(),
+                direction: record.direction,
+                lots: record.order.lots_executed,
+                quantity: record.order.lots_executed * lot,
+                price,
+                commission: 0.0,
+                reason: TradeReason::Signal,
+            })
+        })
+        .collect();
+
+    let signals_up_to = records.iter().map(|record| record.signal_ts).max()?;
+
+    let mut quantities: BTreeMap<Figi, i64> = BTreeMap::default();
+    let mut prices: HashMap<Figi, f64> = HashMap::default();
+    // Cash relative to the start, initial equity is added once known
+    let mut cash = 0.0;
+    let mut max_position_value: f64 = 0.0;
+    let mut equity_curve = Vec::with_capacity(candles.len());
+    let mut pending = trades.iter().peekable();
+
+    for (ts, pack) in candles {
+        let candle_end = resolution.advance(*ts, 1);
+
+        while let Some(trade) = pending.next_if(|trade| trade.ts < candle_end) {
+            let quantity = signed_quantity(trade);
+
+            cash -= quantity as f64 * trade.price;
+            *quantities.entry(trade.figi.clone()).or_default() += quantity;
+            prices.entry(trade.figi.clone()).or_insert(trade.price);
+        }
+
+        for (figi, candle) in pack {
+            prices.insert(figi.clone(), candle.close);
+        }
+
+        let (value, gross_value) =
+            quantities
+                .iter()
+                .fold((0.0, 0.0), |(value, gross), (figi, quantity)| {
+                    let price = prices.get(figi).copied().unwrap_or_default();
+                    (
+                        value + *quantity as f64 * price,
+                        gross + quantity.abs() as f64 * price,
+                    )
+                });
+
+        max_position_value = max_position_value.max(gross_value);
+
+        equity_curve.push(EquityPoint {
+            ts: candle_end,
+            cash,
+            equity: cash + value,
+        });
+    }
+
+    if max_position_value <= 0.0 {
+        return None;
+    }
+
+    for point in &mut equity_curve {
+        point.cash += max_position_value;
+        point.equity += max_position_value;
+    }
+
+    let positions = quantities
+        .into_iter()
+        .filter_map(|(figi, quantity)| {
+            let lot = instruments.get(&figi)?.lot;
+            Some((figi, quantity / lot))
+        })
+        .collect();
+
+    let result = BacktestResult {
+        generation: 0,
+        signals_up_to,
+        trades,
+        equity_curve,
+        positions,
+        cash: cash + max_position_value,
+        income: vec![],
+    };
+
+    Some((max_position_value, result))
+}
+
+/// Computes performance metrics of a backtest started with `initial_equity`
+pub fn build_report(
+    source: ReportSource,
+    initial_equity: f64,
+    result: &BacktestResult,
+) -> Option<PerformanceReport> {
+    let first = result.equity_curve.first()?;
+    let last = result.equity_curve.last()?;
+
+    let equity: Vec<f64> = std::iter::once(initial_equity)
+        .chain(result.equity_curve.iter().map(|point| point.equity))
+        .collect();
+
+    let returns: Vec<f64> = equity
+        .windows(2)
+        .map(|pair| match pair[0] > 0.0 {
+            true => pair[1] / pair[0] - 1.0,
+            false => 0.0,
+        })
+        .collect();
+
+    let total_return = last.equity / initial_equity - 1.0;
+    let years = days(last.ts - first.ts) / DAYS_PER_YEAR;
+
+    let annualised_return = match years > 0.0 && total_return > -1.0 {
+        true => Some((1.0 + total_return).powf(1.0 / years) - 1.0),
+        false => None,
+    };
+
+    // Estimated from data since candles are only produced during trading hours
+    let periods_per_year = match years > 0.0 {
+        true => Some((result.equity_curve.len() - 1) as f64 / years),
+        false => None,
+    };
+
+    let std_dev = std_dev(&returns);
+    let mean_return = mean(&returns);
+
+    let volatility = match (std_dev, periods_per_year) {
+        (Some(std_dev), Some(periods)) => Some(std_dev * periods.sqrt()),
+        _ => None,
+    };
+
+    let sharpe = match (std_dev, periods_per_year) {
+        (Some(std_dev), Some(periods)) if std_dev > 0.0 => {
+            Some(mean_return / std_dev * periods.sqrt())
+        }
+        _ => None,
+    };
+
+    let downside_deviation = (returns
+        .iter()
+        .map(|value| value.min(0.0).powi(2))
+        .sum::<f64>()
+        / returns.len() as f64)
+        .sqrt();
+
+    let sortino = match periods_per_year {
+        Some(periods) if downside_deviation > 0.0 => {
+            Some(mean_return / downside_deviation * periods.sqrt())
+        }
+        _ => None,
+    };
+
+    let (max_drawdown, max_drawdown_duration_days) =
+        max_drawdown(initial_equity, &result.equity_curve);
+
+    let profits = closed_trade_profits(&result.trades);
+    let gross_profit: f64 = profits.iter().filter(|profit| **profit > 0.0).sum();
+    let gross_loss: f64 = -profits.iter().filter(|profit| **profit < 0.0).sum::<f64>();
+
+    let win_rate = match profits.is_empty() {
+        true => None,
+        false => {
+            let wins = profits.iter().filter(|profit| **profit > 0.0).count();
+            Some(wins as f64 / profits.len() as f64)
+        }
+    };
+
+    let profit_factor = match gross_loss > 0.0 {
+        true => Some(gross_profit / gross_loss),
+        false => None,
+    };
+
+    let exposure = exposure(&result.trades, &result.equity_curve);
+
+    let traded_value: f64 = result
+        .trades
+        .iter()
+        .map(|trade| trade.price * trade.quantity as f64)
+        .sum();
+
+    let average_equity = mean(&equity[1..]);
+    let turnover = match average_equity > 0.0 {
+        true => traded_value / average_equity,
+        false => 0.0,
+    };
+
+    Some(PerformanceReport {
+        source,
+        time_from: first.ts,
+        time_to: last.ts,
+        initial_equity,
+        final_equity: last.equity,
+        total_return,
+        annualised_return,
+        volatility,
+        sharpe,
+        sortino,
+        max_drawdown,
+        max_drawdown_duration_days,
+        trades: result.trades.len(),
+        closed_trades: profits.len(),
+        win_rate,
+        profit_factor,
+        exposure,
+        turnover,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::models::account::AccountId;
+    use crate::models::market_data::Candle;
+    use crate::models::orders::{OrderStatus, PostedOrder};
+
+    fn equity_point(day: u32, cash: f64, equity: f64) -> EquityPoint {
+        EquityPoint {
+            ts: Utc.with_ymd_and_hms(2022, 1, day, 0, 0, 0).unwrap(),
+            cash,
+            equity,
+        }
+    }
+
+    fn trade(day: u32, direction: OrderDirection, quantity: i64, price: f64) -> BacktestTrade {
+        BacktestTrade {
+            ts: Utc.with_ymd_and_hms(2022, 1, day, 0, 0, 0).unwrap(),
+            figi: Figi("FIGI".to_owned()),
+            direction,
+            lots: quantity,
+            quantity,
+            price,
+            commission: 0.0,
+            reason: TradeReason::Signal,
+        }
+    }
+
+    fn assert_close(actual: Option<f64>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!(
+            (actual - expected).abs() < 1e-6,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_build_report() {
+        let result = BacktestResult {
+            generation: 0,
+            signals_up_to: Utc.with_ymd_and_hms(2022, 1, 4, 0, 0, 0).unwrap(),
+            trades: vec![
+                trade(1, OrderDirection::Buy, 10, 10.0),
+                trade(2, OrderDirection::Sell, 5, 12.0),
+                trade(3, OrderDirection::Sell, 5, 9.0),
+            ],
+            equity_curve: vec![
+                equity_point(1, 0.0, 110.0),
+                equity_point(2, 60.0, 99.0),
+                equity_point(3, 121.0, 121.0),
+                equity_point(4, 121.0, 121.0),
+            ],
+            positions: Default::default(),
+            cash: 121.0,
+            income: vec![],
+        };
+
+        let report = build_report(ReportSource::Backtest, 100.0, &result).unwrap();
+
+        assert_close(Some(report.total_return), 0.21);
+        assert_close(report.annualised_return, 1.21f64.powf(365.25 / 3.0) - 1.0);
+        assert_close(report.volatility, 2.6351962468);
+        assert_close(report.sharpe, 7.7002487732);
+        assert_close(report.sortino, 21.2350165040);
+        assert_close(Some(report.max_drawdown), 0.1);
+        assert_close(Some(report.max_drawdown_duration_days), 2.0);
+
+        assert_eq!(report.closed_trades, 2);
+        assert_close(report.win_rate, 0.5);
+        assert_close(report.profit_factor, 2.0);
+        assert_close(Some(report.exposure), 0.5);
+        assert_close(Some(report.turnover), 205.0 / 112.75);
+    }
+
+    #[test]
+    fn test_exposure_ignores_receivables() {
+        let trades = vec![
+            trade(1, OrderDirection::Buy, 10, 10.0),
+            trade(2, OrderDirection::Sell, 10, 10.0),
+        ];
+
+        // Dividend is receivable on day 3 after the position is closed
+        let curve = vec![
+            equity_point(1, 0.0, 100.0),
+            equity_point(2, 100.0, 105.0),
+            equity_point(3, 100.0, 105.0),
+            equity_point(4, 105.0, 105.0),
+        ];
+
+        assert_close(Some(exposure(&trades, &curve)), 0.25);
+    }
+
+    fn order_record(day: u32, direction: OrderDirection, lots: i64, amount: f64) -> OrderRecord {
+        let ts = Utc.with_ymd_and_hms(2022, 1, day, 10, 0, 0).unwrap();
+
+        OrderRecord {
+            ts,
+            signal_ts: ts,
+            account_id: AccountId("ACCOUNT".to_owned()),
+            figi: Figi("FIGI".to_owned()),
+            direction,
+            lots,
+            order: PostedOrder {
+                order_id: format!("order-{}", day),
+                status: match amount > 0.0 {
+                    true => OrderStatus::Filled,
+                    false => OrderStatus::Rejected,
+                },
+                lots_requested: lots,
+                lots_executed: match amount > 0.0 {
+                    true => lots,
+                    false => 0,
+                },
+                executed_order_price: Some(amount),
+            },
+        }
+    }
+
+    #[test]
+    fn test_replay_orders() {
+        let figi = Figi("FIGI".to_owned());
+
+        let instruments: HashMap<_, _> = [(
+            figi.clone(),
+            Instrument {
+                figi: figi.clone(),
+                lot: 10,
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect();
+
+        let candles: BTreeMap<_, _> = [10.0, 12.0, 11.0, 11.0]
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let candle = Candle {
+                    open: *close,
+                    high: *close,
+                    low: *close,
+                    close: *close,
+                    volume: 100,
+                };
+
+                let ts = Utc
+                    .with_ymd_and_hms(2022, 1, i as u32 + 1, 0, 0, 0)
+                    .unwrap();
+                let pack: CandlePack = [(figi.clone(), candle)].into_iter().collect();
+
+                (ts, pack)
+            })
+            .collect();
+
+        let records = vec![
+            order_record(1, OrderDirection::Buy, 1, 100.0),
+            order_record(2, OrderDirection::Buy, 1, 0.0),
+            order_record(3, OrderDirection::Sell, 1, 110.0),
+        ];
+
+        let (initial_equity, result) =
+            replay_orders(&records, &instruments, CandleResolution::OneDay, &candles).unwrap();
+
+        // Position is worth most on day 2 close
+        assert_close(Some(initial_equity), 120.0);
+        assert_eq!(result.trades.len(), 2);
+        assert_close(Some(result.trades[1].price), 11.0);
+        assert_close(Some(result.cash), 130.0);
+
+        let equity: Vec<_> = result
+            .equity_curve
+            .iter()
+            .map(|point| point.equity)
+            .collect();
+        assert_eq!(equity, vec![120.0, 140.0, 130.0, 130.0]);
+        assert_eq!(
+            result.equity_curve[0].ts,
+            Utc.with_ymd_and_hms(2022, 1, 2, 0, 0, 0).unwrap()
+        );
+
+        let report = build_report(ReportSource::Orders, initial_equity, &result).unwrap();
+        assert_eq!(report.source, ReportSource::Orders);
+        assert_close(Some(report.exposure), 0.5);
+
+        assert!(replay_orders(
+            &records[1..2],
+            &instruments,
+            CandleResolution::OneDay,
+            &candles
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_max_drawdown_not_recovered() {
+        let curve = vec![
+            equity_point(1, 0.0, 100.0),
+            equity_point(2, 0.0, 90.0),
+            equity_point(3, 0.0, 120.0),
+            equity_point(4, 0.0, 60.0),
+            equity_point(6, 0.0, 80.0),
+        ];
+
+        let (drawdown, duration) = max_drawdown(100.0, &curve);
+
+        assert!((drawdown - 0.5).abs() < 1e-9);
+        assert!((duration - 3.0).abs() < 1e-9);
+    }
+}
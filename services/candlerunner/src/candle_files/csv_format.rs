@@ -0,0 +1,60 @@
+use std::io::{Read, Write};
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::market_data::{Candle, CandleTimeline};
+
+/// Row of a candle file: `ts,open,high,low,close,volume` with RFC 3339 timestamps
+#[derive(Serialize, Deserialize)]
+struct CandleRow {
+    ts: DateTime<Utc>,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: u64,
+}
+
+pub fn read_csv(reader: impl Read) -> anyhow::Result<CandleTimeline> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let mut candles = CandleTimeline::new();
+
+    for (line, row) in reader.deserialize::<CandleRow>().enumerate() {
+        // Header takes the first line
+        let row = row.map_err(|err| anyhow::anyhow!("Invalid row {}: {}", line + 2, err))?;
+
+        let candle = Candle {
+            open: row.open,
+            high: row.high,
+            low: row.low,
+            close: row.close,
+            volume: row.volume,
+        };
+
+        if candles.insert(row.ts, candle).is_some() {
+            anyhow::bail!("Duplicate candle at {}", row.ts);
+        }
+    }
+
+    Ok(candles)
+}
+
+pub fn write_csv(writer: impl Write, candles: &CandleTimeline) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+
+    for (ts, candle) in candles {
+        writer.serialize(CandleRow {
+            ts: *ts,
+            open: candle.open,
+            high: candle.high,
+            low: candle.low,
+            close: candle.close,
+            volume: candle.volume,
+        })?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+mod csv_format;
+mod parquet_format;
+mod transfer;
+
+use std::io::Write;
+use std::path::Path;
+
+use bytes::Bytes;
+use serde::Deserialize;
+
+use crate::models::market_data::CandleTimeline;
+
+pub use csv_format::{read_csv, write_csv};
+pub use parquet_format::{read_parquet, write_parquet};
+pub use transfer::{export_candles, import_candles, import_range};
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum CandleFileFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+impl CandleFileFormat {
+    /// Parquet for `.parquet` files, CSV otherwise
+    pub fn of_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("parquet") => Self::Parquet,
+            _ => Self::Csv,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+}
+
+pub fn read_candles(format: CandleFileFormat, data: Bytes) -> anyhow::Result<CandleTimeline> {
+    match format {
+        CandleFileFormat::Csv => read_csv(data.as_ref()),
+        CandleFileFormat::Parquet => read_parquet(data),
+    }
+}
+
+pub fn write_candles(
+    format: CandleFileFormat,
+    writer: impl Write + Send,
+    candles: &CandleTimeline,
+) -> anyhow::Result<()> {
+    match format {
+        CandleFileFormat::Csv => write_csv(writer, candles),
+        CandleFileFormat::Parquet => write_parquet(writer, candles),
+    }
+}
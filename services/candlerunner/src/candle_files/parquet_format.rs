@@ -0,0 +1,265 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use chrono::prelude::*;
+use parquet::basic::{Compression, LogicalType, TimeUnit, Type as PhysicalType};
+use parquet::column::reader::get_typed_column_reader;
+use parquet::data_type::{DataType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::{FileReader, RowGroupReader, SerializedFileReader};
+use parquet::file::writer::{SerializedFileWriter, SerializedRowGroupWriter};
+use parquet::schema::parser::parse_message_type;
+
+use crate::models::market_data::{Candle, CandleTimeline};
+
+/// Schema of written files, columns are the same as in CSV ones
+const SCHEMA: &str = "
+    message candle {
+        required int64 ts (TIMESTAMP(MILLIS, true));
+        required double open;
+        required double high;
+        required double low;
+        required double close;
+        required int64 volume;
+    }
+";
+
+/// Reads `ts,open,high,low,close,volume` columns, other columns are ignored.
+/// `ts` may be a timestamp of any unit, e.g. nanoseconds written by pandas,
+/// and is read as UTC.
+pub fn read_parquet(data: Bytes) -> anyhow::Result<CandleTimeline> {
+    let reader = SerializedFileReader::new(data)?;
+    let schema = reader.metadata().file_metadata().schema_descr_ptr();
+
+    let column = |name: &str, physical_type: PhysicalType| {
+        let index = schema
+            .columns()
+            .iter()
+            .position(|column| column.path().string() == name)
+            .ok_or_else(|| anyhow::anyhow!("Column `{}` is missing", name))?;
+
+        if schema.column(index).physical_type() != physical_type {
+            anyhow::bail!("Column `{}` is expected to be {}", name, physical_type);
+        }
+
+        Ok(index)
+    };
+
+    let ts_column = column("ts", PhysicalType::INT64)?;
+    let price_columns = [
+        column("open", PhysicalType::DOUBLE)?,
+        column("high", PhysicalType::DOUBLE)?,
+        column("low", PhysicalType::DOUBLE)?,
+        column("close", PhysicalType::DOUBLE)?,
+    ];
+    let volume_column = column("volume", PhysicalType::INT64)?;
+
+    let ts_unit = match schema.column(ts_column).logical_type() {
+        Some(LogicalType::Timestamp { unit, .. }) => unit,
+        _ => anyhow::bail!("Column `ts` is expected to be a timestamp"),
+    };
+
+    let mut candles = CandleTimeline::new();
+
+    for i in 0..reader.num_row_groups() {
+        let row_group = reader.get_row_group(i)?;
+        let rows = row_group.metadata().num_rows() as usize;
+
+        let ts = read_column::<Int64Type>(row_group.as_ref(), ts_column, rows)?;
+        let [open, high, low, close] =
+            price_columns.map(|index| read_column::<DoubleType>(row_group.as_ref(), index, rows));
+        let (open, high, low, close) = (open?, high?, low?, close?);
+        let volume = read_column::<Int64Type>(row_group.as_ref(), volume_column, rows)?;
+
+        for row in 0..rows {
+            let ts = match ts_unit {
+                TimeUnit::MILLIS(_) => DateTime::from_timestamp_millis(ts[row]),
+                TimeUnit::MICROS(_) => DateTime::from_timestamp_micros(ts[row]),
+                TimeUnit::NANOS(_) => Some(DateTime::from_timestamp_nanos(ts[row])),
+            }
+            .ok_or_else(|| anyhow::anyhow!("Timestamp {} is out of range", ts[row]))?;
+
+            let candle = Candle {
+                open: open[row],
+                high: high[row],
+                low: low[row],
+                close: close[row],
+                volume: u64::try_from(volume[row])
+                    .map_err(|_| anyhow::anyhow!("Negative volume at {}", ts))?,
+            };
+
+            if candles.insert(ts, candle).is_some() {
+                anyhow::bail!("Duplicate candle at {}", ts);
+            }
+        }
+    }
+
+    Ok(candles)
+}
+
+fn read_column<T: DataType>(
+    row_group: &dyn RowGroupReader,
+    index: usize,
+    rows: usize,
+) -> anyhow::Result<Vec<T::T>> {
+    let name = row_group.metadata().column(index).column_path().string();
+    let mut reader = get_typed_column_reader::<T>(row_group.get_column_reader(index)?);
+    let mut def_levels = Vec::with_capacity(rows);
+    let mut values = Vec::with_capacity(rows);
+
+    let (records, _, _) = reader.read_records(rows, Some(&mut def_levels), None, &mut values)?;
+
+    if records != rows || values.len() != rows {
+        anyhow::bail!("Column `{}` has null values", name);
+    }
+
+    Ok(values)
+}
+
+pub fn write_parquet(writer: impl Write + Send, candles: &CandleTimeline) -> anyhow::Result<()> {
+    let schema = Arc::new(parse_message_type(SCHEMA)?);
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+
+    let mut writer = SerializedFileWriter::new(writer, schema, Arc::new(properties))?;
+    let mut row_group = writer.next_row_group()?;
+
+    let ts: Vec<i64> = candles.keys().map(|ts| ts.timestamp_millis()).collect();
+    write_column::<Int64Type, _>(&mut row_group, &ts)?;
+
+    let prices: [fn(&Candle) -> f64; 4] = [
+        |candle| candle.open,
+        |candle| candle.high,
+        |candle| candle.low,
+        |candle| candle.close,
+    ];
+
+    for price in prices {
+        let values: Vec<f64> = candles.values().map(price).collect();
+        write_column::<DoubleType, _>(&mut row_group, &values)?;
+    }
+
+    let volume: Vec<i64> = candles
+        .values()
+        .map(|candle| candle.volume as i64)
+        .collect();
+    write_column::<Int64Type, _>(&mut row_group, &volume)?;
+
+    row_group.close()?;
+    writer.close()?;
+
+    Ok(())
+}
+
+fn write_column<T: DataType, W: Write + Send>(
+    row_group: &mut SerializedRowGroupWriter<'_, W>,
+    values: &[T::T],
+) -> anyhow::Result<()> {
+    let mut column = row_group
+        .next_column()?
+        .ok_or_else(|| anyhow::anyhow!("Schema has no column left to write"))?;
+
+    column.typed::<T>().write_batch(values, None, None)?;
+    column.close()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(price: f64, volume: u64) -> Candle {
+        Candle {
+            open: price,
+            high: price + 1.0,
+            low: price - 1.0,
+            close: price + 0.5,
+            volume,
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let candles: CandleTimeline = (0..3)
+            .map(|i| {
+                let ts = Utc.with_ymd_and_hms(2022, 1, 3, 10, i, 0).unwrap();
+
+                (ts, candle(100.0 + i as f64, 10 * i as u64))
+            })
+            .collect();
+
+        let mut data = Vec::new();
+        write_parquet(&mut data, &candles).unwrap();
+
+        let read = read_parquet(Bytes::from(data)).unwrap();
+
+        assert_eq!(read.len(), 3);
+
+        for ((ts, candle), (read_ts, read_candle)) in candles.iter().zip(&read) {
+            assert_eq!(ts, read_ts);
+            assert_eq!(candle.open, read_candle.open);
+            assert_eq!(candle.high, read_candle.high);
+            assert_eq!(candle.low, read_candle.low);
+            assert_eq!(candle.close, read_candle.close);
+            assert_eq!(candle.volume, read_candle.volume);
+        }
+    }
+
+    /// Layout pandas writes by default: nanosecond timestamps, optional columns and an index
+    #[test]
+    fn test_pandas_layout() {
+        let schema = Arc::new(
+            parse_message_type(
+                "
+                message schema {
+                    optional double close;
+                    optional double high;
+                    optional double low;
+                    optional double open;
+                    optional int64 ts (TIMESTAMP(NANOS, false));
+                    optional int64 volume;
+                    optional int64 __index_level_0__;
+                }
+                ",
+            )
+            .unwrap(),
+        );
+
+        let ts = Utc.with_ymd_and_hms(2022, 1, 3, 10, 0, 0).unwrap();
+
+        let mut data = Vec::new();
+        let mut writer = SerializedFileWriter::new(&mut data, schema, Default::default()).unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+
+        for value in [101.5, 102.0, 99.0, 100.0] {
+            let mut column = row_group.next_column().unwrap().unwrap();
+            column
+                .typed::<DoubleType>()
+                .write_batch(&[value], Some(&[1]), None)
+                .unwrap();
+            column.close().unwrap();
+        }
+
+        for value in [ts.timestamp_nanos_opt().unwrap(), 42, 0] {
+            let mut column = row_group.next_column().unwrap().unwrap();
+            column
+                .typed::<Int64Type>()
+                .write_batch(&[value], Some(&[1]), None)
+                .unwrap();
+            column.close().unwrap();
+        }
+
+        row_group.close().unwrap();
+        writer.close().unwrap();
+
+        let candles = read_parquet(Bytes::from(data)).unwrap();
+
+        assert_eq!(candles.len(), 1);
+        assert_eq!(candles[&ts].open, 100.0);
+        assert_eq!(candles[&ts].close, 101.5);
+        assert_eq!(candles[&ts].volume, 42);
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use chrono::prelude::*;
+
+use crate::components;
+use crate::models::corporate_actions::PriceAdjustment;
+use crate::models::instruments::Figi;
+use crate::models::market_data::{CandleResolution, CandleTimeline, DataAvailability};
+
+/// Checks candles to import and returns the range they replace.
+/// The range defaults to the midnight of the first candle up to the end of the last one.
+pub fn import_range(
+    resolution: CandleResolution,
+    candles: &CandleTimeline,
+    time_from: Option<DateTime<Utc>>,
+    time_to: Option<DateTime<Utc>>,
+) -> anyhow::Result<(DateTime<Utc>, DateTime<Utc>)> {
+    if !CandleResolution::STORED.contains(&resolution) {
+        anyhow::bail!("Candles of `{}` resolution are not stored", resolution);
+    }
+
+    if let Some(ts) = candles.keys().find(|ts| resolution.align(**ts) != **ts) {
+        anyhow::bail!("Candle at {} is not aligned to `{}`", ts, resolution);
+    }
+
+    let time_from = match (time_from, candles.keys().next()) {
+        (Some(time_from), _) => time_from,
+        (None, Some(first)) => first.date_naive().and_time(NaiveTime::MIN).and_utc(),
+        (None, None) => anyhow::bail!("No candles to import"),
+    };
+
+    let time_to = match (time_to, candles.keys().next_back()) {
+        (Some(time_to), _) => time_to,
+        (None, Some(last)) => resolution.advance(*last, 1),
+        (None, None) => anyhow::bail!("No candles to import"),
+    };
+
+    if time_from >= time_to {
+        anyhow::bail!("Empty import range [{}; {})", time_from, time_to);
+    }
+
+    if candles
+        .keys()
+        .any(|ts| *ts < time_from || resolution.advance(*ts, 1) > time_to)
+    {
+        anyhow::bail!(
+            "Candles do not fit into import range [{}; {})",
+            time_from,
+            time_to
+        );
+    }
+
+    Ok((time_from, time_to))
+}
+
+/// Replaces stored candles in [`time_from`; `time_to`) with `candles`
+/// and marks days covered by the range as available.
+/// The range is expected to be checked with `import_range`.
+pub async fn import_candles(
+    mongo: &components::Mongo,
+    figi: &Figi,
+    resolution: CandleResolution,
+    candles: CandleTimeline,
+    (time_from, time_to): (DateTime<Utc>, DateTime<Utc>),
+) -> anyhow::Result<usize> {
+    let imported = candles.len();
+
+    mongo
+        .delete_candles(figi, resolution, time_from, time_to)
+        .await?;
+    mongo.write_candles(figi, resolution, candles).await?;
+
+    let existing = mongo
+        .read_candle_data_availability(figi, resolution)
+        .await?;
+
+    for (date, availability) in imported_availability(&existing, time_from, time_to) {
+        mongo
+            .write_candle_data_availability(figi, resolution, date, availability)
+            .await?;
+    }
+
+    println!(
+        "Imported {} {} candles of {} for [{}; {})",
+        imported, resolution, figi.0, time_from, time_to
+    );
+
+    Ok(imported)
+}
+
+/// Reads candles of any resolution the same way strategies get them,
+/// optionally adjusted for corporate actions
+pub async fn export_candles(
+    mongo: &components::Mongo,
+    figi: &Figi,
+    resolution: CandleResolution,
+    time_from: DateTime<Utc>,
+    time_to: DateTime<Utc>,
+    adjustment: PriceAdjustment,
+) -> anyhow::Result<CandleTimeline> {
+    let instrument = mongo
+        .read_instruments()
+        .await?
+        .into_iter()
+        .find(|instrument| &instrument.figi == figi);
+
+    let mut calendars = HashMap::new();
+
+    if let Some(instrument) = instrument {
+        if let Some(calendar) = mongo
+            .read_trading_calendars()
+            .await?
+            .remove(&instrument.exchange)
+        {
+            calendars.insert(figi.clone(), Arc::new(calendar));
+        }
+    }
+
+    let packs = components::read_market_data(
+        mongo,
+        &calendars,
+        std::slice::from_ref(figi),
+        time_from,
+        time_to,
+        resolution,
+        adjustment,
+    )
+    .await?;
+
+    Ok(packs
+        .into_iter()
+        .filter_map(|(ts, mut pack)| Some((ts, pack.remove(figi)?)))
+        .collect())
+}
+
+/// Availability of days after candles in [`time_from`; `time_to`) are imported.
+/// Only days with data known from the midnight are updated; days already
+/// available further than the import reaches are left as is.
+fn imported_availability(
+    existing: &BTreeMap<NaiveDate, DataAvailability>,
+    time_from: DateTime<Utc>,
+    time_to: DateTime<Utc>,
+) -> BTreeMap<NaiveDate, DataAvailability> {
+    let mut result = BTreeMap::new();
+    let mut date = time_from.date_naive();
+
+    while date.and_time(NaiveTime::MIN).and_utc() < time_to {
+        let day_start = date.and_time(NaiveTime::MIN).and_utc();
+        let day_end = date.succ_opt().unwrap().and_time(NaiveTime::MIN).and_utc();
+
+        let known_up_to = match existing.get(&date) {
+            Some(DataAvailability::Available) => day_end,
+            Some(DataAvailability::PartiallyAvailable { available_up_to }) => *available_up_to,
+            _ => day_start,
+        };
+
+        if time_from <= known_up_to && known_up_to < time_to {
+            let availability = if time_to >= day_end {
+                DataAvailability::Available
+            } else {
+                DataAvailability::PartiallyAvailable {
+                    available_up_to: time_to,
+                }
+            };
+
+            result.insert(date, availability);
+        }
+
+        date = date.succ_opt().unwrap();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imported_availability() {
+        let existing = [
+            (
+                NaiveDate::from_ymd_opt(2022, 1, 3).unwrap(),
+                DataAvailability::PartiallyAvailable {
+                    available_up_to: Utc.with_ymd_and_hms(2022, 1, 3, 12, 0, 0).unwrap(),
+                },
+            ),
+            (
+                NaiveDate::from_ymd_opt(2022, 1, 5).unwrap(),
+                DataAvailability::Available,
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let time_from = Utc.with_ymd_and_hms(2022, 1, 3, 11, 0, 0).unwrap();
+        let time_to = Utc.with_ymd_and_hms(2022, 1, 6, 15, 0, 0).unwrap();
+
+        assert_eq!(
+            imported_availability(&existing, time_from, time_to),
+            [
+                (
+                    NaiveDate::from_ymd_opt(2022, 1, 3).unwrap(),
+                    DataAvailability::Available
+                ),
+                (
+                    NaiveDate::from_ymd_opt(2022, 1, 4).unwrap(),
+                    DataAvailability::Available
+                ),
+                (
+                    NaiveDate::from_ymd_opt(2022, 1, 5).unwrap(),
+                    DataAvailability::Available
+                ),
+                (
+                    NaiveDate::from_ymd_opt(2022, 1, 6).unwrap(),
+                    DataAvailability::PartiallyAvailable {
+                        available_up_to: time_to
+                    }
+                ),
+            ]
+            .into_iter()
+            .collect()
+        );
+
+        // Data before the middle of the day is unknown
+        assert!(imported_availability(
+            &Default::default(),
+            time_from,
+            Utc.with_ymd_and_hms(2022, 1, 4, 0, 0, 0).unwrap()
+        )
+        .is_empty());
+    }
+}
@@ -34,7 +34,7 @@ impl Periodic for AccountsCachePeriodic {
         })
     }
 
-    fn step(&mut self, state: Arc<Self::State>) -> periodic_component::PeriodicFuture<Self::State> {
+    fn step(&mut self, state: Arc<Self::State>) -> periodic_component::PeriodicFuture<'_, Self::State> {
         Box::pin(async move {
             match self.tinkoff_client.list_accounts().await {
                 Ok(instruments) => {
@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use component_store::prelude::*;
+use periodic_component::{Periodic, PeriodicComponent, PeriodicCreateFuture, PeriodicFuture};
+
+use crate::backtest::Backtest;
+use crate::components;
+use crate::models::corporate_actions::{CorporateActionKind, PriceAdjustment};
+use crate::models::strategy::{Strategy, StrategyInstanceDefinition};
+
+use super::strategy_runner::read_market_data;
+
+pub struct BacktestRunnerPeriodic {
+    strategy_cache: Arc<components::StrategyCache>,
+    instrument_cache: Arc<components::InstrumentCache>,
+    trading_calendar_cache: Arc<components::TradingCalendarCache>,
+    mongo: Arc<components::Mongo>,
+
+    /// Candles backtested between checkpoints
+    chunk_size: i32,
+}
+
+impl ComponentName for BacktestRunnerPeriodic {
+    fn component_name() -> &'static str {
+        "backtest-runner"
+    }
+}
+
+impl Periodic for BacktestRunnerPeriodic {
+    type State = ();
+
+    fn init(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> PeriodicCreateFuture<(Self, Self::State)> {
+        Box::pin(Self::new(resolver, config))
+    }
+
+    fn step(&mut self, prev_state: Arc<Self::State>) -> PeriodicFuture<'_, Self::State> {
+        Box::pin(self.step(prev_state))
+    }
+}
+
+impl BacktestRunnerPeriodic {
+    async fn new(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> Result<(Self, <Self as Periodic>::State), ComponentError> {
+        let strategy_cache = resolver.resolve::<components::StrategyCache>().await?;
+        let instrument_cache = resolver.resolve::<components::InstrumentCache>().await?;
+        let trading_calendar_cache = resolver
+            .resolve::<components::TradingCalendarCache>()
+            .await?;
+        let mongo = resolver.resolve::<components::Mongo>().await?;
+
+        let chunk_size = config.get_u64("chunk_size")?.clamp(1, i32::MAX as u64) as i32;
+
+        Ok((
+            Self {
+                strategy_cache,
+                instrument_cache,
+                trading_calendar_cache,
+                mongo,
+                chunk_size,
+            },
+            <Self as Periodic>::State::default(),
+        ))
+    }
+
+    async fn exec_backtest(
+        &self,
+        strategy_id: &uuid::Uuid,
+        strategy_definition: &StrategyInstanceDefinition,
+        strategy: &dyn Strategy,
+    ) -> anyhow::Result<()> {
+        let (settings, progress) = match self.mongo.read_backtest_settings(strategy_id).await? {
+            Some(backtest) => backtest,
+            None => return Ok(()),
+        };
+
+        let execution = match self.mongo.read_strategy_execution(strategy_id).await? {
+            Some(execution) => execution,
+            None => return Ok(()),
+        };
+
+        let last_signal_ts = match self.mongo.read_last_strategy_state(strategy_id).await? {
+            Some((ts, _)) => ts,
+            None => return Ok(()),
+        };
+
+        // Results are up to date until strategy runner produces new signals or is reset
+        if progress.is_some_and(|progress| progress.is_current(&execution, last_signal_ts)) {
+            return Ok(());
+        }
+
+        let corporate_actions = self
+            .mongo
+            .read_corporate_actions(strategy.data_requirements())
+            .await?;
+
+        let instruments = self.instrument_cache.state();
+
+        // Fills are simulated on split-adjusted prices whatever the instance sees
+        let adjustment = PriceAdjustment {
+            splits: true,
+            ..strategy_definition.price_adjustment()
+        };
+
+        // Backtest of a reset execution or of prices adjusted for other actions is started over
+        let checkpoint = match progress {
+            Some(progress) if progress.generation == execution.generation() => self
+                .mongo
+                .read_backtest_checkpoint(strategy_id)
+                .await?
+                .filter(|backtest| backtest.is_adjusted_for(&corporate_actions, adjustment)),
+            _ => None,
+        };
+        let mut is_resumed = checkpoint.is_some();
+
+        let mut backtest = checkpoint.unwrap_or_else(|| {
+            Backtest::new(settings, &instruments, &corporate_actions, adjustment)
+        });
+
+        let resolution = strategy_definition.resolution();
+
+        let time_from = match backtest.candles_up_to() {
+            Some(ts) => resolution.advance(ts, 1),
+            None => strategy_definition.time_from(),
+        };
+
+        // Candles without signals yet are left for the next run,
+        // so orders placed on the last signal are filled once the runner gets further
+        let time_to = resolution.advance(last_signal_ts, 1);
+
+        if time_from >= time_to {
+            return Ok(());
+        }
+
+        // Dividends already in adjusted prices are not credited to cash once more
+        let income_actions: Vec<_> = corporate_actions
+            .iter()
+            .filter(|action| {
+                !adjustment.dividends
+                    || !matches!(action.kind, CorporateActionKind::Dividend { .. })
+            })
+            .cloned()
+            .collect();
+
+        let calendars = self.trading_calendar_cache.state();
+        let mut chunk_from = time_from;
+
+        // Backtest is checkpointed after every chunk, so a long range is neither read at once
+        // nor started over when the runner is interrupted
+        while chunk_from < time_to {
+            let chunk_to = resolution.advance(chunk_from, self.chunk_size).min(time_to);
+
+            let states = self
+                .mongo
+                .read_strategy_state(strategy_id, chunk_from, Some(chunk_to))
+                .await?;
+
+            let candles = read_market_data(
+                self.mongo.as_ref(),
+                &calendars,
+                strategy.data_requirements(),
+                chunk_from,
+                chunk_to,
+                resolution,
+                adjustment,
+            )
+            .await?;
+
+            backtest.run(
+                strategy_definition.place_order_settings().as_ref(),
+                resolution,
+                &instruments,
+                &candles,
+                &states,
+                &income_actions,
+            );
+
+            // Signals of the chunk only, the backtest is not current until the last one
+            let signals_up_to = last_signal_ts.min(resolution.advance(chunk_to, -1));
+
+            let mut result = backtest.take_result(signals_up_to);
+            result.generation = execution.generation();
+
+            println!(
+                "Backtested strategy {} up to {}: {} new trades, equity {:.2}",
+                strategy_id,
+                signals_up_to,
+                result.trades.len(),
+                result
+                    .equity_curve
+                    .last()
+                    .map(|point| point.equity)
+                    .unwrap_or(result.cash)
+            );
+
+            self.mongo
+                .write_backtest_result(strategy_id, &result, &backtest, is_resumed)
+                .await?;
+
+            is_resumed = true;
+            chunk_from = chunk_to;
+        }
+
+        Ok(())
+    }
+
+    async fn step(
+        &mut self,
+        prev_state: Arc<<Self as Periodic>::State>,
+    ) -> anyhow::Result<Arc<<Self as Periodic>::State>> {
+        let strategies = self.strategy_cache.state();
+
+        for (strategy_id, (def, strategy)) in strategies.iter() {
+            if let Err(err) = self
+                .exec_backtest(strategy_id, def, strategy.as_ref())
+                .await
+            {
+                println!("Failed to backtest strategy {}: {}", strategy_id, err);
+            }
+        }
+
+        Ok(prev_state)
+    }
+}
+
+pub type BacktestRunner = PeriodicComponent<BacktestRunnerPeriodic>;
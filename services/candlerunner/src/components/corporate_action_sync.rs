@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{prelude::*, Duration};
+use component_store::prelude::*;
+use periodic_component::{Periodic, PeriodicComponent};
+
+use crate::components;
+use crate::models::instruments::{Figi, Instrument};
+use crate::models::strategy::required_series;
+
+/// Fetches corporate actions of instruments required by strategy instances
+pub struct CorporateActionSyncPeriodic {
+    data_provider: Arc<components::DataProvider>,
+    instrument_cache: Arc<components::InstrumentCache>,
+    strategy_cache: Arc<components::StrategyCache>,
+    mongo: Arc<components::Mongo>,
+    days_ahead: i64,
+}
+
+impl ComponentName for CorporateActionSyncPeriodic {
+    fn component_name() -> &'static str {
+        "corporate-action-sync"
+    }
+}
+
+impl Periodic for CorporateActionSyncPeriodic {
+    type State = ();
+
+    fn init(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> periodic_component::PeriodicCreateFuture<(Self, Self::State)> {
+        Box::pin(async move {
+            let data_provider = resolver.resolve::<components::DataProvider>().await?;
+            let instrument_cache = resolver.resolve::<components::InstrumentCache>().await?;
+            let strategy_cache = resolver.resolve::<components::StrategyCache>().await?;
+            let mongo = resolver.resolve::<components::Mongo>().await?;
+
+            let days_ahead = config.get_u64("days_ahead")? as i64;
+
+            let periodic = Self {
+                data_provider,
+                instrument_cache,
+                strategy_cache,
+                mongo,
+                days_ahead,
+            };
+
+            Ok((periodic, ()))
+        })
+    }
+
+    fn step(
+        &mut self,
+        state: Arc<Self::State>,
+    ) -> periodic_component::PeriodicFuture<'_, Self::State> {
+        Box::pin(async move {
+            // Actions are fetched since the earliest start of instances requiring the instrument
+            let mut ranges: HashMap<Figi, DateTime<Utc>> = HashMap::new();
+
+            for (def, strategy) in self.strategy_cache.state().values() {
+                for (figi, _) in required_series(strategy.as_ref(), def.resolution()) {
+                    let time_from = ranges.entry(figi).or_insert(def.time_from());
+                    *time_from = (*time_from).min(def.time_from());
+                }
+            }
+
+            // Announced actions are fetched ahead as they are needed for adjustment right away
+            let time_to = Utc::now() + Duration::days(self.days_ahead);
+            let instruments = self.instrument_cache.state();
+
+            for (figi, time_from) in ranges {
+                let instrument = match instruments.get(&figi) {
+                    Some(instrument) => instrument,
+                    None => continue,
+                };
+
+                if let Err(err) = self.sync_instrument(instrument, time_from, time_to).await {
+                    println!("Failed to fetch corporate actions for {}: {}", figi.0, err);
+                }
+            }
+
+            Ok(state)
+        })
+    }
+}
+
+impl CorporateActionSyncPeriodic {
+    async fn sync_instrument(
+        &self,
+        instrument: &Instrument,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        let actions = self
+            .data_provider
+            .market_data()
+            .get_corporate_actions(instrument, from, to)
+            .await?;
+
+        self.mongo.write_corporate_actions(&actions).await
+    }
+}
+
+pub type CorporateActionSync = PeriodicComponent<CorporateActionSyncPeriodic>;
@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use chrono::{prelude::*, Duration};
+use futures::stream::BoxStream;
+
+use component_store::prelude::*;
+
+use crate::components;
+use crate::models::corporate_actions::CorporateAction;
+use crate::models::instruments::{Figi, Instrument};
+use crate::models::market_data::{CandleResolution, CandleTimeline, CandleUpdate};
+use crate::models::trading_calendar::TradingDay;
+
+use super::file_provider::FileProvider;
+
+#[async_trait::async_trait]
+pub trait InstrumentProvider: Sync + Send + 'static {
+    async fn get_instruments(&self) -> anyhow::Result<Vec<Instrument>>;
+}
+
+#[async_trait::async_trait]
+pub trait MarketDataProvider: Sync + Send + 'static {
+    async fn get_candles(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<CandleTimeline>;
+
+    /// Longest time range candles of the resolution are fetched for at once
+    fn max_candles_window(&self, resolution: CandleResolution) -> Duration;
+
+    /// Whether candles of the resolution can be subscribed to
+    fn is_streamable(&self, resolution: CandleResolution) -> bool;
+
+    /// Streams updates of candles being formed for the (instrument, resolution) pairs
+    async fn subscribe_candles(
+        &self,
+        subscriptions: &[(Figi, CandleResolution)],
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<CandleUpdate>>>;
+
+    /// Trading days of every exchange within [`from`; `to`]
+    async fn get_trading_schedules(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> anyhow::Result<HashMap<String, BTreeMap<NaiveDate, TradingDay>>>;
+
+    async fn get_last_prices(&self, figis: &[Figi]) -> anyhow::Result<HashMap<Figi, f64>>;
+
+    /// Corporate actions of the instrument with ex-dates within [`from`; `to`]
+    async fn get_corporate_actions(
+        &self,
+        instrument: &Instrument,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<CorporateAction>>;
+}
+
+/// Source of instruments and market data selected by `backend` config option:
+/// `tinkoff` or `files` serving data from `directory`
+pub struct DataProvider {
+    instruments: Arc<dyn InstrumentProvider>,
+    market_data: Arc<dyn MarketDataProvider>,
+}
+
+impl InitComponent for DataProvider {
+    fn init(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> ComponentFuture<Result<Self, ComponentError>> {
+        Box::pin(DataProvider::new(resolver, config))
+    }
+}
+
+impl ShutdownComponent for DataProvider {}
+
+impl ComponentName for DataProvider {
+    fn component_name() -> &'static str {
+        "data-provider"
+    }
+}
+
+impl Component for DataProvider {}
+
+impl DataProvider {
+    async fn new(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> Result<Self, ComponentError> {
+        match config.get_str("backend")? {
+            "tinkoff" => {
+                let tinkoff_client = resolver.resolve::<components::TinkoffClient>().await?;
+
+                Ok(Self {
+                    instruments: tinkoff_client.clone(),
+                    market_data: tinkoff_client,
+                })
+            }
+            "files" => {
+                let file_provider = Arc::new(FileProvider::new(config.get_str("directory")?));
+
+                Ok(Self {
+                    instruments: file_provider.clone(),
+                    market_data: file_provider,
+                })
+            }
+            backend => Err(ComponentError::InitializationFailed {
+                source: anyhow::anyhow!("Unknown data provider backend `{}`", backend).into(),
+            }),
+        }
+    }
+
+    pub fn instruments(&self) -> &dyn InstrumentProvider {
+        self.instruments.as_ref()
+    }
+
+    pub fn market_data(&self) -> &dyn MarketDataProvider {
+        self.market_data.as_ref()
+    }
+}
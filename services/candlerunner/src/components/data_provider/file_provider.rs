@@ -0,0 +1,155 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use chrono::{prelude::*, Duration};
+use futures::stream::BoxStream;
+
+use crate::candle_files;
+use crate::models::corporate_actions::CorporateAction;
+use crate::models::instruments::{Figi, Instrument};
+use crate::models::market_data::{CandleResolution, CandleTimeline, CandleUpdate};
+use crate::models::trading_calendar::TradingDay;
+
+use super::data_provider::{InstrumentProvider, MarketDataProvider};
+
+const INSTRUMENTS_FILE_NAME: &str = "instruments.json";
+const CANDLES_DIRECTORY_NAME: &str = "candles";
+const CORPORATE_ACTIONS_FILE_NAME: &str = "corporate_actions.json";
+
+/// Serves data from a local directory:
+/// - `instruments.json` with instruments in the format of `/list-instruments`
+/// - `candles/<figi>/<resolution>.csv` with candles in the format of `export-candles`,
+///   e.g. `candles/BBG000B9XRY4/oneMinute.csv`
+/// - optional `corporate_actions.json` with corporate actions of all instruments
+///
+/// Trading schedules are not provided, so every day is treated as a trading one.
+pub struct FileProvider {
+    directory: PathBuf,
+}
+
+impl FileProvider {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Reads all candles of the series, none if the file is missing
+    fn read_candles(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+    ) -> anyhow::Result<Option<CandleTimeline>> {
+        let path = self
+            .directory
+            .join(CANDLES_DIRECTORY_NAME)
+            .join(&figi.0)
+            .join(format!("{}.csv", resolution));
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let candles = candle_files::read_csv(std::fs::File::open(&path)?)
+            .map_err(|err| anyhow::anyhow!("Failed to read {}: {}", path.display(), err))?;
+
+        Ok(Some(candles))
+    }
+}
+
+#[async_trait::async_trait]
+impl InstrumentProvider for FileProvider {
+    async fn get_instruments(&self) -> anyhow::Result<Vec<Instrument>> {
+        let file = std::fs::File::open(self.directory.join(INSTRUMENTS_FILE_NAME))?;
+
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+}
+
+#[async_trait::async_trait]
+impl MarketDataProvider for FileProvider {
+    async fn get_candles(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<CandleTimeline> {
+        let candles = match self.read_candles(figi, resolution)? {
+            Some(candles) => candles,
+            None => return Ok(Default::default()),
+        };
+
+        Ok(candles
+            .range(from..to)
+            .map(|(ts, candle)| (*ts, *candle))
+            .collect())
+    }
+
+    /// Files are read as a whole anyway
+    fn max_candles_window(&self, _: CandleResolution) -> Duration {
+        Duration::days(365)
+    }
+
+    fn is_streamable(&self, _: CandleResolution) -> bool {
+        false
+    }
+
+    async fn subscribe_candles(
+        &self,
+        _: &[(Figi, CandleResolution)],
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<CandleUpdate>>> {
+        Err(anyhow::anyhow!("Candles from files can not be streamed"))
+    }
+
+    async fn get_trading_schedules(
+        &self,
+        _: NaiveDate,
+        _: NaiveDate,
+    ) -> anyhow::Result<HashMap<String, BTreeMap<NaiveDate, TradingDay>>> {
+        Ok(Default::default())
+    }
+
+    /// Closes of the latest stored candles
+    async fn get_last_prices(&self, figis: &[Figi]) -> anyhow::Result<HashMap<Figi, f64>> {
+        let mut prices = HashMap::new();
+
+        for figi in figis {
+            for resolution in CandleResolution::STORED {
+                let last = self
+                    .read_candles(figi, resolution)?
+                    .and_then(|candles| candles.values().next_back().copied());
+
+                if let Some(candle) = last {
+                    prices.insert(figi.clone(), candle.close);
+                    break;
+                }
+            }
+        }
+
+        Ok(prices)
+    }
+
+    async fn get_corporate_actions(
+        &self,
+        instrument: &Instrument,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<CorporateAction>> {
+        let path = self.directory.join(CORPORATE_ACTIONS_FILE_NAME);
+
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+
+        let file = std::fs::File::open(path)?;
+        let actions: Vec<CorporateAction> = serde_json::from_reader(std::io::BufReader::new(file))?;
+
+        Ok(actions
+            .into_iter()
+            .filter(|action| {
+                action.figi == instrument.figi && action.ex_date >= from && action.ex_date <= to
+            })
+            .collect())
+    }
+}
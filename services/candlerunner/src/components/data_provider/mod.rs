@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+mod data_provider;
+mod file_provider;
+
+pub use data_provider::{DataProvider, InstrumentProvider, MarketDataProvider};
@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use chrono::{prelude::*, Duration};
+use component_store::prelude::*;
+use periodic_component::{Periodic, PeriodicComponent};
+
+use crate::components;
+use crate::models::data_quality::{DataQualityReport, DayVerification};
+use crate::models::instruments::Figi;
+use crate::models::market_data::{CandleResolution, DataAvailability};
+use crate::models::strategy::required_series;
+use crate::models::trading_calendar::TradingCalendar;
+
+use super::verify_candles::{verify_candles, VerifySettings};
+
+pub struct DataVerifierPeriodic {
+    mongo: Arc<components::Mongo>,
+    strategy_cache: Arc<components::StrategyCache>,
+    trading_calendar_cache: Arc<components::TradingCalendarCache>,
+    settings: VerifySettings,
+    days_back: i64,
+    requeue_days: bool,
+}
+
+impl ComponentName for DataVerifierPeriodic {
+    fn component_name() -> &'static str {
+        "data-verifier"
+    }
+}
+
+impl Periodic for DataVerifierPeriodic {
+    type State = DataQualityReport;
+
+    fn init(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> periodic_component::PeriodicCreateFuture<(Self, Self::State)> {
+        Box::pin(async move {
+            let mongo = resolver.resolve::<components::Mongo>().await?;
+            let strategy_cache = resolver.resolve::<components::StrategyCache>().await?;
+            let trading_calendar_cache = resolver
+                .resolve::<components::TradingCalendarCache>()
+                .await?;
+
+            let settings = VerifySettings {
+                min_gap_candles: config.get_u64("min_gap_candles")? as usize,
+                min_zero_volume_run: config.get_u64("min_zero_volume_run")? as usize,
+            };
+            let days_back = config.get_u64("days_back")? as i64;
+            let requeue_days = config.get_bool("requeue_days")?;
+
+            let periodic = Self {
+                mongo,
+                strategy_cache,
+                trading_calendar_cache,
+                settings,
+                days_back,
+                requeue_days,
+            };
+
+            Ok((periodic, DataQualityReport::default()))
+        })
+    }
+
+    fn step(&mut self, _: Arc<Self::State>) -> periodic_component::PeriodicFuture<'_, Self::State> {
+        Box::pin(async move {
+            let strategies = self.strategy_cache.state();
+
+            // Series market-data-sync keeps for the strategies
+            let series: HashSet<(Figi, CandleResolution)> = strategies
+                .values()
+                .flat_map(|(def, strategy)| required_series(strategy.as_ref(), def.resolution()))
+                .filter_map(|(figi, resolution)| Some((figi, resolution.sources().next()?)))
+                .collect();
+
+            let calendars = self.trading_calendar_cache.state();
+            let mut report = DataQualityReport::default();
+
+            for (figi, resolution) in series {
+                let calendar = calendars.get(&figi).cloned().unwrap_or_default();
+
+                if let Err(err) = self
+                    .verify_series(&figi, resolution, &calendar, &mut report)
+                    .await
+                {
+                    println!(
+                        "Failed to verify {} candles of {}: {}",
+                        resolution, figi.0, err
+                    );
+                }
+            }
+
+            report.verified_at = Some(Utc::now());
+
+            println!(
+                "Verified {} days of candles, {} with anomalies",
+                report.verified_days,
+                report.days.len()
+            );
+
+            Ok(Arc::new(report))
+        })
+    }
+}
+
+impl DataVerifierPeriodic {
+    /// Verifies days of the series which are marked as available
+    async fn verify_series(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        calendar: &TradingCalendar,
+        report: &mut DataQualityReport,
+    ) -> anyhow::Result<()> {
+        let first_date = Utc::now().date_naive() - Duration::days(self.days_back);
+
+        let availability = self
+            .mongo
+            .read_candle_data_availability(figi, resolution)
+            .await?;
+
+        for (date, day_availability) in availability.range(first_date..) {
+            if !matches!(day_availability, DataAvailability::Available) {
+                continue;
+            }
+
+            let records = self
+                .mongo
+                .read_candle_records(
+                    figi,
+                    resolution,
+                    date.and_time(NaiveTime::MIN).and_utc(),
+                    date.succ_opt().unwrap().and_time(NaiveTime::MIN).and_utc(),
+                )
+                .await?;
+
+            let session = calendar
+                .day(*date)
+                .and_then(|day| Some((day.start_time?, day.end_time?)));
+
+            let anomalies = verify_candles(&records, resolution, session, &self.settings);
+            report.verified_days += 1;
+
+            if anomalies.is_empty() {
+                continue;
+            }
+
+            let requeued =
+                self.requeue_days && anomalies.iter().any(|anomaly| anomaly.requires_refetch());
+
+            if requeued {
+                self.requeue_day(figi, resolution, *date).await?;
+            }
+
+            report.days.push(DayVerification {
+                figi: figi.clone(),
+                resolution,
+                date: *date,
+                candles: records.len(),
+                anomalies,
+                requeued,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Drops stored candles of the day for market-data-sync to fetch them again
+    async fn requeue_day(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        date: NaiveDate,
+    ) -> anyhow::Result<()> {
+        println!(
+            "Requeueing {} candles of {} at {}",
+            resolution, figi.0, date
+        );
+
+        self.mongo
+            .write_candle_data_availability(figi, resolution, date, DataAvailability::Unavailable)
+            .await?;
+
+        self.mongo
+            .delete_candles(
+                figi,
+                resolution,
+                date.and_time(NaiveTime::MIN).and_utc(),
+                date.succ_opt().unwrap().and_time(NaiveTime::MIN).and_utc(),
+            )
+            .await
+    }
+}
+
+pub type DataVerifier = PeriodicComponent<DataVerifierPeriodic>;
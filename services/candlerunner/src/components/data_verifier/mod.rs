@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+mod data_verifier;
+mod verify_candles;
+
+pub use data_verifier::DataVerifier;
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+
+use chrono::prelude::*;
+
+use crate::models::data_quality::CandleAnomaly;
+use crate::models::market_data::{Candle, CandleResolution};
+
+pub struct VerifySettings {
+    /// Shorter runs of missing candles are considered periods without trades
+    pub min_gap_candles: usize,
+
+    /// Shorter runs of zero volume candles are not reported
+    pub min_zero_volume_run: usize,
+}
+
+fn has_invalid_prices(candle: &Candle) -> bool {
+    candle.low <= 0.0
+        || candle.high < candle.low
+        || !(candle.low..=candle.high).contains(&candle.open)
+        || !(candle.low..=candle.high).contains(&candle.close)
+}
+
+/// Checks candles stored for a day. Gaps are searched within the trading `session`
+/// or between the first and the last candle when the session is unknown.
+pub fn verify_candles(
+    records: &[(DateTime<Utc>, Candle)],
+    resolution: CandleResolution,
+    session: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    settings: &VerifySettings,
+) -> Vec<CandleAnomaly> {
+    let mut anomalies = Vec::new();
+
+    let mut candles: BTreeMap<DateTime<Utc>, (usize, &Candle)> = BTreeMap::new();
+
+    for (ts, candle) in records {
+        let entry = candles.entry(*ts).or_insert((0, candle));
+        entry.0 += 1;
+        entry.1 = candle;
+    }
+
+    let mut zero_volume_run: Vec<DateTime<Utc>> = Vec::new();
+
+    let flush_zero_volume_run =
+        |run: &mut Vec<DateTime<Utc>>, anomalies: &mut Vec<CandleAnomaly>| {
+            if run.len() >= settings.min_zero_volume_run {
+                anomalies.push(CandleAnomaly::ZeroVolumeRun {
+                    from: run[0],
+                    to: run[run.len() - 1],
+                    candles: run.len(),
+                });
+            }
+
+            run.clear();
+        };
+
+    for (ts, (copies, candle)) in &candles {
+        if *copies > 1 {
+            anomalies.push(CandleAnomaly::DuplicateTimestamp {
+                ts: *ts,
+                copies: *copies,
+            });
+        }
+
+        if has_invalid_prices(candle) {
+            anomalies.push(CandleAnomaly::InvalidPrices { ts: *ts });
+        }
+
+        match candle.volume {
+            0 => zero_volume_run.push(*ts),
+            _ => flush_zero_volume_run(&mut zero_volume_run, &mut anomalies),
+        }
+    }
+
+    flush_zero_volume_run(&mut zero_volume_run, &mut anomalies);
+
+    let session = session.or_else(|| {
+        let first = *candles.keys().next()?;
+        let last = *candles.keys().next_back()?;
+        Some((first, resolution.advance(last, 1)))
+    });
+
+    let (session_start, session_end) = match session {
+        Some(session) => session,
+        None => return anomalies,
+    };
+
+    let mut gap: Option<(DateTime<Utc>, usize)> = None;
+    let mut ts = resolution.align(session_start);
+
+    loop {
+        let is_over = ts >= session_end;
+
+        if !is_over && !candles.contains_key(&ts) {
+            let (_, missing) = gap.get_or_insert((ts, 0));
+            *missing += 1;
+        } else if let Some((from, missing)) = gap.take() {
+            if missing >= settings.min_gap_candles {
+                anomalies.push(CandleAnomaly::Gap {
+                    from,
+                    to: ts.min(session_end),
+                    missing_candles: missing,
+                });
+            }
+        }
+
+        if is_over {
+            break;
+        }
+
+        ts = resolution.advance(ts, 1);
+    }
+
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(close: f64, volume: u64) -> Candle {
+        Candle {
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume,
+        }
+    }
+
+    #[test]
+    fn test_verify_candles() {
+        let ts = |minute: u32| Utc.with_ymd_and_hms(2022, 1, 3, 10, minute, 0).unwrap();
+
+        let records = vec![
+            (ts(0), candle(10.0, 5)),
+            (ts(1), candle(10.0, 5)),
+            (ts(1), candle(10.0, 5)),
+            (
+                ts(2),
+                Candle {
+                    high: 9.0,
+                    ..candle(10.0, 5)
+                },
+            ),
+            (ts(6), candle(10.0, 0)),
+            (ts(7), candle(10.0, 0)),
+            (ts(8), candle(10.0, 5)),
+        ];
+
+        let settings = VerifySettings {
+            min_gap_candles: 2,
+            min_zero_volume_run: 2,
+        };
+
+        assert_eq!(
+            verify_candles(
+                &records,
+                CandleResolution::OneMinute,
+                Some((ts(0), ts(12))),
+                &settings
+            ),
+            vec![
+                CandleAnomaly::DuplicateTimestamp {
+                    ts: ts(1),
+                    copies: 2
+                },
+                CandleAnomaly::InvalidPrices { ts: ts(2) },
+                CandleAnomaly::ZeroVolumeRun {
+                    from: ts(6),
+                    to: ts(7),
+                    candles: 2
+                },
+                CandleAnomaly::Gap {
+                    from: ts(3),
+                    to: ts(6),
+                    missing_candles: 3
+                },
+                CandleAnomaly::Gap {
+                    from: ts(9),
+                    to: ts(12),
+                    missing_candles: 3
+                },
+            ]
+        );
+
+        // Without session only gaps between stored candles are found
+        assert_eq!(
+            verify_candles(&records, CandleResolution::OneMinute, None, &settings).len(),
+            4
+        );
+    }
+}
@@ -7,6 +7,8 @@ use periodic_component::{Periodic, PeriodicComponent, PeriodicCreateFuture, Peri
 use crate::components;
 use crate::models::instruments::{Figi, Instrument};
 
+use super::instrument_index::InstrumentIndex;
+
 pub struct InstrumentCachePeriodic {
     mongo: Arc<components::Mongo>,
 }
@@ -18,7 +20,7 @@ impl ComponentName for InstrumentCachePeriodic {
 }
 
 impl Periodic for InstrumentCachePeriodic {
-    type State = HashMap<Figi, Instrument>;
+    type State = InstrumentIndex;
 
     fn init(
         resolver: ComponentResolver,
@@ -38,16 +40,17 @@ impl Periodic for InstrumentCachePeriodic {
         })
     }
 
-    fn step(&mut self, _: Arc<Self::State>) -> PeriodicFuture<Self::State> {
+    fn step(&mut self, _: Arc<Self::State>) -> PeriodicFuture<'_, Self::State> {
         Box::pin(async move {
-            let instruments: HashMap<_, _> = self.mongo
+            let instruments: HashMap<Figi, Instrument> = self
+                .mongo
                 .read_instruments()
                 .await?
                 .into_iter()
                 .map(|instrument| (instrument.figi.clone(), instrument))
                 .collect();
 
-            Ok(Arc::new(instruments))
+            Ok(Arc::new(InstrumentIndex::new(instruments)))
         })
     }
 }
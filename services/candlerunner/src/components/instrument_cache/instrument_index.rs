@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::instruments::{Figi, Instrument, InstrumentType};
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+
+/// Instrument search request. Filters are exact, `query` is matched
+/// against ticker, name and FIGI
+#[derive(Deserialize, Debug, Default)]
+pub struct InstrumentQuery {
+    /// Every instrument passing the filters matches an empty query
+    #[serde(default)]
+    pub query: String,
+    pub instrument_type: Option<InstrumentType>,
+    pub currency: Option<String>,
+    pub exchange: Option<String>,
+
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct InstrumentSearchResult<'a> {
+    /// Number of matching instruments before pagination
+    pub total: usize,
+    pub instruments: Vec<&'a Instrument>,
+}
+
+/// How well an instrument matches the query, better matches go first
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+enum Rank {
+    Exact,
+    TickerPrefix,
+    NamePrefix,
+    WordPrefix,
+    Substring,
+    Subsequence,
+}
+
+/// Search keys of an instrument, normalized once per cache refresh
+struct IndexEntry {
+    figi: Figi,
+    ticker: String,
+    name: String,
+    figi_key: String,
+}
+
+impl IndexEntry {
+    fn rank(&self, needle: &str) -> Option<Rank> {
+        if needle.is_empty()
+            || self.ticker == needle
+            || self.name == needle
+            || self.figi_key == needle
+        {
+            Some(Rank::Exact)
+        } else if self.ticker.starts_with(needle) || self.figi_key.starts_with(needle) {
+            Some(Rank::TickerPrefix)
+        } else if self.name.starts_with(needle) {
+            Some(Rank::NamePrefix)
+        } else if self
+            .name
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| word.starts_with(needle))
+        {
+            Some(Rank::WordPrefix)
+        } else if self.ticker.contains(needle) || self.name.contains(needle) {
+            Some(Rank::Substring)
+        } else if is_subsequence(needle, &self.ticker) || is_subsequence(needle, &self.name) {
+            Some(Rank::Subsequence)
+        } else {
+            None
+        }
+    }
+}
+
+/// Instruments by FIGI along with search keys. Dereferences to the map,
+/// so lookups by FIGI work the same as on a plain map.
+#[derive(Default)]
+pub struct InstrumentIndex {
+    instruments: HashMap<Figi, Instrument>,
+
+    /// Ordered by ticker, equally ranked matches keep this order
+    entries: Vec<IndexEntry>,
+}
+
+impl InstrumentIndex {
+    pub fn new(instruments: HashMap<Figi, Instrument>) -> Self {
+        let mut entries: Vec<_> = instruments
+            .values()
+            .map(|instrument| IndexEntry {
+                figi: instrument.figi.clone(),
+                ticker: normalize(&instrument.ticker.0),
+                name: normalize(&instrument.display_name),
+                figi_key: normalize(&instrument.figi.0),
+            })
+            .collect();
+
+        entries.sort_by(|lhs, rhs| {
+            lhs.ticker
+                .cmp(&rhs.ticker)
+                .then_with(|| lhs.figi.cmp(&rhs.figi))
+        });
+
+        Self {
+            instruments,
+            entries,
+        }
+    }
+
+    pub fn search(&self, query: &InstrumentQuery) -> InstrumentSearchResult<'_> {
+        let needle = normalize(&query.query);
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+        let mut matches: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let instrument = self.instruments.get(&entry.figi)?;
+
+                if !matches_filters(instrument, query) {
+                    return None;
+                }
+
+                entry.rank(&needle).map(|rank| (rank, instrument))
+            })
+            .collect();
+
+        // Sort is stable, so ticker order is kept within a rank
+        matches.sort_by_key(|(rank, _)| *rank);
+
+        InstrumentSearchResult {
+            total: matches.len(),
+            instruments: matches
+                .into_iter()
+                .skip(query.offset)
+                .take(limit)
+                .map(|(_, instrument)| instrument)
+                .collect(),
+        }
+    }
+}
+
+impl Deref for InstrumentIndex {
+    type Target = HashMap<Figi, Instrument>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.instruments
+    }
+}
+
+fn normalize(value: &str) -> String {
+    value.trim().to_lowercase().replace('ё', "е")
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+fn matches_filters(instrument: &Instrument, query: &InstrumentQuery) -> bool {
+    query
+        .instrument_type
+        .is_none_or(|instrument_type| instrument.instrument_type == instrument_type)
+        && query
+            .currency
+            .as_ref()
+            .is_none_or(|currency| instrument.currency.eq_ignore_ascii_case(currency))
+        && query
+            .exchange
+            .as_ref()
+            .is_none_or(|exchange| instrument.exchange.eq_ignore_ascii_case(exchange))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::models::instruments::Ticker;
+
+    fn instrument(
+        figi: &str,
+        ticker: &str,
+        name: &str,
+        instrument_type: InstrumentType,
+    ) -> Instrument {
+        Instrument {
+            figi: Figi(figi.to_owned()),
+            ticker: Ticker(ticker.to_owned()),
+            display_name: name.to_owned(),
+            instrument_type,
+            currency: "rub".to_owned(),
+            ..Default::default()
+        }
+    }
+
+    fn search(index: &InstrumentIndex, query: &str) -> Vec<String> {
+        let query = InstrumentQuery {
+            query: query.to_owned(),
+            ..Default::default()
+        };
+
+        index
+            .search(&query)
+            .instruments
+            .into_iter()
+            .map(|instrument| instrument.ticker.0.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_search() {
+        let index = InstrumentIndex::new(
+            [
+                instrument("BBG004730N88", "SBER", "Сбер Банк", InstrumentType::Share),
+                instrument(
+                    "BBG0047315Y7",
+                    "SBERP",
+                    "Сбер Банк - привилегированные акции",
+                    InstrumentType::Share,
+                ),
+                instrument("BBG004S681W1", "MTSS", "МТС", InstrumentType::Share),
+                instrument(
+                    "TCS00A0JQXL5",
+                    "SU26214RMFS5",
+                    "ОФЗ 26214",
+                    InstrumentType::Bond,
+                ),
+                instrument("BBG00QPYJ5H0", "TCSG", "TCS Group", InstrumentType::Share),
+            ]
+            .into_iter()
+            .map(|instrument| (instrument.figi.clone(), instrument))
+            .collect(),
+        );
+
+        assert_eq!(search(&index, "sber"), ["SBER", "SBERP"]);
+        assert_eq!(search(&index, "привилег"), ["SBERP"]);
+        assert_eq!(search(&index, "bbg004s"), ["MTSS"]);
+        assert_eq!(search(&index, "group"), ["TCSG"]);
+        assert_eq!(search(&index, "sbrp"), ["SBERP"]);
+        assert!(search(&index, "gazp").is_empty());
+
+        // Ticker prefix goes before substring matches
+        assert_eq!(search(&index, "s")[..2], ["SBER", "SBERP"]);
+
+        let bonds = index.search(&InstrumentQuery {
+            instrument_type: Some(InstrumentType::Bond),
+            ..Default::default()
+        });
+        assert_eq!(bonds.total, 1);
+        assert_eq!(bonds.instruments[0].ticker.0, "SU26214RMFS5");
+
+        let page = index.search(&InstrumentQuery {
+            offset: 1,
+            limit: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(page.total, 5);
+        assert_eq!(
+            page.instruments
+                .iter()
+                .map(|instrument| instrument.ticker.0.as_str())
+                .collect::<Vec<_>>(),
+            ["SBER", "SBERP"]
+        );
+
+        assert!(index.contains_key(&Figi("BBG004730N88".to_owned())));
+    }
+}
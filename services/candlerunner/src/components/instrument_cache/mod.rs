@@ -0,0 +1,6 @@
+#[allow(clippy::module_inception)]
+mod instrument_cache;
+mod instrument_index;
+
+pub use instrument_cache::InstrumentCache;
+pub use instrument_index::InstrumentQuery;
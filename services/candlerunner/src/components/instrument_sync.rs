@@ -6,7 +6,7 @@ use periodic_component::{Periodic, PeriodicComponent};
 use crate::components;
 
 pub struct InstrumentSyncPeriodic {
-    tinkoff_client: Arc<components::TinkoffClient>,
+    data_provider: Arc<components::DataProvider>,
     mongo: Arc<components::Mongo>,
 }
 
@@ -24,11 +24,11 @@ impl Periodic for InstrumentSyncPeriodic {
         _: Box<dyn ConfigProvider>,
     ) -> periodic_component::PeriodicCreateFuture<(Self, Self::State)> {
         Box::pin(async move {
-            let tinkoff_client = resolver.resolve::<components::TinkoffClient>().await?;
+            let data_provider = resolver.resolve::<components::DataProvider>().await?;
             let mongo = resolver.resolve::<components::Mongo>().await?;
 
             let periodic = Self {
-                tinkoff_client,
+                data_provider,
                 mongo,
             };
 
@@ -36,9 +36,9 @@ impl Periodic for InstrumentSyncPeriodic {
         })
     }
 
-    fn step(&mut self, state: Arc<Self::State>) -> periodic_component::PeriodicFuture<Self::State> {
+    fn step(&mut self, state: Arc<Self::State>) -> periodic_component::PeriodicFuture<'_, Self::State> {
         Box::pin(async move {
-            let instruments = self.tinkoff_client.get_instruments().await?;
+            let instruments = self.data_provider.instruments().get_instruments().await?;
             self.mongo.write_instruments(instruments).await?;
 
             Ok(state)
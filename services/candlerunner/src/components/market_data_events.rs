@@ -0,0 +1,70 @@
+use chrono::prelude::*;
+use component_store::prelude::*;
+use tokio::sync::broadcast;
+
+use crate::models::instruments::Figi;
+use crate::models::market_data::CandleResolution;
+
+/// Candles of the instrument are stored at `resolution` up to `available_up_to`
+#[derive(Debug, Clone)]
+pub struct DataAvailable {
+    pub figi: Figi,
+    pub resolution: CandleResolution,
+    pub available_up_to: DateTime<Utc>,
+}
+
+/// Broadcasts stored market data to components reacting on it.
+/// Subscribers falling behind by more than `capacity` events miss the oldest ones,
+/// so they should poll periodically as well.
+pub struct MarketDataEvents {
+    sender: broadcast::Sender<DataAvailable>,
+}
+
+impl MarketDataEvents {
+    pub fn publish(
+        &self,
+        figi: Figi,
+        resolution: CandleResolution,
+        available_up_to: DateTime<Utc>,
+    ) {
+        // Nobody may be subscribed yet, which is fine
+        let _ = self.sender.send(DataAvailable {
+            figi,
+            resolution,
+            available_up_to,
+        });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DataAvailable> {
+        self.sender.subscribe()
+    }
+
+    async fn new(
+        _: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> Result<Self, ComponentError> {
+        let capacity = config.get_u64("capacity")?.max(1) as usize;
+        let (sender, _) = broadcast::channel(capacity);
+
+        Ok(Self { sender })
+    }
+}
+
+impl InitComponent for MarketDataEvents {
+    fn init(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> ComponentFuture<Result<Self, ComponentError>> {
+        Box::pin(Self::new(resolver, config))
+    }
+}
+
+impl ShutdownComponent for MarketDataEvents {}
+
+impl ComponentName for MarketDataEvents {
+    fn component_name() -> &'static str {
+        "market-data-events"
+    }
+}
+
+impl Component for MarketDataEvents {}
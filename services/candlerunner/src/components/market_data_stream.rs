@@ -0,0 +1,344 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{prelude::*, Duration};
+use component_store::prelude::*;
+use futures::{FutureExt, StreamExt};
+use periodic_component::{Periodic, PeriodicComponent};
+use tokio::task::JoinHandle;
+
+use crate::components;
+use crate::models::instruments::Figi;
+use crate::models::market_data::{CandleResolution, CandleUpdate, DataAvailability};
+use crate::models::strategy::required_series;
+
+/// Candle is considered closed once this many seconds passed after its end
+/// unless a newer candle of the instrument arrived earlier
+const CANDLE_CLOSE_DELAY_SECONDS: i64 = 5;
+
+/// How often candles are checked to be closed
+const CLOSE_CHECK_PERIOD: std::time::Duration = std::time::Duration::from_secs(1);
+
+type Subscription = (Figi, CandleResolution);
+
+/// Receives candles from market data stream and stores the closed ones
+struct CandleListener {
+    data_provider: Arc<components::DataProvider>,
+    mongo: Arc<components::Mongo>,
+    market_data_events: Arc<components::MarketDataEvents>,
+}
+
+impl CandleListener {
+    async fn run(&self, subscriptions: Vec<Subscription>) -> anyhow::Result<()> {
+        let stream = self
+            .data_provider
+            .market_data()
+            .subscribe_candles(&subscriptions)
+            .await?;
+        futures::pin_mut!(stream);
+
+        // Candles being formed
+        let mut pending: HashMap<Subscription, CandleUpdate> = Default::default();
+        // End of the last closed candle
+        let mut closed_up_to: HashMap<Subscription, DateTime<Utc>> = Default::default();
+        // Start of the first streamed candle. No candles are missing after it.
+        let mut streamed_since: HashMap<Subscription, DateTime<Utc>> = Default::default();
+
+        let mut close_check = tokio::time::interval(CLOSE_CHECK_PERIOD);
+
+        loop {
+            let mut closed: Vec<CandleUpdate> = Vec::new();
+
+            tokio::select! {
+                update = stream.next() => {
+                    let update = match update {
+                        Some(update) => update?,
+                        None => return Err(anyhow::anyhow!("Market data stream was closed")),
+                    };
+
+                    let key = (update.figi.clone(), update.resolution);
+
+                    let is_closed = closed_up_to
+                        .get(&key)
+                        .is_some_and(|closed_up_to| update.ts < *closed_up_to);
+
+                    if is_closed {
+                        continue;
+                    }
+
+                    streamed_since.entry(key.clone()).or_insert(update.ts);
+
+                    match pending.get(&key) {
+                        Some(prev) if prev.ts > update.ts => continue,
+                        Some(prev) if prev.ts < update.ts => {
+                            closed.extend(pending.remove(&key));
+                        }
+                        _ => (),
+                    }
+
+                    pending.insert(key, update);
+                }
+                _ = close_check.tick() => {
+                    let now = Utc::now();
+                    let delay = Duration::seconds(CANDLE_CLOSE_DELAY_SECONDS);
+
+                    let keys: Vec<Subscription> = pending
+                        .iter()
+                        .filter(|(_, update)| {
+                            update.resolution.advance(update.ts, 1) + delay <= now
+                        })
+                        .map(|(key, _)| key.clone())
+                        .collect();
+
+                    for key in keys {
+                        closed.extend(pending.remove(&key));
+                    }
+                }
+            }
+
+            if closed.is_empty() {
+                continue;
+            }
+
+            for update in closed {
+                let key = (update.figi.clone(), update.resolution);
+                let candle_end = update.resolution.advance(update.ts, 1);
+                closed_up_to.insert(key.clone(), candle_end);
+
+                let since = streamed_since[&key];
+
+                match self.store_closed_candle(update, since).await {
+                    Ok(()) => self.market_data_events.publish(key.0, key.1, candle_end),
+                    Err(err) => {
+                        println!("Failed to store streamed candle for {}: {}", key.0 .0, err)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes the candle in place of the stored one and moves availability of its day
+    /// to the candle end if the day is available up to the streamed range
+    async fn store_closed_candle(
+        &self,
+        update: CandleUpdate,
+        streamed_since: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        let CandleUpdate {
+            figi,
+            resolution,
+            ts,
+            candle,
+        } = update;
+
+        let date = ts.date_naive();
+        let candle_end = resolution.advance(ts, 1);
+
+        // Replaces the candle market-data-sync stored while it was being formed
+        self.mongo
+            .delete_candles(&figi, resolution, ts, candle_end)
+            .await?;
+        self.mongo
+            .write_candles(&figi, resolution, [(ts, candle)].into_iter().collect())
+            .await?;
+
+        let availability = self
+            .mongo
+            .read_candle_data_day_availability(&figi, resolution, date)
+            .await?;
+
+        if let Some(availability) = streamed_availability(availability, streamed_since, candle_end)
+        {
+            self.mongo
+                .write_candle_data_availability(&figi, resolution, date, availability)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Availability of the day once a streamed candle ending at `candle_end` is stored.
+/// Only a day available up to the streamed range is moved on,
+/// gaps are left for market-data-sync to fill.
+pub fn streamed_availability(
+    availability: DataAvailability,
+    streamed_since: DateTime<Utc>,
+    candle_end: DateTime<Utc>,
+) -> Option<DataAvailability> {
+    match availability {
+        DataAvailability::PartiallyAvailable { available_up_to }
+            if available_up_to >= streamed_since && available_up_to < candle_end =>
+        {
+            Some(DataAvailability::PartiallyAvailable {
+                available_up_to: candle_end,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Coarsest streamable resolution candles of the resolution are built from,
+/// so fewer candles are streamed for 1h and 1d instances.
+/// market-data-sync keeps the current day of it for the stream to carry on.
+pub fn stream_source(
+    resolution: CandleResolution,
+    is_streamable: impl Fn(CandleResolution) -> bool,
+) -> Option<CandleResolution> {
+    resolution.sources().find(|source| is_streamable(*source))
+}
+
+pub struct MarketDataStreamPeriodic {
+    listener: Arc<CandleListener>,
+    strategy_cache: Arc<components::StrategyCache>,
+    subscriptions: HashSet<Subscription>,
+    /// Live series no stream subscription can build
+    unstreamable: HashSet<Subscription>,
+    stream_task: Option<JoinHandle<()>>,
+}
+
+impl ComponentName for MarketDataStreamPeriodic {
+    fn component_name() -> &'static str {
+        "market-data-stream"
+    }
+}
+
+impl Periodic for MarketDataStreamPeriodic {
+    type State = ();
+
+    fn init(
+        resolver: ComponentResolver,
+        _: Box<dyn ConfigProvider>,
+    ) -> periodic_component::PeriodicCreateFuture<(Self, Self::State)> {
+        Box::pin(async move {
+            let data_provider = resolver.resolve::<components::DataProvider>().await?;
+            let mongo = resolver.resolve::<components::Mongo>().await?;
+            let strategy_cache = resolver.resolve::<components::StrategyCache>().await?;
+            let market_data_events = resolver.resolve::<components::MarketDataEvents>().await?;
+
+            let periodic = Self {
+                listener: Arc::new(CandleListener {
+                    data_provider,
+                    mongo,
+                    market_data_events,
+                }),
+                strategy_cache,
+                subscriptions: Default::default(),
+                unstreamable: Default::default(),
+                stream_task: None,
+            };
+
+            Ok((periodic, ()))
+        })
+    }
+
+    fn step(
+        &mut self,
+        state: Arc<Self::State>,
+    ) -> periodic_component::PeriodicFuture<'_, Self::State> {
+        Box::pin(async move {
+            let strategies = self.strategy_cache.state();
+            let market_data = self.listener.data_provider.market_data();
+
+            // Live strategies only, historical data is left for market-data-sync
+            let series: HashSet<Subscription> = strategies
+                .values()
+                .filter(|(def, _)| def.time_to().is_none())
+                .flat_map(|(def, strategy)| required_series(strategy.as_ref(), def.resolution()))
+                .collect();
+
+            let mut subscriptions: HashSet<Subscription> = HashSet::new();
+            let mut unstreamable: HashSet<Subscription> = HashSet::new();
+
+            for (figi, resolution) in series {
+                match stream_source(resolution, |source| market_data.is_streamable(source)) {
+                    Some(source) => subscriptions.insert((figi, source)),
+                    None => unstreamable.insert((figi, resolution)),
+                };
+            }
+
+            // Reported once, the set is recomputed on every step
+            for (figi, resolution) in unstreamable.difference(&self.unstreamable) {
+                println!(
+                    "No streamable candles to build {} candles of {} from, live data is left for market-data-sync",
+                    resolution, figi.0
+                );
+            }
+            self.unstreamable = unstreamable;
+
+            let stream_is_running = self
+                .stream_task
+                .as_mut()
+                .is_some_and(|task| task.now_or_never().is_none());
+
+            if stream_is_running && subscriptions == self.subscriptions {
+                return Ok(state);
+            }
+
+            if let Some(task) = self.stream_task.take() {
+                task.abort();
+            }
+
+            self.subscriptions = subscriptions;
+
+            if self.subscriptions.is_empty() {
+                return Ok(state);
+            }
+
+            println!(
+                "Subscribing to candles of {} instruments",
+                self.subscriptions.len()
+            );
+
+            let listener = self.listener.clone();
+            let subscriptions = self.subscriptions.iter().cloned().collect();
+
+            self.stream_task = Some(tokio::spawn(async move {
+                if let Err(err) = listener.run(subscriptions).await {
+                    println!("Market data stream failed: {}", err);
+                }
+            }));
+
+            Ok(state)
+        })
+    }
+}
+
+impl Drop for MarketDataStreamPeriodic {
+    fn drop(&mut self) {
+        if let Some(task) = self.stream_task.take() {
+            task.abort();
+        }
+    }
+}
+
+pub type MarketDataStream = PeriodicComponent<MarketDataStreamPeriodic>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_source() {
+        let is_streamable = |resolution| {
+            matches!(
+                resolution,
+                CandleResolution::OneMinute | CandleResolution::FiveMinutes
+            )
+        };
+
+        assert_eq!(
+            stream_source(CandleResolution::OneHour, is_streamable),
+            Some(CandleResolution::FiveMinutes)
+        );
+        assert_eq!(
+            stream_source(CandleResolution::OneDay, is_streamable),
+            Some(CandleResolution::FiveMinutes)
+        );
+        assert_eq!(
+            stream_source(CandleResolution::OneMinute, is_streamable),
+            Some(CandleResolution::OneMinute)
+        );
+        assert_eq!(stream_source(CandleResolution::OneHour, |_| false), None);
+    }
+}
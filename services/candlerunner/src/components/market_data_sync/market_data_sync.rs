@@ -1,20 +1,25 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use chrono::prelude::*;
 use component_store::prelude::*;
 use periodic_component::{Periodic, PeriodicComponent};
 
-use crate::components;
+use crate::components::market_data_stream::stream_source;
+use crate::components::{self, MarketDataProvider};
 use crate::models::instruments::Figi;
-use crate::models::market_data::DataAvailability;
+use crate::models::market_data::{CandleResolution, CandleTimeline, DataAvailability};
+use crate::models::strategy::SERIES_LOOKBACK_CANDLES;
+use crate::models::trading_calendar::TradingCalendar;
 
 use super::requirements_collector::{Ranges, RequirementsCollector};
 
 pub struct MarketDataSyncPeriodic {
-    tinkoff_client: Arc<components::TinkoffClient>,
+    data_provider: Arc<components::DataProvider>,
+    market_data_events: Arc<components::MarketDataEvents>,
     mongo: Arc<components::Mongo>,
     strategy_cache: Arc<components::StrategyCache>,
+    trading_calendar_cache: Arc<components::TradingCalendarCache>,
     max_chunks_per_instrument: usize,
 }
 
@@ -32,16 +37,22 @@ impl Periodic for MarketDataSyncPeriodic {
         config: Box<dyn ConfigProvider>,
     ) -> periodic_component::PeriodicCreateFuture<(Self, Self::State)> {
         Box::pin(async move {
-            let tinkoff_client = resolver.resolve::<components::TinkoffClient>().await?;
+            let data_provider = resolver.resolve::<components::DataProvider>().await?;
+            let market_data_events = resolver.resolve::<components::MarketDataEvents>().await?;
             let mongo = resolver.resolve::<components::Mongo>().await?;
             let strategy_cache = resolver.resolve::<components::StrategyCache>().await?;
+            let trading_calendar_cache = resolver
+                .resolve::<components::TradingCalendarCache>()
+                .await?;
 
             let max_chunks_per_instrument = config.get_u64("max_chunks_per_instrument")? as usize;
 
             let periodic = Self {
-                tinkoff_client,
+                data_provider,
+                market_data_events,
                 mongo,
                 strategy_cache,
+                trading_calendar_cache,
                 max_chunks_per_instrument,
             };
 
@@ -55,20 +66,63 @@ impl Periodic for MarketDataSyncPeriodic {
     ) -> periodic_component::PeriodicFuture<'_, Self::State> {
         Box::pin(async move {
             let strategies = self.strategy_cache.state();
+            let market_data = self.data_provider.market_data();
+            let today = Utc::now().date_naive().and_time(NaiveTime::MIN).and_utc();
             let mut collector = RequirementsCollector::default();
 
             for (def, strategy) in strategies.values() {
-                strategy.data_requirements().iter().for_each(|figi| {
-                    collector.push(figi.clone(), def.time_from(), def.time_to());
-                });
+                // Series requirements are read ahead of the instance range by the runner
+                let series = strategy
+                    .series_requirements()
+                    .iter()
+                    .map(|(figi, resolution)| {
+                        let time_from =
+                            resolution.advance(def.time_from(), -SERIES_LOOKBACK_CANDLES);
+                        (figi.clone(), *resolution, time_from)
+                    });
+
+                let requirements = strategy
+                    .data_requirements()
+                    .iter()
+                    .map(|figi| (figi.clone(), def.resolution(), def.time_from()))
+                    .chain(series);
+
+                for (figi, resolution, time_from) in requirements {
+                    // Coarsest series candles of the resolution can be assembled from
+                    let source = match resolution.sources().next() {
+                        Some(source) => source,
+                        None => continue,
+                    };
+
+                    // Streamed candles extend the availability of the current day only,
+                    // so it is fetched for live instances when the stream uses another source
+                    let streamed =
+                        stream_source(resolution, |source| market_data.is_streamable(source))
+                            .filter(|streamed| def.time_to().is_none() && *streamed != source);
+
+                    if let Some(streamed) = streamed {
+                        collector.push(figi.clone(), streamed, time_from.max(today), None);
+                    }
+
+                    collector.push(figi, source, time_from, def.time_to());
+                }
             }
 
             let data_ranges = collector.finalize();
+            let calendars = self.trading_calendar_cache.state();
 
-            for (figi, ranges) in data_ranges {
-                match self.sync_market_data_ranges(&figi, ranges).await {
+            for ((figi, resolution), ranges) in data_ranges {
+                let calendar = calendars.get(&figi).cloned().unwrap_or_default();
+
+                match self
+                    .sync_market_data_ranges(&figi, resolution, &calendar, ranges)
+                    .await
+                {
                     Ok(_) => (),
-                    Err(err) => println!("Failed to retrieve market data for {}: {}", figi.0, err),
+                    Err(err) => println!(
+                        "Failed to retrieve {} market data for {}: {}",
+                        resolution, figi.0, err
+                    ),
                 }
             }
 
@@ -77,79 +131,206 @@ impl Periodic for MarketDataSyncPeriodic {
     }
 }
 
-impl MarketDataSyncPeriodic {
-    async fn sync_candle_data(&self, figi: &Figi, cursor: DateTime<Utc>) -> anyhow::Result<()> {
-        let candles = self
-            .tinkoff_client
-            .get_candles(figi, cursor, cursor.date().and_hms(23, 59, 59))
-            .await?;
+/// Candles fetched for a chunk of days
+struct FetchedCandles {
+    candles: CandleTimeline,
+    availability: BTreeMap<NaiveDate, DataAvailability>,
 
-        let today = Utc::now().date();
+    /// End of the last fetched day, or the last candle when the day is in progress
+    available_up_to: DateTime<Utc>,
+}
 
-        let availability = if cursor.date() == today {
-            let available_up_to = candles.keys().last().cloned().unwrap_or(cursor);
+/// Fetches candles for trading days [`cursor`; `last_date`]
+/// and tells which of the days are complete as of `now`
+async fn fetch_candle_data(
+    market_data: &dyn MarketDataProvider,
+    figi: &Figi,
+    resolution: CandleResolution,
+    calendar: &TradingCalendar,
+    cursor: DateTime<Utc>,
+    last_date: NaiveDate,
+    now: DateTime<Utc>,
+) -> anyhow::Result<FetchedCandles> {
+    let candles = market_data
+        .get_candles(
+            figi,
+            resolution,
+            cursor,
+            last_date.and_hms_opt(23, 59, 59).unwrap().and_utc(),
+        )
+        .await?;
+
+    let today = now.date_naive();
+
+    let mut availability: BTreeMap<NaiveDate, DataAvailability> = Default::default();
+    let mut date = cursor.date_naive();
+
+    while date <= last_date {
+        let day_availability = if calendar.is_closed(date) {
+            DataAvailability::NonTrading
+        } else if date == today && !calendar.is_session_over(date, now) {
+            let available_up_to = candles
+                .range(date.and_time(NaiveTime::MIN).and_utc()..)
+                .map(|(ts, _)| *ts)
+                .next_back()
+                .unwrap_or_else(|| cursor.max(date.and_time(NaiveTime::MIN).and_utc()));
 
             DataAvailability::PartiallyAvailable { available_up_to }
         } else {
             DataAvailability::Available
         };
 
-        self.mongo.write_candles(figi, candles).await?;
+        availability.insert(date, day_availability);
+        date = date.succ_opt().unwrap();
+    }
+
+    let available_up_to = match availability.values().next_back() {
+        Some(DataAvailability::PartiallyAvailable { available_up_to }) => *available_up_to,
+        _ => last_date
+            .succ_opt()
+            .unwrap()
+            .and_time(NaiveTime::MIN)
+            .and_utc(),
+    };
+
+    Ok(FetchedCandles {
+        candles,
+        availability,
+        available_up_to,
+    })
+}
+
+impl MarketDataSyncPeriodic {
+    /// Fetches candles for trading days [`cursor`; `last_date`]
+    async fn sync_candle_data(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        calendar: &TradingCalendar,
+        cursor: DateTime<Utc>,
+        last_date: NaiveDate,
+    ) -> anyhow::Result<()> {
+        let fetched = fetch_candle_data(
+            self.data_provider.market_data(),
+            figi,
+            resolution,
+            calendar,
+            cursor,
+            last_date,
+            Utc::now(),
+        )
+        .await?;
+
+        // Candles fetched earlier are replaced, the ones being formed are fetched again
+        if let Some(last_ts) = fetched.candles.keys().next_back() {
+            self.mongo
+                .delete_candles(figi, resolution, cursor, resolution.advance(*last_ts, 1))
+                .await?;
+        }
+
         self.mongo
-            .write_candle_data_availability(figi, cursor.date(), availability)
+            .write_candles(figi, resolution, fetched.candles)
             .await?;
 
+        for (date, day_availability) in fetched.availability {
+            self.mongo
+                .write_candle_data_availability(figi, resolution, date, day_availability)
+                .await?;
+        }
+
+        self.market_data_events
+            .publish(figi.clone(), resolution, fetched.available_up_to);
+
         Ok(())
     }
 
-    async fn sync_market_data_ranges(&self, figi: &Figi, ranges: Ranges) -> anyhow::Result<()> {
-        let availability = self.mongo.read_candle_data_availability(figi).await?;
+    async fn sync_market_data_ranges(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        calendar: &TradingCalendar,
+        ranges: Ranges,
+    ) -> anyhow::Result<()> {
+        let availability = self
+            .mongo
+            .read_candle_data_availability(figi, resolution)
+            .await?;
 
-        let cursors =
-            ranges
-                .into_iter()
-                .fold(HashSet::<DateTime<Utc>>::default(), |mut cursors, range| {
-                    let mut from = range.0.date();
-                    let to = range.1.date().succ();
-
-                    while from < to {
-                        let availability = availability
-                            .get(&from)
-                            .cloned()
-                            .unwrap_or(DataAvailability::Unavailable);
-
-                        let cursor = match availability {
-                            DataAvailability::Unavailable => Some(from.and_hms(0, 0, 0)),
-                            DataAvailability::Available => None,
-                            DataAvailability::PartiallyAvailable {
-                                available_up_to: cursor,
-                            } => Some(cursor),
-                        };
-
-                        if let Some(cursor) = cursor {
-                            cursors.insert(cursor);
+        // Days the exchange is closed on are not fetched and marked as empty right away
+        let mut non_trading_days: BTreeSet<NaiveDate> = Default::default();
+
+        let cursors = ranges.into_iter().fold(
+            BTreeSet::<DateTime<Utc>>::default(),
+            |mut cursors, range| {
+                let mut from = range.0.date_naive();
+                let to = range.1.date_naive().succ_opt().unwrap();
+
+                while from < to {
+                    let availability = availability
+                        .get(&from)
+                        .cloned()
+                        .unwrap_or(DataAvailability::Unavailable);
+
+                    let cursor = match availability {
+                        DataAvailability::Available | DataAvailability::NonTrading => None,
+                        _ if calendar.is_closed(from) => {
+                            non_trading_days.insert(from);
+                            None
                         }
+                        DataAvailability::Unavailable => {
+                            Some(from.and_time(NaiveTime::MIN).and_utc())
+                        }
+                        DataAvailability::PartiallyAvailable {
+                            available_up_to: cursor,
+                        } => Some(cursor),
+                    };
 
-                        from = from.succ();
+                    if let Some(cursor) = cursor {
+                        cursors.insert(cursor);
                     }
 
-                    cursors
-                });
+                    from = from.succ_opt().unwrap();
+                }
+
+                cursors
+            },
+        );
+
+        for date in non_trading_days {
+            self.mongo
+                .write_candle_data_availability(
+                    figi,
+                    resolution,
+                    date,
+                    DataAvailability::NonTrading,
+                )
+                .await?;
+        }
 
         let mut chunks_fetched = 0;
 
-        for cursor in cursors {
+        let max_days = self
+            .data_provider
+            .market_data()
+            .max_candles_window(resolution)
+            .num_days()
+            .max(1);
+
+        for (cursor, last_date) in group_into_chunks(cursors, max_days, calendar) {
             if chunks_fetched > self.max_chunks_per_instrument {
                 break;
             }
 
-            let fetch_result = self.sync_candle_data(figi, cursor).await;
+            let fetch_result = self
+                .sync_candle_data(figi, resolution, calendar, cursor, last_date)
+                .await;
 
             if let Err(err) = fetch_result {
                 println!(
-                    "Failed to fetch candles data for {} at {}: {}",
+                    "Failed to fetch {} candles data for {} at {}: {}",
+                    resolution,
                     figi.0,
-                    cursor.date(),
+                    cursor.date_naive(),
                     err,
                 );
                 continue;
@@ -162,4 +343,179 @@ impl MarketDataSyncPeriodic {
     }
 }
 
+/// Groups consecutive days which have to be fetched into chunks
+/// of at most `max_days` days. Chunks span days the exchange is closed on.
+/// Returns first cursor and last day of each chunk.
+fn group_into_chunks(
+    cursors: BTreeSet<DateTime<Utc>>,
+    max_days: i64,
+    calendar: &TradingCalendar,
+) -> Vec<(DateTime<Utc>, NaiveDate)> {
+    let mut chunks: Vec<(DateTime<Utc>, NaiveDate)> = Vec::new();
+
+    let is_consecutive = |last_date: NaiveDate, date: NaiveDate| {
+        let mut next = last_date.succ_opt().unwrap();
+
+        while next < date && calendar.is_closed(next) {
+            next = next.succ_opt().unwrap();
+        }
+
+        next == date
+    };
+
+    for cursor in cursors {
+        match chunks.last_mut() {
+            Some((first, last_date))
+                if is_consecutive(*last_date, cursor.date_naive())
+                    && (cursor.date_naive() - first.date_naive()).num_days() < max_days =>
+            {
+                *last_date = cursor.date_naive();
+            }
+            _ => chunks.push((cursor, cursor.date_naive())),
+        }
+    }
+
+    chunks
+}
+
 pub type MarketDataSync = PeriodicComponent<MarketDataSyncPeriodic>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fake_broker::{FakeBroker, FakeMarket};
+    use crate::models::trading_calendar::TradingDay;
+
+    #[test]
+    fn test_group_into_chunks() {
+        let cursors: BTreeSet<DateTime<Utc>> = [
+            Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2022, 1, 2, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2022, 1, 3, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2022, 1, 5, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2022, 1, 6, 12, 30, 0).unwrap(),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            group_into_chunks(cursors.clone(), 2, &TradingCalendar::default()),
+            vec![
+                (
+                    Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap(),
+                    NaiveDate::from_ymd_opt(2022, 1, 2).unwrap()
+                ),
+                (
+                    Utc.with_ymd_and_hms(2022, 1, 3, 0, 0, 0).unwrap(),
+                    NaiveDate::from_ymd_opt(2022, 1, 3).unwrap()
+                ),
+                (
+                    Utc.with_ymd_and_hms(2022, 1, 5, 0, 0, 0).unwrap(),
+                    NaiveDate::from_ymd_opt(2022, 1, 6).unwrap()
+                ),
+            ]
+        );
+
+        let closed = TradingDay {
+            is_trading_day: false,
+            start_time: None,
+            end_time: None,
+        };
+        let calendar = TradingCalendar::new(
+            [(NaiveDate::from_ymd_opt(2022, 1, 4).unwrap(), closed)]
+                .into_iter()
+                .collect(),
+        );
+
+        assert_eq!(
+            group_into_chunks(cursors, 7, &calendar),
+            vec![(
+                Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap(),
+                NaiveDate::from_ymd_opt(2022, 1, 6).unwrap()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_candle_data() {
+        let broker = FakeBroker::new(FakeMarket::sample());
+        let client = broker.spawn().await;
+
+        let figi = Figi("FAKE0000001".to_owned());
+        let trading_days = client
+            .get_trading_schedules(
+                NaiveDate::from_ymd_opt(2022, 3, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2022, 3, 3).unwrap(),
+            )
+            .await
+            .unwrap();
+        let calendar = TradingCalendar::new(trading_days["MOEX"].clone());
+
+        let cursor = Utc.with_ymd_and_hms(2022, 3, 1, 0, 0, 0).unwrap();
+        let last_date = NaiveDate::from_ymd_opt(2022, 3, 3).unwrap();
+
+        // Session of March 3 is in progress
+        let now = Utc.with_ymd_and_hms(2022, 3, 3, 12, 0, 0).unwrap();
+
+        let fetched = fetch_candle_data(
+            &client,
+            &figi,
+            CandleResolution::OneMinute,
+            &calendar,
+            cursor,
+            last_date,
+            now,
+        )
+        .await
+        .unwrap();
+
+        let last_candle_ts = Utc.with_ymd_and_hms(2022, 3, 3, 10, 0, 0).unwrap();
+
+        assert_eq!(fetched.candles.len(), 4);
+        assert_eq!(
+            fetched.availability.into_iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    NaiveDate::from_ymd_opt(2022, 3, 1).unwrap(),
+                    DataAvailability::Available
+                ),
+                (
+                    NaiveDate::from_ymd_opt(2022, 3, 2).unwrap(),
+                    DataAvailability::NonTrading
+                ),
+                (
+                    NaiveDate::from_ymd_opt(2022, 3, 3).unwrap(),
+                    DataAvailability::PartiallyAvailable {
+                        available_up_to: last_candle_ts
+                    }
+                ),
+            ]
+        );
+        assert_eq!(fetched.available_up_to, last_candle_ts);
+
+        // Once the session is over the day is complete
+        let now = Utc.with_ymd_and_hms(2022, 3, 3, 18, 0, 0).unwrap();
+
+        let fetched = fetch_candle_data(
+            &client,
+            &figi,
+            CandleResolution::OneMinute,
+            &calendar,
+            cursor,
+            last_date,
+            now,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            fetched.availability[&last_date],
+            DataAvailability::Available
+        );
+        assert_eq!(
+            fetched.available_up_to,
+            Utc.with_ymd_and_hms(2022, 3, 4, 0, 0, 0).unwrap()
+        );
+    }
+}
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 mod market_data_sync;
 mod requirements_collector;
 
@@ -3,12 +3,13 @@ use std::collections::HashMap;
 use chrono::prelude::*;
 
 use crate::models::instruments::Figi;
+use crate::models::market_data::CandleResolution;
 
 pub type Ranges = Vec<(DateTime<Utc>, DateTime<Utc>)>;
 
 #[derive(Default)]
 pub struct RequirementsCollector {
-    instruments: HashMap<Figi, Ranges>,
+    instruments: HashMap<(Figi, CandleResolution), Ranges>,
 }
 
 fn merge_ranges(mut input: Ranges) -> Ranges {
@@ -24,12 +25,11 @@ fn merge_ranges(mut input: Ranges) -> Ranges {
                 None => {
                     state.push(elem);
                 }
-                Some(range) => {
-                    if range.1 >= elem.0 {
-                        range.1 = elem.1;
-                    } else {
-                        state.push(elem);
-                    }
+                Some(range) if range.1 >= elem.0 => {
+                    range.1 = elem.1;
+                }
+                Some(_) => {
+                    state.push(elem);
                 }
             }
 
@@ -38,18 +38,24 @@ fn merge_ranges(mut input: Ranges) -> Ranges {
 }
 
 impl RequirementsCollector {
-    pub fn push(&mut self, figi: Figi, time_from: DateTime<Utc>, time_to: Option<DateTime<Utc>>) {
+    pub fn push(
+        &mut self,
+        figi: Figi,
+        resolution: CandleResolution,
+        time_from: DateTime<Utc>,
+        time_to: Option<DateTime<Utc>>,
+    ) {
         let range = (time_from, time_to.unwrap_or_else(Utc::now));
 
-        let ranges = self.instruments.entry(figi).or_default();
+        let ranges = self.instruments.entry((figi, resolution)).or_default();
         ranges.push(range);
     }
 
-    pub fn finalize(mut self) -> HashMap<Figi, Ranges> {
-        let mut res: HashMap<Figi, Ranges> = Default::default();
+    pub fn finalize(mut self) -> HashMap<(Figi, CandleResolution), Ranges> {
+        let mut res: HashMap<(Figi, CandleResolution), Ranges> = Default::default();
 
-        for (figi, raw_ranges) in self.instruments.drain() {
-            res.insert(figi, merge_ranges(raw_ranges));
+        for (key, raw_ranges) in self.instruments.drain() {
+            res.insert(key, merge_ranges(raw_ranges));
         }
 
         res
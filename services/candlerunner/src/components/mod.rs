@@ -1,23 +1,47 @@
 mod accounts_cache;
+mod backtest_runner;
+mod corporate_action_sync;
+mod data_provider;
+mod data_verifier;
 mod instrument_cache;
 mod instrument_sync;
+mod market_data_events;
+mod market_data_stream;
 mod market_data_sync;
 mod mongo;
+mod order_executor;
 mod param_validator;
+mod position_manager_cache;
+mod position_manager_registry;
+mod position_manager_runner;
 mod positions_cache;
 mod strategy_cache;
 mod strategy_registry;
 mod strategy_runner;
 mod tinkoff_client;
+mod trading_calendar_cache;
+mod trading_calendar_sync;
 
 pub use accounts_cache::AccountsCache;
-pub use instrument_cache::InstrumentCache;
+pub use backtest_runner::BacktestRunner;
+pub use corporate_action_sync::CorporateActionSync;
+pub use data_provider::{DataProvider, InstrumentProvider, MarketDataProvider};
+pub use data_verifier::DataVerifier;
+pub use instrument_cache::{InstrumentCache, InstrumentQuery};
 pub use instrument_sync::InstrumentSync;
+pub use market_data_events::{DataAvailable, MarketDataEvents};
+pub use market_data_stream::MarketDataStream;
 pub use market_data_sync::MarketDataSync;
 pub use mongo::Mongo;
+pub use order_executor::OrderExecutor;
 pub use param_validator::ParamValidator;
+pub use position_manager_cache::PositionManagerCache;
+pub use position_manager_registry::PositionManagerRegistry;
+pub use position_manager_runner::PositionManagerRunner;
 pub use positions_cache::PositionsCache;
 pub use strategy_cache::StrategyCache;
 pub use strategy_registry::StrategyRegistry;
-pub use strategy_runner::StrategyRunner;
+pub use strategy_runner::{read_market_data, StrategyRunner};
 pub use tinkoff_client::TinkoffClient;
+pub use trading_calendar_cache::TradingCalendarCache;
+pub use trading_calendar_sync::TradingCalendarSync;
@@ -1,27 +1,42 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
 use bson::{from_bson, to_bson};
 use chrono::prelude::*;
 use futures::stream::StreamExt;
 use futures::{TryFutureExt, TryStreamExt};
-use mongodb::bson::{doc, from_document, to_document, Document};
+use mongodb::bson::{doc, from_document, to_document, Bson, Document};
 use mongodb::options::{
-    CreateCollectionOptions, TimeseriesGranularity, TimeseriesOptions, UpdateOptions,
+    CreateCollectionOptions, FindOneOptions, FindOptions, TimeseriesGranularity,
+    TimeseriesOptions, UpdateOptions,
 };
 use mongodb::{options::ClientOptions, Client, Database};
 use serde::{de::DeserializeOwned, Serialize};
 
 use component_store::{init_err, prelude::*};
 
+use crate::backtest::Backtest;
+use crate::models::backtest::{BacktestProgress, BacktestResult, BacktestSettings, EquityPoint};
+use crate::models::corporate_actions::CorporateAction;
 use crate::models::instance_id::InstanceId;
 use crate::models::instruments::{Figi, Instrument};
-use crate::models::market_data::{Candle, CandleTimeline, DataAvailability};
-use crate::models::strategy::{StrategyExecution, StrategyInstanceDefinition, StrategyState};
+use crate::models::market_data::{Candle, CandleResolution, CandleTimeline, DataAvailability};
+use crate::models::orders::{OrderExecutionCursor, OrderRecord};
+use crate::models::position_manager::PositionManagerInstanceDefinition;
+use crate::models::strategy::{
+    ExecutionRevision, StrategyExecution, StrategyInstanceDefinition, StrategyState,
+};
+use crate::models::trading_calendar::{TradingCalendar, TradingDay};
 
 const CANDLE_DATA_COLLECTION_NAME: &str = "candleData";
 const CANDLE_DATA_AVAILABILITY_COLLECTION_NAME: &str = "candleDataAvailability";
 const STRATEGY_STATE_COLLECTION_NAME: &str = "strategyState";
 const STRATEGY_EXECUTION_COLLECTION_NAME: &str = "strategyExecution";
+const ORDERS_COLLECTION_NAME: &str = "orders";
+const ORDER_EXECUTION_COLLECTION_NAME: &str = "orderExecution";
+const BACKTEST_COLLECTION_NAME: &str = "backtests";
+const BACKTEST_EQUITY_COLLECTION_NAME: &str = "backtestEquity";
+const TRADING_CALENDAR_COLLECTION_NAME: &str = "tradingCalendar";
+const CORPORATE_ACTIONS_COLLECTION_NAME: &str = "corporateActions";
 
 pub struct Mongo {
     db: Database,
@@ -57,6 +72,98 @@ fn get_datetime(doc: &Document, field_name: &str) -> anyhow::Result<DateTime<Utc
         .to_chrono())
 }
 
+/// Results stored before executions were reset by generation belong to the first one
+fn get_generation(doc: &Document) -> anyhow::Result<u32> {
+    match doc.get("generation") {
+        Some(generation) => Ok(from_bson::<u32>(generation.clone())?),
+        None => Ok(0),
+    }
+}
+
+/// Minute candles keep collection names used before other resolutions were stored
+fn candle_data_collection_name(resolution: CandleResolution) -> String {
+    match resolution {
+        CandleResolution::OneMinute => CANDLE_DATA_COLLECTION_NAME.to_owned(),
+        _ => format!("{}.{}", CANDLE_DATA_COLLECTION_NAME, resolution),
+    }
+}
+
+fn candle_data_availability_collection_name(resolution: CandleResolution) -> String {
+    match resolution {
+        CandleResolution::OneMinute => CANDLE_DATA_AVAILABILITY_COLLECTION_NAME.to_owned(),
+        _ => format!(
+            "{}.{}",
+            CANDLE_DATA_AVAILABILITY_COLLECTION_NAME, resolution
+        ),
+    }
+}
+
+/// `$dateTrunc` expression aligning `ts` the way `CandleResolution::align` does
+fn date_trunc(resolution: CandleResolution) -> Document {
+    let (unit, bin_size) = match resolution {
+        CandleResolution::OneMinute => ("minute", 1),
+        CandleResolution::TwoMinutes => ("minute", 2),
+        CandleResolution::FiveMinutes => ("minute", 5),
+        CandleResolution::TenMinutes => ("minute", 10),
+        CandleResolution::FifteenMinutes => ("minute", 15),
+        CandleResolution::ThirtyMinutes => ("minute", 30),
+        CandleResolution::OneHour => ("hour", 1),
+        CandleResolution::FourHours => ("hour", 4),
+        CandleResolution::OneDay => ("day", 1),
+        CandleResolution::OneWeek => ("week", 1),
+        CandleResolution::OneMonth => ("month", 1),
+    };
+
+    doc! {
+        "$dateTrunc": {
+            "date": "$ts",
+            "unit": unit,
+            "binSize": bin_size,
+            "startOfWeek": "monday",
+        }
+    }
+}
+
+fn strategy_state_filter(
+    strategy_id: &uuid::Uuid,
+    time_from: DateTime<Utc>,
+    time_to: Option<DateTime<Utc>>,
+) -> Document {
+    match time_to {
+        Some(time_to) => doc! {
+            "strategyId": strategy_id,
+            "$and": [
+                {
+                    "ts": {
+                        "$gte": time_from
+                    }
+                },
+                {
+                    "ts": {
+                        "$lt": time_to
+                    }
+
+                }
+            ]
+        },
+        None => doc! {
+            "strategyId": strategy_id,
+            "ts": {
+                "$gte": time_from
+            }
+        },
+    }
+}
+
+fn parse_strategy_state(doc: &Document) -> anyhow::Result<(DateTime<Utc>, StrategyState)> {
+    let ts = get_datetime(doc, "ts")?;
+    let serialized = doc
+        .get("state")
+        .ok_or_else(|| anyhow::anyhow!("`state` field is missing from document"))?;
+
+    Ok((ts, from_bson::<StrategyState>(serialized.to_owned())?))
+}
+
 impl Mongo {
     async fn new(
         _: component_store::ComponentResolver,
@@ -71,15 +178,28 @@ impl Mongo {
 
         let collections = db.list_collection_names(None).await.map_err(init_err)?;
 
-        if !collections.contains(&CANDLE_DATA_COLLECTION_NAME.to_string()) {
+        for resolution in CandleResolution::STORED {
+            let collection_name = candle_data_collection_name(resolution);
+            if collections.contains(&collection_name) {
+                continue;
+            }
+
+            let granularity = match resolution {
+                CandleResolution::OneMinute => TimeseriesGranularity::Seconds,
+                CandleResolution::OneHour | CandleResolution::OneDay => {
+                    TimeseriesGranularity::Hours
+                }
+                _ => TimeseriesGranularity::Minutes,
+            };
+
             db.create_collection(
-                CANDLE_DATA_COLLECTION_NAME,
+                collection_name,
                 CreateCollectionOptions::builder()
                     .timeseries(
                         TimeseriesOptions::builder()
                             .time_field("ts".into())
                             .meta_field(Some("figi".to_string()))
-                            .granularity(Some(TimeseriesGranularity::Seconds))
+                            .granularity(Some(granularity))
                             .build(),
                     )
                     .build(),
@@ -217,8 +337,104 @@ impl Mongo {
             .await;
     }
 
-    pub async fn write_candles(&self, figi: &Figi, candles: CandleTimeline) -> anyhow::Result<()> {
-        let collection = self.db.collection::<Document>(CANDLE_DATA_COLLECTION_NAME);
+    /// Deletes the instance along with everything computed for it.
+    /// Order records are kept as they describe orders actually placed.
+    pub async fn delete_strategy_instance(&self, strategy_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.db
+            .collection::<Document>("strategy_instances")
+            .delete_one(doc! {"_id": strategy_id}, None)
+            .await?;
+
+        for collection_name in [
+            STRATEGY_STATE_COLLECTION_NAME,
+            STRATEGY_EXECUTION_COLLECTION_NAME,
+            ORDER_EXECUTION_COLLECTION_NAME,
+            BACKTEST_COLLECTION_NAME,
+            BACKTEST_EQUITY_COLLECTION_NAME,
+        ] {
+            self.db
+                .collection::<Document>(collection_name)
+                .delete_many(doc! {"strategyId": strategy_id}, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Drops everything computed from states of the instance, so that it is computed anew.
+    /// Backtest settings are kept.
+    pub async fn reset_strategy_results(&self, strategy_id: &uuid::Uuid) -> anyhow::Result<()> {
+        for collection_name in [
+            STRATEGY_STATE_COLLECTION_NAME,
+            ORDER_EXECUTION_COLLECTION_NAME,
+            BACKTEST_EQUITY_COLLECTION_NAME,
+        ] {
+            self.db
+                .collection::<Document>(collection_name)
+                .delete_many(doc! {"strategyId": strategy_id}, None)
+                .await?;
+        }
+
+        self.db
+            .collection::<Document>(BACKTEST_COLLECTION_NAME)
+            .update_one(
+                doc! {"strategyId": strategy_id},
+                doc! {
+                    "$unset": {
+                        "generation": "",
+                        "signalsUpTo": "",
+                        "trades": "",
+                        "positions": "",
+                        "cash": "",
+                        "income": "",
+                        "checkpoint": ""
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn write_position_manager_instance(
+        &self,
+        instance_def: &PositionManagerInstanceDefinition,
+    ) -> anyhow::Result<()> {
+        let collection = self
+            .db
+            .collection::<Document>("position_manager_instances");
+        let doc = to_document(instance_def)?;
+
+        collection
+            .update_one(
+                doc! {"_id": instance_def.id()},
+                doc! {"$set": doc},
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn read_position_manager_instances(
+        &self,
+    ) -> anyhow::Result<Vec<PositionManagerInstanceDefinition>> {
+        return self
+            .read_items::<PositionManagerInstanceDefinition>("position_manager_instances")
+            .await;
+    }
+
+    /// Inserts candles as is, stored candles of the same range have to be deleted beforehand
+    pub async fn write_candles(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        candles: CandleTimeline,
+    ) -> anyhow::Result<()> {
+        let collection = self
+            .db
+            .collection::<Document>(&candle_data_collection_name(resolution));
 
         for (ts, candle) in candles {
             let candle = to_document(&candle)?;
@@ -241,10 +457,29 @@ impl Mongo {
     pub async fn read_candles(
         &self,
         figi: &Figi,
+        resolution: CandleResolution,
         time_from: DateTime<Utc>,
         time_to: DateTime<Utc>,
     ) -> anyhow::Result<CandleTimeline> {
-        let collection = self.db.collection::<Document>(CANDLE_DATA_COLLECTION_NAME);
+        let records = self
+            .read_candle_records(figi, resolution, time_from, time_to)
+            .await?;
+
+        Ok(records.into_iter().collect())
+    }
+
+    /// Reads stored candles in the order they were written.
+    /// Unlike `read_candles` keeps every copy of candles stored more than once.
+    pub async fn read_candle_records(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        time_from: DateTime<Utc>,
+        time_to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<(DateTime<Utc>, Candle)>> {
+        let collection = self
+            .db
+            .collection::<Document>(&candle_data_collection_name(resolution));
 
         let raw_data: Vec<_> = collection
             .find(
@@ -269,7 +504,7 @@ impl Mongo {
             .try_collect()
             .await?;
 
-        let mut candles = CandleTimeline::default();
+        let mut candles = Vec::with_capacity(raw_data.len());
 
         for doc in raw_data {
             let ts = get_datetime(&doc, "ts")?;
@@ -279,22 +514,58 @@ impl Mongo {
 
             let candle = from_bson::<Candle>(candle_doc.clone())?;
 
-            candles.insert(ts, candle);
+            candles.push((ts, candle));
         }
 
         Ok(candles)
     }
 
+    pub async fn delete_candles(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        time_from: DateTime<Utc>,
+        time_to: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        let collection = self
+            .db
+            .collection::<Document>(&candle_data_collection_name(resolution));
+
+        collection
+            .delete_many(
+                doc! {
+                    "figi": &figi.0,
+                    "$and": [
+                        {
+                            "ts": {
+                                "$gte": time_from
+                            },
+                        },
+                        {
+                            "ts": {
+                                "$lt": time_to
+                            }
+                        }
+                    ]
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn write_candle_data_availability(
         &self,
         figi: &Figi,
-        date: Date<Utc>,
+        resolution: CandleResolution,
+        date: NaiveDate,
         availability: DataAvailability,
     ) -> anyhow::Result<()> {
         let collection = self
             .db
-            .collection::<Document>(CANDLE_DATA_AVAILABILITY_COLLECTION_NAME);
-        let ts = mongodb::bson::DateTime::from_chrono(date.and_hms(0, 0, 0));
+            .collection::<Document>(&candle_data_availability_collection_name(resolution));
+        let ts = mongodb::bson::DateTime::from_chrono(date.and_time(NaiveTime::MIN).and_utc());
         let availability = to_bson(&availability)?;
 
         collection
@@ -311,17 +582,18 @@ impl Mongo {
     pub async fn read_candle_data_availability(
         &self,
         figi: &Figi,
-    ) -> anyhow::Result<BTreeMap<Date<Utc>, DataAvailability>> {
+        resolution: CandleResolution,
+    ) -> anyhow::Result<BTreeMap<NaiveDate, DataAvailability>> {
         let collection = self
             .db
-            .collection::<Document>(CANDLE_DATA_AVAILABILITY_COLLECTION_NAME);
+            .collection::<Document>(&candle_data_availability_collection_name(resolution));
         let raw_data: Vec<_> = collection
             .find(doc! {"figi": &figi.0}, None)
             .await?
             .try_collect()
             .await?;
 
-        let mut res: BTreeMap<Date<Utc>, DataAvailability> = Default::default();
+        let mut res: BTreeMap<NaiveDate, DataAvailability> = Default::default();
 
         for doc in raw_data {
             let ts = get_datetime(&doc, "ts")?;
@@ -330,17 +602,151 @@ impl Mongo {
                 .ok_or_else(|| anyhow::anyhow!("`availability` field is missing"))?;
 
             let availability = from_bson::<DataAvailability>(availability.clone())?;
-            res.insert(ts.date(), availability);
+            res.insert(ts.date_naive(), availability);
         }
 
         println!(
-            "Fetched {} items from `candle_data_availability` collection",
-            res.len()
+            "Fetched {} items from `candle_data_availability` collection for {}",
+            res.len(),
+            resolution
         );
 
         Ok(res)
     }
 
+    pub async fn read_candle_data_day_availability(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        date: NaiveDate,
+    ) -> anyhow::Result<DataAvailability> {
+        let collection = self
+            .db
+            .collection::<Document>(&candle_data_availability_collection_name(resolution));
+        let ts = mongodb::bson::DateTime::from_chrono(date.and_time(NaiveTime::MIN).and_utc());
+
+        let doc = match collection
+            .find_one(doc! {"figi": &figi.0, "ts": ts}, None)
+            .await?
+        {
+            Some(doc) => doc,
+            None => return Ok(DataAvailability::Unavailable),
+        };
+
+        let availability = doc
+            .get("availability")
+            .ok_or_else(|| anyhow::anyhow!("`availability` field is missing"))?;
+
+        Ok(from_bson::<DataAvailability>(availability.clone())?)
+    }
+
+    pub async fn write_trading_days(
+        &self,
+        exchange: &str,
+        days: BTreeMap<NaiveDate, TradingDay>,
+    ) -> anyhow::Result<()> {
+        let collection = self
+            .db
+            .collection::<Document>(TRADING_CALENDAR_COLLECTION_NAME);
+
+        for (date, day) in days {
+            let ts = mongodb::bson::DateTime::from_chrono(date.and_time(NaiveTime::MIN).and_utc());
+            let day = to_bson(&day)?;
+
+            collection
+                .update_one(
+                    doc! {"exchange": exchange, "ts": ts},
+                    doc! { "$set": { "day": day }},
+                    UpdateOptions::builder().upsert(true).build(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads trading calendars of all exchanges
+    pub async fn read_trading_calendars(&self) -> anyhow::Result<HashMap<String, TradingCalendar>> {
+        let collection = self
+            .db
+            .collection::<Document>(TRADING_CALENDAR_COLLECTION_NAME);
+        let raw_data: Vec<_> = collection.find(None, None).await?.try_collect().await?;
+
+        let mut days: HashMap<String, BTreeMap<NaiveDate, TradingDay>> = Default::default();
+
+        for doc in raw_data {
+            let exchange = doc.get_str("exchange")?;
+            let ts = get_datetime(&doc, "ts")?;
+            let day = doc
+                .get("day")
+                .ok_or_else(|| anyhow::anyhow!("`day` field is missing"))?;
+
+            let day = from_bson::<TradingDay>(day.clone())?;
+            days.entry(exchange.to_owned())
+                .or_default()
+                .insert(ts.date_naive(), day);
+        }
+
+        Ok(days
+            .into_iter()
+            .map(|(exchange, days)| (exchange, TradingCalendar::new(days)))
+            .collect())
+    }
+
+    /// Upserts corporate actions, an action is identified by FIGI, ex-date and type
+    pub async fn write_corporate_actions(&self, actions: &[CorporateAction]) -> anyhow::Result<()> {
+        let collection = self
+            .db
+            .collection::<Document>(CORPORATE_ACTIONS_COLLECTION_NAME);
+
+        for action in actions {
+            let serialized = to_bson(action)?;
+
+            collection
+                .update_one(
+                    doc! {
+                        "figi": &action.figi.0,
+                        "exDate": action.ex_date,
+                        "type": action.kind.name(),
+                    },
+                    doc! { "$set": { "action": serialized }},
+                    UpdateOptions::builder().upsert(true).build(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads corporate actions of given instruments ordered by ex-date
+    pub async fn read_corporate_actions(
+        &self,
+        figis: &[Figi],
+    ) -> anyhow::Result<Vec<CorporateAction>> {
+        let collection = self
+            .db
+            .collection::<Document>(CORPORATE_ACTIONS_COLLECTION_NAME);
+        let figis: Vec<_> = figis.iter().map(|figi| figi.0.as_str()).collect();
+
+        let options = FindOptions::builder().sort(doc! {"exDate": 1}).build();
+        let raw_data: Vec<_> = collection
+            .find(doc! {"figi": {"$in": figis}}, options)
+            .await?
+            .try_collect()
+            .await?;
+
+        raw_data
+            .into_iter()
+            .map(|doc| {
+                let action = doc
+                    .get("action")
+                    .ok_or_else(|| anyhow::anyhow!("`action` field is missing"))?;
+
+                Ok(from_bson::<CorporateAction>(action.clone())?)
+            })
+            .collect()
+    }
+
     pub async fn read_strategy_state(
         &self,
         strategy_id: &uuid::Uuid,
@@ -351,46 +757,92 @@ impl Mongo {
             .db
             .collection::<Document>(STRATEGY_STATE_COLLECTION_NAME);
 
-        let filter = match time_to {
-            Some(time_to) => doc! {
-                "strategyId": strategy_id,
-                "$and": [
-                    {
-                        "ts": {
-                            "$gte": time_from
-                        }
-                    },
-                    {
-                        "ts": {
-                            "$lt": time_to
-                        }
+        let filter = strategy_state_filter(strategy_id, time_from, time_to);
 
-                    }
-                ]
-            },
-            None => doc! {
-                "strategyId": strategy_id,
-                "ts": {
-                    "$gte": time_from
+        let raw_data: Vec<_> = collection.find(filter, None).await?.try_collect().await?;
+
+        raw_data.iter().map(parse_strategy_state).collect()
+    }
+
+    /// Reads a page of states ordered by time along with the number of states in the range.
+    /// With `resolution` only the last state within each candle is kept.
+    pub async fn read_strategy_history(
+        &self,
+        strategy_id: &uuid::Uuid,
+        time_from: DateTime<Utc>,
+        time_to: Option<DateTime<Utc>>,
+        resolution: Option<CandleResolution>,
+        offset: usize,
+        limit: usize,
+    ) -> anyhow::Result<(usize, Vec<(DateTime<Utc>, StrategyState)>)> {
+        let collection = self
+            .db
+            .collection::<Document>(STRATEGY_STATE_COLLECTION_NAME);
+
+        let mut stages = vec![
+            doc! {"$match": strategy_state_filter(strategy_id, time_from, time_to)},
+            doc! {"$sort": {"ts": 1}},
+        ];
+
+        if let Some(resolution) = resolution {
+            // States are sorted, so the last one of a candle wins
+            stages.push(doc! {
+                "$group": {
+                    "_id": date_trunc(resolution),
+                    "ts": {"$last": "$ts"},
+                    "state": {"$last": "$state"},
                 }
-            },
+            });
+            stages.push(doc! {"$sort": {"ts": 1}});
+        }
+
+        let count_stages = stages.iter().cloned().chain([doc! {"$count": "total"}]);
+        let total = match collection
+            .aggregate(count_stages, None)
+            .await?
+            .try_next()
+            .await?
+        {
+            Some(doc) => doc.get_i32("total")? as usize,
+            None => 0,
         };
 
-        let raw_data: Vec<_> = collection.find(filter, None).await?.try_collect().await?;
-        let mut states: BTreeMap<DateTime<Utc>, StrategyState> = Default::default();
+        stages.push(doc! {"$skip": offset as i64});
+        stages.push(doc! {"$limit": limit as i64});
 
-        for doc in raw_data {
-            let ts = get_datetime(&doc, "ts")?;
-            let serialized = doc
-                .get("state")
-                .ok_or_else(|| anyhow::anyhow!("`context` field is missing from document"))?;
+        let raw_data: Vec<_> = collection
+            .aggregate(stages, None)
+            .await?
+            .try_collect()
+            .await?;
+        let states = raw_data
+            .iter()
+            .map(parse_strategy_state)
+            .collect::<anyhow::Result<_>>()?;
 
-            let state = from_bson::<StrategyState>(serialized.to_owned())?;
+        Ok((total, states))
+    }
 
-            states.insert(ts, state);
-        }
+    pub async fn read_last_strategy_state(
+        &self,
+        strategy_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(DateTime<Utc>, StrategyState)>> {
+        let collection = self
+            .db
+            .collection::<Document>(STRATEGY_STATE_COLLECTION_NAME);
+
+        let doc = match collection
+            .find_one(
+                doc! {"strategyId": strategy_id},
+                FindOneOptions::builder().sort(doc! {"ts": -1}).build(),
+            )
+            .await?
+        {
+            Some(doc) => doc,
+            None => return Ok(None),
+        };
 
-        Ok(states)
+        Ok(Some(parse_strategy_state(&doc)?))
     }
 
     pub async fn write_strategy_state(
@@ -402,21 +854,23 @@ impl Mongo {
             .db
             .collection::<Document>(STRATEGY_STATE_COLLECTION_NAME);
 
-        for (ts, state) in states {
-            let serialized = to_document(&state)?;
-
-            collection
-                .insert_one(
-                    doc! {
-                        "ts": ts,
-                        "strategyId": strategy_id,
-                        "state": serialized
-                    },
-                    None,
-                )
-                .await?;
+        if states.is_empty() {
+            return Ok(());
         }
 
+        let docs = states
+            .into_iter()
+            .map(|(ts, state)| {
+                Ok(doc! {
+                    "ts": ts,
+                    "strategyId": strategy_id,
+                    "state": to_document(&state)?
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        collection.insert_many(docs, None).await?;
+
         Ok(())
     }
 
@@ -441,6 +895,68 @@ impl Mongo {
         Ok(())
     }
 
+    /// Writes the execution unless it has been changed since `revision` was read,
+    /// `None` standing for no stored execution. Returns whether the execution is written.
+    pub async fn write_strategy_execution_if(
+        &self,
+        strategy_id: &uuid::Uuid,
+        execution: &StrategyExecution,
+        revision: Option<ExecutionRevision>,
+    ) -> anyhow::Result<bool> {
+        let collection = self
+            .db
+            .collection::<Document>(STRATEGY_EXECUTION_COLLECTION_NAME);
+        let serialized = to_bson(execution)?;
+
+        let revision = match revision {
+            Some(revision) => revision,
+            None => {
+                let result = collection
+                    .update_one(
+                        doc! { "strategyId": strategy_id },
+                        doc! { "$setOnInsert": { "execution": serialized } },
+                        UpdateOptions::builder().upsert(true).build(),
+                    )
+                    .await?;
+
+                return Ok(result.upserted_id.is_some());
+            }
+        };
+
+        // Executions stored before history and generation were tracked have neither field
+        let history = match revision.history_len {
+            0 => doc! { "$in": [Bson::Null, Bson::Array(vec![])] },
+            len => doc! { "$size": len as i64 },
+        };
+        let generation = match revision.generation {
+            0 => doc! { "$in": [Bson::Null, 0] },
+            generation => doc! { "$eq": i64::from(generation) },
+        };
+
+        let result = collection
+            .update_one(
+                doc! {
+                    "strategyId": strategy_id,
+                    "execution.history": history,
+                    "execution.generation": generation
+                },
+                doc! { "$set": { "execution": serialized } },
+                None,
+            )
+            .await?;
+
+        Ok(result.matched_count == 1)
+    }
+
+    pub async fn delete_strategy_states(&self, strategy_id: &uuid::Uuid) -> anyhow::Result<()> {
+        self.db
+            .collection::<Document>(STRATEGY_STATE_COLLECTION_NAME)
+            .delete_many(doc! {"strategyId": strategy_id}, None)
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn read_strategy_execution(
         &self,
         strategy_id: &uuid::Uuid,
@@ -465,4 +981,334 @@ impl Mongo {
 
         Ok(Some(execution))
     }
+
+    pub async fn write_order_record(
+        &self,
+        strategy_id: &uuid::Uuid,
+        record: &OrderRecord,
+    ) -> anyhow::Result<()> {
+        let collection = self.db.collection::<Document>(ORDERS_COLLECTION_NAME);
+        let serialized = to_document(record)?;
+
+        collection
+            .insert_one(
+                doc! {
+                    "ts": record.ts,
+                    "strategyId": strategy_id,
+                    "record": serialized
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Orders placed for the instance, oldest first
+    pub async fn read_order_records(
+        &self,
+        strategy_id: &uuid::Uuid,
+    ) -> anyhow::Result<Vec<OrderRecord>> {
+        let collection = self.db.collection::<Document>(ORDERS_COLLECTION_NAME);
+
+        let options = FindOptions::builder().sort(doc! {"ts": 1}).build();
+        let raw_data: Vec<_> = collection
+            .find(doc! {"strategyId": strategy_id}, options)
+            .await?
+            .try_collect()
+            .await?;
+
+        raw_data
+            .into_iter()
+            .map(|doc| {
+                let record = doc
+                    .get("record")
+                    .ok_or_else(|| anyhow::anyhow!("`record` field is missing"))?;
+
+                Ok(from_bson::<OrderRecord>(record.clone())?)
+            })
+            .collect()
+    }
+
+    /// Strategy states processed by order executor
+    pub async fn read_order_execution_cursor(
+        &self,
+        strategy_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<OrderExecutionCursor>> {
+        let collection = self
+            .db
+            .collection::<Document>(ORDER_EXECUTION_COLLECTION_NAME);
+
+        let doc = match collection
+            .find_one(doc! {"strategyId": strategy_id}, None)
+            .await?
+        {
+            Some(doc) => doc,
+            None => return Ok(None),
+        };
+
+        let partially_executed = match doc.get_str("figi") {
+            Ok(figi) => Some((get_datetime(&doc, "signalTs")?, Figi(figi.to_owned()))),
+            Err(_) => None,
+        };
+
+        Ok(Some(OrderExecutionCursor {
+            executed_up_to: get_datetime(&doc, "cursor")?,
+            partially_executed,
+        }))
+    }
+
+    pub async fn write_order_execution_cursor(
+        &self,
+        strategy_id: &uuid::Uuid,
+        cursor: &OrderExecutionCursor,
+    ) -> anyhow::Result<()> {
+        let collection = self
+            .db
+            .collection::<Document>(ORDER_EXECUTION_COLLECTION_NAME);
+
+        let update = match &cursor.partially_executed {
+            Some((signal_ts, figi)) => doc! {
+                "$set": {
+                    "cursor": cursor.executed_up_to,
+                    "signalTs": signal_ts,
+                    "figi": &figi.0
+                }
+            },
+            None => doc! {
+                "$set": { "cursor": cursor.executed_up_to },
+                "$unset": { "signalTs": "", "figi": "" }
+            },
+        };
+
+        collection
+            .update_one(
+                doc! { "strategyId": strategy_id },
+                update,
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stores backtest settings and drops results computed with previous ones
+    pub async fn write_backtest_settings(
+        &self,
+        strategy_id: &uuid::Uuid,
+        settings: &BacktestSettings,
+    ) -> anyhow::Result<()> {
+        let collection = self.db.collection::<Document>(BACKTEST_COLLECTION_NAME);
+        let serialized = to_bson(settings)?;
+
+        collection
+            .update_one(
+                doc! { "strategyId": strategy_id },
+                doc! {
+                    "$set": { "settings": serialized },
+                    "$unset": {
+                        "generation": "",
+                        "signalsUpTo": "",
+                        "trades": "",
+                        "positions": "",
+                        "cash": "",
+                        "income": "",
+                        "checkpoint": ""
+                    }
+                },
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Backtest settings along with the timestamp of the last signal taken into account
+    pub async fn read_backtest_settings(
+        &self,
+        strategy_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<(BacktestSettings, Option<BacktestProgress>)>> {
+        let collection = self.db.collection::<Document>(BACKTEST_COLLECTION_NAME);
+
+        let doc = match collection
+            .find_one(doc! {"strategyId": strategy_id}, None)
+            .await?
+        {
+            Some(doc) => doc,
+            None => return Ok(None),
+        };
+
+        let serialized = doc
+            .get("settings")
+            .ok_or_else(|| anyhow::anyhow!("Backtest document is missing `settings` field"))?;
+
+        let settings = from_bson::<BacktestSettings>(serialized.to_owned())?;
+        let progress = match doc.contains_key("signalsUpTo") {
+            true => Some(BacktestProgress {
+                generation: get_generation(&doc)?,
+                signals_up_to: get_datetime(&doc, "signalsUpTo")?,
+            }),
+            false => None,
+        };
+
+        Ok(Some((settings, progress)))
+    }
+
+    /// Stores the result of a backtest run along with the backtest to continue from.
+    /// Trades, income and equity points of a resumed backtest are appended to the stored ones.
+    pub async fn write_backtest_result(
+        &self,
+        strategy_id: &uuid::Uuid,
+        result: &BacktestResult,
+        backtest: &Backtest,
+        is_resumed: bool,
+    ) -> anyhow::Result<()> {
+        // Equity curve may easily exceed document size limit, so it is stored point by point
+        let equity_collection = self
+            .db
+            .collection::<Document>(BACKTEST_EQUITY_COLLECTION_NAME);
+
+        if !is_resumed {
+            equity_collection
+                .delete_many(doc! { "strategyId": strategy_id }, None)
+                .await?;
+        }
+
+        let points: Vec<_> = result
+            .equity_curve
+            .iter()
+            .map(|point| {
+                doc! {
+                    "ts": point.ts,
+                    "strategyId": strategy_id,
+                    "cash": point.cash,
+                    "equity": point.equity
+                }
+            })
+            .collect();
+
+        if !points.is_empty() {
+            equity_collection.insert_many(points, None).await?;
+        }
+
+        let mut update = doc! {
+            "$set": {
+                "generation": result.generation as i64,
+                "signalsUpTo": result.signals_up_to,
+                "positions": to_bson(&result.positions)?,
+                "cash": result.cash,
+                "checkpoint": to_bson(backtest)?
+            }
+        };
+
+        let trades = to_bson(&result.trades)?;
+        let income = to_bson(&result.income)?;
+
+        match is_resumed {
+            true => {
+                update.insert(
+                    "$push",
+                    doc! {
+                        "trades": { "$each": trades },
+                        "income": { "$each": income }
+                    },
+                );
+            }
+            false => {
+                let set = update.get_document_mut("$set")?;
+                set.insert("trades", trades);
+                set.insert("income", income);
+            }
+        }
+
+        self.db
+            .collection::<Document>(BACKTEST_COLLECTION_NAME)
+            .update_one(doc! { "strategyId": strategy_id }, update, None)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Backtest stored by the last run, if its results are still kept
+    pub async fn read_backtest_checkpoint(
+        &self,
+        strategy_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<Backtest>> {
+        let collection = self.db.collection::<Document>(BACKTEST_COLLECTION_NAME);
+
+        let doc = collection
+            .find_one(doc! {"strategyId": strategy_id}, None)
+            .await?;
+
+        match doc.as_ref().and_then(|doc| doc.get("checkpoint")) {
+            Some(checkpoint) => Ok(Some(from_bson::<Backtest>(checkpoint.clone())?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn read_backtest_result(
+        &self,
+        strategy_id: &uuid::Uuid,
+    ) -> anyhow::Result<Option<BacktestResult>> {
+        let collection = self.db.collection::<Document>(BACKTEST_COLLECTION_NAME);
+
+        let doc = match collection
+            .find_one(doc! {"strategyId": strategy_id}, None)
+            .await?
+        {
+            Some(doc) if doc.contains_key("signalsUpTo") => doc,
+            _ => return Ok(None),
+        };
+
+        let get_field = |field_name: &str| {
+            doc.get(field_name).cloned().ok_or_else(|| {
+                anyhow::anyhow!("Backtest document is missing `{}` field", field_name)
+            })
+        };
+
+        let generation = get_generation(&doc)?;
+        let signals_up_to = get_datetime(&doc, "signalsUpTo")?;
+        let trades = from_bson(get_field("trades")?)?;
+        let positions = from_bson(get_field("positions")?)?;
+        let cash = from_bson(get_field("cash")?)?;
+
+        // Results stored before income was tracked have none
+        let income = match doc.get("income") {
+            Some(income) => from_bson(income.clone())?,
+            None => vec![],
+        };
+
+        let equity_collection = self
+            .db
+            .collection::<Document>(BACKTEST_EQUITY_COLLECTION_NAME);
+
+        let raw_data: Vec<_> = equity_collection
+            .find(
+                doc! { "strategyId": strategy_id },
+                FindOptions::builder().sort(doc! {"ts": 1}).build(),
+            )
+            .await?
+            .try_collect()
+            .await?;
+
+        let mut equity_curve = Vec::with_capacity(raw_data.len());
+
+        for doc in raw_data {
+            equity_curve.push(EquityPoint {
+                ts: get_datetime(&doc, "ts")?,
+                cash: doc.get_f64("cash")?,
+                equity: doc.get_f64("equity")?,
+            });
+        }
+
+        Ok(Some(BacktestResult {
+            generation,
+            signals_up_to,
+            trades,
+            equity_curve,
+            positions,
+            cash,
+            income,
+        }))
+    }
 }
@@ -0,0 +1,556 @@
+use std::sync::Arc;
+
+use chrono::prelude::*;
+use component_store::prelude::*;
+use periodic_component::{Periodic, PeriodicComponent, PeriodicCreateFuture, PeriodicFuture};
+
+use crate::components;
+use crate::models::account::{AccessLevel, Account, Environment};
+use crate::models::instruments::{Figi, Instrument};
+use crate::models::market_data::CandleResolution;
+use crate::models::namespaces::get_order_ns;
+use crate::models::orders::{
+    OrderDirection, OrderExecutionCursor, OrderRecord, OrderRequest, OrderType, PostedOrder,
+    StopOrderRequest, StopOrderType,
+};
+use crate::models::strategy::{PlaceOrderSettings, StrategyInstanceDefinition};
+use crate::utils::id_generator::IdGenerator;
+
+fn generate_order_id(
+    strategy_id: &uuid::Uuid,
+    signal_ts: DateTime<Utc>,
+    figi: &Figi,
+    direction: OrderDirection,
+) -> String {
+    let mut generator = IdGenerator::default();
+    generator.add("strategyId", strategy_id.as_bytes());
+    generator.add("signalTs", signal_ts.timestamp().to_le_bytes());
+    generator.add("figi", figi.0.as_bytes());
+    generator.add(
+        "direction",
+        match direction {
+            OrderDirection::Buy => b"buy".as_slice(),
+            OrderDirection::Sell => b"sell".as_slice(),
+        },
+    );
+
+    generator.generate(get_order_ns()).to_string()
+}
+
+/// Candle is complete at the start of the next one.
+/// Signals older than one more candle are stale and are not executed.
+fn is_stale(resolution: CandleResolution, signal_ts: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    now > resolution.advance(signal_ts, 2)
+}
+
+/// Stop loss and take profit selling the executed lots, offset in ticks from the average fill price
+fn stop_order_requests(
+    account: &Account,
+    instrument: &Instrument,
+    order: &PostedOrder,
+    expire_at: DateTime<Utc>,
+    settings: &PlaceOrderSettings,
+) -> anyhow::Result<Vec<StopOrderRequest>> {
+    let figi = &instrument.figi;
+    let tick = instrument.min_price_increment;
+
+    if tick <= 0.0 {
+        return Err(anyhow::anyhow!(
+            "Tick size is unknown for {}, stop orders are not placed",
+            figi.0
+        ));
+    }
+
+    let price = order.average_price(instrument.lot).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Order {} is not executed yet, stop orders are not placed",
+            order.order_id
+        )
+    })?;
+
+    let stops = [
+        (
+            StopOrderType::StopLoss,
+            settings.stop_loss_offset(),
+            price - tick * settings.stop_loss_offset() as f64,
+        ),
+        (
+            StopOrderType::TakeProfit,
+            settings.take_profit_offset(),
+            price + tick * settings.take_profit_offset() as f64,
+        ),
+    ];
+
+    let requests = stops
+        .into_iter()
+        .filter(|(_, offset, _)| *offset != 0)
+        .map(|(stop_order_type, _, stop_price)| StopOrderRequest {
+            account_id: account.id.clone(),
+            figi: figi.clone(),
+            direction: OrderDirection::Sell,
+            stop_order_type,
+            stop_price,
+            lots: order.lots_executed,
+            expire_at: Some(expire_at),
+        })
+        .collect();
+
+    Ok(requests)
+}
+
+pub struct OrderExecutorPeriodic {
+    strategy_cache: Arc<components::StrategyCache>,
+    accounts_cache: Arc<components::AccountsCache>,
+    positions_cache: Arc<components::PositionsCache>,
+    instrument_cache: Arc<components::InstrumentCache>,
+    tinkoff_client: Arc<components::TinkoffClient>,
+    mongo: Arc<components::Mongo>,
+    use_limit_orders: bool,
+}
+
+impl ComponentName for OrderExecutorPeriodic {
+    fn component_name() -> &'static str {
+        "order-executor"
+    }
+}
+
+impl Periodic for OrderExecutorPeriodic {
+    type State = ();
+
+    fn init(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> PeriodicCreateFuture<(Self, Self::State)> {
+        Box::pin(Self::new(resolver, config))
+    }
+
+    fn step(&mut self, prev_state: Arc<Self::State>) -> PeriodicFuture<'_, Self::State> {
+        Box::pin(self.step(prev_state))
+    }
+}
+
+impl OrderExecutorPeriodic {
+    async fn new(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> Result<(Self, <Self as Periodic>::State), ComponentError> {
+        let strategy_cache = resolver.resolve::<components::StrategyCache>().await?;
+        let accounts_cache = resolver.resolve::<components::AccountsCache>().await?;
+        let positions_cache = resolver.resolve::<components::PositionsCache>().await?;
+        let instrument_cache = resolver.resolve::<components::InstrumentCache>().await?;
+        let tinkoff_client = resolver.resolve::<components::TinkoffClient>().await?;
+        let mongo = resolver.resolve::<components::Mongo>().await?;
+
+        let use_limit_orders = match config.get_str("order_type")? {
+            "market" => false,
+            "limit" => true,
+            other => {
+                return Err(ComponentError::InitializationFailed {
+                    source: format!(
+                        "Unknown order type `{}`, expected `market` or `limit`",
+                        other
+                    )
+                    .into(),
+                })
+            }
+        };
+
+        Ok((
+            Self {
+                strategy_cache,
+                accounts_cache,
+                positions_cache,
+                instrument_cache,
+                tinkoff_client,
+                mongo,
+                use_limit_orders,
+            },
+            <Self as Periodic>::State::default(),
+        ))
+    }
+
+    fn resolve_account(&self, settings: &PlaceOrderSettings) -> anyhow::Result<Account> {
+        let accounts = self.accounts_cache.state();
+        let account = accounts
+            .get(settings.account_id())
+            .ok_or_else(|| anyhow::anyhow!("Account {} not found", settings.account_id().0))?;
+
+        if !matches!(account.access_level, AccessLevel::FullAccess) {
+            return Err(anyhow::anyhow!(
+                "Account {} has no trading access",
+                settings.account_id().0
+            ));
+        }
+
+        Ok(account.clone())
+    }
+
+    /// Places stop orders around the average fill price of the executed lots
+    async fn attach_stop_orders(
+        &self,
+        account: &Account,
+        figi: &Figi,
+        order: &PostedOrder,
+        expire_at: DateTime<Utc>,
+        settings: &PlaceOrderSettings,
+    ) -> anyhow::Result<()> {
+        if matches!(account.environment, Environment::Sandbox) {
+            println!(
+                "Warning: stop orders are not supported in sandbox, none are placed for {}",
+                figi.0
+            );
+            return Ok(());
+        }
+
+        let instruments = self.instrument_cache.state();
+        let instrument = instruments
+            .get(figi)
+            .ok_or_else(|| anyhow::anyhow!("Instrument {} not found", figi.0))?;
+
+        for request in stop_order_requests(account, instrument, order, expire_at, settings)? {
+            let stop_order_id = self
+                .tinkoff_client
+                .post_stop_order(account, &request)
+                .await?;
+            println!(
+                "Placed {:?} stop order {} for {} at {}",
+                request.stop_order_type, stop_order_id, figi.0, request.stop_price
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn execute_signals(
+        &self,
+        strategy_id: &uuid::Uuid,
+        strategy_definition: &StrategyInstanceDefinition,
+        settings: &PlaceOrderSettings,
+    ) -> anyhow::Result<()> {
+        let now = Utc::now();
+
+        let cursor = match self.mongo.read_order_execution_cursor(strategy_id).await? {
+            Some(cursor) => cursor,
+            None => {
+                // Signals produced before executor has seen the instance are historical.
+                return self
+                    .mongo
+                    .write_order_execution_cursor(strategy_id, &OrderExecutionCursor::new(now))
+                    .await;
+            }
+        };
+
+        let states = self
+            .mongo
+            .read_strategy_state(strategy_id, cursor.executed_up_to, None)
+            .await?;
+
+        let (signal_ts, state) = match states
+            .into_iter()
+            .rfind(|(ts, _)| *ts > cursor.executed_up_to)
+        {
+            Some(item) => item,
+            None => return Ok(()),
+        };
+
+        let resolution = strategy_definition.resolution();
+
+        if is_stale(resolution, signal_ts, now) {
+            println!(
+                "Skipping stale signals of strategy {} at {}",
+                strategy_id, signal_ts
+            );
+
+            return self
+                .mongo
+                .write_order_execution_cursor(strategy_id, &OrderExecutionCursor::new(signal_ts))
+                .await;
+        }
+
+        // Instruments are processed in FIGI order, so that the cursor tells the ones done
+        let mut signals: Vec<(&Figi, &f64)> = state
+            .signals()
+            .iter()
+            .filter(|(figi, _)| !cursor.is_executed(signal_ts, figi))
+            .collect();
+        signals.sort_by_key(|(figi, _)| *figi);
+
+        let account = self.resolve_account(settings)?;
+        let positions = self.positions_cache.state();
+        let positions = positions.get(&account.id);
+
+        let figis: Vec<_> = signals.iter().map(|(figi, _)| (*figi).clone()).collect();
+        let last_prices = self.tinkoff_client.get_last_prices(&figis).await?;
+
+        let mut orders_placed = 0;
+
+        for (figi, signal) in signals {
+            let position_lots = positions
+                .and_then(|p| p.positions.iter().find(|pos| pos.figi == *figi))
+                .map(|pos| pos.lots)
+                .unwrap_or_default();
+
+            let (direction, lots) = match settings.decide_order(*signal, position_lots) {
+                Some(order) => order,
+                None => continue,
+            };
+
+            let last_price = last_prices
+                .get(figi)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("Last price is unknown for {}", figi.0))?;
+
+            let order_type = match self.use_limit_orders {
+                true => OrderType::Limit { price: last_price },
+                false => OrderType::Market,
+            };
+
+            let request = OrderRequest {
+                order_id: generate_order_id(strategy_id, signal_ts, figi, direction),
+                account_id: account.id.clone(),
+                figi: figi.clone(),
+                direction,
+                order_type,
+                lots,
+            };
+
+            let order = self.tinkoff_client.post_order(&account, &request).await?;
+
+            println!(
+                "Placed {:?} order {} for {} lots of {} ({:?})",
+                direction, order.order_id, lots, figi.0, order.status
+            );
+
+            let record = OrderRecord {
+                ts: Utc::now(),
+                signal_ts,
+                account_id: account.id.clone(),
+                figi: figi.clone(),
+                direction,
+                lots,
+                order,
+            };
+            self.mongo.write_order_record(strategy_id, &record).await?;
+
+            // Stop orders have no idempotency key, so progress is saved before placing them
+            // and a retry after a failure never places them twice
+            self.mongo
+                .write_order_execution_cursor(
+                    strategy_id,
+                    &OrderExecutionCursor {
+                        executed_up_to: cursor.executed_up_to,
+                        partially_executed: Some((signal_ts, figi.clone())),
+                    },
+                )
+                .await?;
+
+            orders_placed += 1;
+
+            if direction == OrderDirection::Buy {
+                let expire_at =
+                    resolution.advance(signal_ts, settings.interval_length() as i32 + 1);
+
+                if let Err(err) = self
+                    .attach_stop_orders(&account, figi, &record.order, expire_at, settings)
+                    .await
+                {
+                    println!("Failed to attach stop orders for {}: {}", figi.0, err);
+                }
+            }
+        }
+
+        self.mongo
+            .write_order_execution_cursor(strategy_id, &OrderExecutionCursor::new(signal_ts))
+            .await?;
+
+        if orders_placed > 0 {
+            self.positions_cache
+                .force_update(Some(std::time::Duration::from_millis(500)))
+                .await;
+        }
+
+        Ok(())
+    }
+
+    async fn step(
+        &mut self,
+        prev_state: Arc<<Self as Periodic>::State>,
+    ) -> anyhow::Result<Arc<<Self as Periodic>::State>> {
+        let strategies = self.strategy_cache.state();
+
+        for (strategy_id, (def, _)) in strategies.iter() {
+            // Orders are placed for live instances only
+            if def.time_to().is_some() {
+                continue;
+            }
+
+            let settings = match def.place_order_settings() {
+                Some(settings) => settings,
+                None => continue,
+            };
+
+            if let Err(err) = self.execute_signals(strategy_id, def, settings).await {
+                println!(
+                    "Failed to execute signals of strategy {}: {}",
+                    strategy_id, err
+                );
+            }
+        }
+
+        Ok(prev_state)
+    }
+}
+
+pub type OrderExecutor = PeriodicComponent<OrderExecutorPeriodic>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fake_broker::{FakeBroker, FakeMarket};
+
+    #[test]
+    fn test_stale_signals() {
+        let signal_ts = Utc.with_ymd_and_hms(2022, 3, 17, 10, 0, 0).unwrap();
+        let resolution = CandleResolution::FiveMinutes;
+
+        // Signal candle closes at 10:05, orders are placed until the next one closes
+        assert!(!is_stale(
+            resolution,
+            signal_ts,
+            Utc.with_ymd_and_hms(2022, 3, 17, 10, 5, 1).unwrap()
+        ));
+        assert!(!is_stale(
+            resolution,
+            signal_ts,
+            Utc.with_ymd_and_hms(2022, 3, 17, 10, 10, 0).unwrap()
+        ));
+        assert!(is_stale(
+            resolution,
+            signal_ts,
+            Utc.with_ymd_and_hms(2022, 3, 17, 10, 10, 1).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_order_id_idempotency() {
+        let strategy_id = uuid::Uuid::from_u128(1);
+        let signal_ts = Utc.with_ymd_and_hms(2022, 3, 17, 10, 0, 0).unwrap();
+        let figi = Figi("BBG000B9XRY4".to_owned());
+
+        let order_id = generate_order_id(&strategy_id, signal_ts, &figi, OrderDirection::Buy);
+
+        // Retries of the same signal reuse the key, so broker places the order once
+        assert_eq!(
+            order_id,
+            generate_order_id(&strategy_id, signal_ts, &figi, OrderDirection::Buy)
+        );
+        assert!(order_id.len() <= 36);
+
+        let other_ids = [
+            generate_order_id(&strategy_id, signal_ts, &figi, OrderDirection::Sell),
+            generate_order_id(
+                &strategy_id,
+                signal_ts + chrono::Duration::minutes(5),
+                &figi,
+                OrderDirection::Buy,
+            ),
+            generate_order_id(
+                &strategy_id,
+                signal_ts,
+                &Figi("BBG000BPH459".to_owned()),
+                OrderDirection::Buy,
+            ),
+            generate_order_id(
+                &uuid::Uuid::from_u128(2),
+                signal_ts,
+                &figi,
+                OrderDirection::Buy,
+            ),
+        ];
+        assert!(other_ids.iter().all(|other_id| *other_id != order_id));
+    }
+
+    #[tokio::test]
+    async fn test_stop_orders() {
+        use crate::generated::tinkoff_invest_api;
+
+        let broker = FakeBroker::new(FakeMarket::sample());
+        let account_id = broker.open_account("Fake account", 10000.0);
+        let client = broker.spawn().await;
+
+        let accounts = client.list_accounts().await.unwrap();
+        let account = &accounts[0];
+
+        let instruments = client.get_instruments().await.unwrap();
+        let instrument = &instruments[0];
+
+        let order = client
+            .post_order(
+                account,
+                &OrderRequest {
+                    order_id: "order-1".to_owned(),
+                    account_id: account.id.clone(),
+                    figi: instrument.figi.clone(),
+                    direction: OrderDirection::Buy,
+                    order_type: OrderType::Market,
+                    lots: 2,
+                },
+            )
+            .await
+            .unwrap();
+
+        let settings: PlaceOrderSettings = serde_json::from_value(serde_json::json!({
+            "accountId": account_id,
+            "stopLossOffset": 5,
+            "takeProfitOffset": 10,
+            "intervalLength": 10
+        }))
+        .unwrap();
+
+        let expire_at = Utc.with_ymd_and_hms(2022, 3, 4, 0, 0, 0).unwrap();
+        let requests =
+            stop_order_requests(account, instrument, &order, expire_at, &settings).unwrap();
+
+        for request in &requests {
+            client.post_stop_order(account, request).await.unwrap();
+        }
+
+        let mut stop_orders = broker.stop_orders(&account_id);
+        stop_orders.sort_by_key(|stop_order| stop_order.order_type);
+
+        let stop_price = |stop_order: &tinkoff_invest_api::StopOrder| {
+            let price = stop_order.stop_price.as_ref().unwrap();
+            price.units as f64 + price.nano as f64 * 1e-9
+        };
+
+        // Order is filled at 103 with a tick of 0.01
+        assert_eq!(stop_orders.len(), 2);
+        assert_eq!(
+            stop_orders[0].order_type,
+            tinkoff_invest_api::StopOrderType::TakeProfit as i32
+        );
+        assert!((stop_price(&stop_orders[0]) - 103.1).abs() < 1e-6);
+        assert_eq!(
+            stop_orders[1].order_type,
+            tinkoff_invest_api::StopOrderType::StopLoss as i32
+        );
+        assert!((stop_price(&stop_orders[1]) - 102.95).abs() < 1e-6);
+
+        for stop_order in &stop_orders {
+            assert_eq!(stop_order.lots_requested, 2);
+            assert_eq!(
+                stop_order.direction,
+                tinkoff_invest_api::StopOrderDirection::Sell as i32
+            );
+        }
+
+        // Unfilled orders get no stops
+        let unfilled = PostedOrder {
+            lots_executed: 0,
+            executed_order_price: None,
+            ..order
+        };
+        assert!(stop_order_requests(account, instrument, &unfilled, expire_at, &settings).is_err());
+    }
+}
@@ -19,7 +19,7 @@ impl ParamValidator {
         let instruments = self.instrument_cache.state();
 
         for param_name in params.keys() {
-            if param_definitions
+            if !param_definitions
                 .iter()
                 .any(|expected_param| (*expected_param).name() == param_name)
             {
@@ -28,9 +28,15 @@ impl ParamValidator {
         }
 
         for expected_param in param_definitions {
-            let actual_value = params
-                .get(expected_param.name())
-                .ok_or_else(|| ParamError::ParamMissing(expected_param.name().to_owned()))?;
+            let actual_value = match params.get(expected_param.name()) {
+                Some(value) => value,
+                // Factory falls back to default value
+                None if expected_param.default_value().is_some() => continue,
+                None if expected_param.is_optional() => continue,
+                None => {
+                    return Err(ParamError::ParamMissing(expected_param.name().to_owned()));
+                }
+            };
 
             let actual_type = ParamType::from(actual_value);
 
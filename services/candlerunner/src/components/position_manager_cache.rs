@@ -0,0 +1,91 @@
+use std::{collections::HashMap, sync::Arc};
+
+use component_store::prelude::*;
+use periodic_component::{Periodic, PeriodicComponent, PeriodicCreateFuture, PeriodicFuture};
+use uuid::Uuid;
+
+use crate::components;
+use crate::models::instance_id::InstanceId;
+use crate::models::position_manager::{PositionManager, PositionManagerInstanceDefinition};
+
+pub struct PositionManagerCachePeriodic {
+    mongo: Arc<components::Mongo>,
+    registry: Arc<components::PositionManagerRegistry>,
+}
+
+impl ComponentName for PositionManagerCachePeriodic {
+    fn component_name() -> &'static str {
+        "position-manager-cache"
+    }
+}
+
+impl Periodic for PositionManagerCachePeriodic {
+    type State = HashMap<Uuid, (PositionManagerInstanceDefinition, Arc<dyn PositionManager>)>;
+
+    fn init(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> PeriodicCreateFuture<(Self, Self::State)> {
+        Box::pin(Self::new(resolver, config))
+    }
+
+    fn step(&mut self, prev_state: Arc<Self::State>) -> PeriodicFuture<'_, Self::State> {
+        Box::pin(self.step(prev_state))
+    }
+}
+
+impl PositionManagerCachePeriodic {
+    async fn new(
+        resolver: ComponentResolver,
+        _: Box<dyn ConfigProvider>,
+    ) -> Result<(Self, <Self as Periodic>::State), ComponentError> {
+        let mongo = resolver.resolve::<components::Mongo>().await?;
+        let registry = resolver
+            .resolve::<components::PositionManagerRegistry>()
+            .await?;
+
+        Ok((
+            Self { mongo, registry },
+            <Self as Periodic>::State::default(),
+        ))
+    }
+
+    async fn step(
+        &mut self,
+        prev_state: Arc<<Self as Periodic>::State>,
+    ) -> anyhow::Result<Arc<<Self as Periodic>::State>> {
+        let defs = self.mongo.read_position_manager_instances().await?;
+
+        let mut new_state = <Self as Periodic>::State::default();
+        let mut failed = 0;
+
+        for def in defs {
+            let id = def.id();
+
+            if let Some(item) = prev_state.get(&id) {
+                new_state.insert(id, item.clone());
+                continue;
+            }
+
+            match self.registry.instantiate_position_manager(&def) {
+                Ok(instance) => {
+                    new_state.insert(id, (def, instance));
+                }
+                Err(err) => {
+                    println!("Failed to instantiate position manager: {}", err);
+                    failed += 1;
+                }
+            }
+        }
+
+        println!(
+            "Updated position manager cache: {} failed, {} total",
+            failed,
+            new_state.len()
+        );
+
+        Ok(Arc::new(new_state))
+    }
+}
+
+pub type PositionManagerCache = PeriodicComponent<PositionManagerCachePeriodic>;
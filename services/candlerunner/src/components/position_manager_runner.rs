@@ -0,0 +1,205 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use component_store::prelude::*;
+use periodic_component::{Periodic, PeriodicComponent, PeriodicCreateFuture, PeriodicFuture};
+use uuid::Uuid;
+
+use crate::components;
+use crate::models::account::AccountId;
+use crate::models::instruments::Figi;
+use crate::models::position_manager::{
+    weights_to_lots, PositionManager, PositionManagerInstanceDefinition, PositionManagerOptions,
+    StrategySignals, TargetPosition, TargetWeights,
+};
+
+const PORTFOLIO_CURRENCY: &str = "rub";
+
+pub struct PositionManagerRunnerPeriodic {
+    position_manager_cache: Arc<components::PositionManagerCache>,
+    positions_cache: Arc<components::PositionsCache>,
+    instrument_cache: Arc<components::InstrumentCache>,
+    data_provider: Arc<components::DataProvider>,
+    mongo: Arc<components::Mongo>,
+}
+
+impl ComponentName for PositionManagerRunnerPeriodic {
+    fn component_name() -> &'static str {
+        "position-manager-runner"
+    }
+}
+
+impl Periodic for PositionManagerRunnerPeriodic {
+    /// Latest target positions per position manager instance
+    type State = HashMap<Uuid, HashMap<Figi, TargetPosition>>;
+
+    fn init(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> PeriodicCreateFuture<(Self, Self::State)> {
+        Box::pin(Self::new(resolver, config))
+    }
+
+    fn step(&mut self, prev_state: Arc<Self::State>) -> PeriodicFuture<'_, Self::State> {
+        Box::pin(self.step(prev_state))
+    }
+}
+
+impl PositionManagerRunnerPeriodic {
+    async fn new(
+        resolver: ComponentResolver,
+        _: Box<dyn ConfigProvider>,
+    ) -> Result<(Self, <Self as Periodic>::State), ComponentError> {
+        let position_manager_cache = resolver
+            .resolve::<components::PositionManagerCache>()
+            .await?;
+        let positions_cache = resolver.resolve::<components::PositionsCache>().await?;
+        let instrument_cache = resolver.resolve::<components::InstrumentCache>().await?;
+        let data_provider = resolver.resolve::<components::DataProvider>().await?;
+        let mongo = resolver.resolve::<components::Mongo>().await?;
+
+        Ok((
+            Self {
+                position_manager_cache,
+                positions_cache,
+                instrument_cache,
+                data_provider,
+                mongo,
+            },
+            <Self as Periodic>::State::default(),
+        ))
+    }
+
+    async fn read_signals(
+        &self,
+        def: &PositionManagerInstanceDefinition,
+    ) -> anyhow::Result<HashMap<Uuid, StrategySignals>> {
+        let mut signals = HashMap::default();
+
+        for strategy_id in def.strategies() {
+            if let Some((_, state)) = self.mongo.read_last_strategy_state(strategy_id).await? {
+                signals.insert(*strategy_id, state.signals().clone());
+            }
+        }
+
+        Ok(signals)
+    }
+
+    async fn weights_to_positions(
+        &self,
+        account_id: &AccountId,
+        weights: &TargetWeights,
+    ) -> anyhow::Result<HashMap<Figi, TargetPosition>> {
+        let positions = self.positions_cache.state();
+        let positions = positions
+            .get(account_id)
+            .ok_or_else(|| anyhow::anyhow!("Positions of account {} are unknown", account_id.0))?;
+
+        let instruments = self.instrument_cache.state();
+
+        let figis: Vec<Figi> = weights
+            .keys()
+            .chain(positions.positions.iter().map(|position| &position.figi))
+            .cloned()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        let prices = self
+            .data_provider
+            .market_data()
+            .get_last_prices(&figis)
+            .await?;
+        let lot_sizes: HashMap<Figi, i64> = figis
+            .iter()
+            .filter_map(|figi| {
+                instruments
+                    .get(figi)
+                    .map(|instrument| (figi.clone(), instrument.lot))
+            })
+            .collect();
+
+        let cash: f64 = positions
+            .currencies
+            .iter()
+            .filter(|currency| currency.iso_currency == PORTFOLIO_CURRENCY)
+            .map(|currency| currency.amount)
+            .sum();
+
+        let securities: f64 = positions
+            .positions
+            .iter()
+            .map(|position| {
+                let price = prices.get(&position.figi).cloned().unwrap_or_default();
+                let lot = lot_sizes.get(&position.figi).cloned().unwrap_or_default();
+
+                position.lots as f64 * lot as f64 * price
+            })
+            .sum();
+
+        let lots = weights_to_lots(weights, cash + securities, &prices, &lot_sizes);
+
+        Ok(weights
+            .iter()
+            .map(|(figi, weight)| {
+                (
+                    figi.clone(),
+                    TargetPosition {
+                        weight: *weight,
+                        lots: lots.get(figi).cloned(),
+                    },
+                )
+            })
+            .collect())
+    }
+
+    async fn target_positions(
+        &self,
+        def: &PositionManagerInstanceDefinition,
+        position_manager: &dyn PositionManager,
+    ) -> anyhow::Result<HashMap<Figi, TargetPosition>> {
+        let signals = self.read_signals(def).await?;
+        let weights = position_manager.target_weights(&signals);
+
+        match def.options() {
+            PositionManagerOptions::Realtime { account_id } => {
+                self.weights_to_positions(account_id, &weights).await
+            }
+            PositionManagerOptions::Backtest {} => Ok(weights
+                .into_iter()
+                .map(|(figi, weight)| (figi, TargetPosition { weight, lots: None }))
+                .collect()),
+        }
+    }
+
+    async fn step(
+        &mut self,
+        prev_state: Arc<<Self as Periodic>::State>,
+    ) -> anyhow::Result<Arc<<Self as Periodic>::State>> {
+        let instances = self.position_manager_cache.state();
+        let mut new_state = <Self as Periodic>::State::default();
+
+        for (id, (def, position_manager)) in instances.iter() {
+            match self.target_positions(def, position_manager.as_ref()).await {
+                Ok(positions) => {
+                    new_state.insert(*id, positions);
+                }
+                Err(err) => {
+                    println!(
+                        "Failed to compute target positions of position manager {}: {}",
+                        id, err
+                    );
+
+                    // Keep last known targets
+                    if let Some(positions) = prev_state.get(id) {
+                        new_state.insert(*id, positions.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(Arc::new(new_state))
+    }
+}
+
+pub type PositionManagerRunner = PeriodicComponent<PositionManagerRunnerPeriodic>;
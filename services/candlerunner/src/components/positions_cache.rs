@@ -39,7 +39,7 @@ impl Periodic for PositionsCachePeriodic {
         })
     }
 
-    fn step(&mut self, state: Arc<Self::State>) -> periodic_component::PeriodicFuture<Self::State> {
+    fn step(&mut self, state: Arc<Self::State>) -> periodic_component::PeriodicFuture<'_, Self::State> {
         Box::pin(async move {
             let accounts_cache = self.accounts_cache.state();
 
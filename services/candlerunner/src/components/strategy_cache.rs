@@ -30,7 +30,7 @@ impl Periodic for StrategyCachePeriodic {
         Box::pin(Self::new(resolver, config))
     }
 
-    fn step(&mut self, prev_state: Arc<Self::State>) -> PeriodicFuture<Self::State> {
+    fn step(&mut self, prev_state: Arc<Self::State>) -> PeriodicFuture<'_, Self::State> {
         Box::pin(self.step(prev_state))
     }
 }
@@ -76,6 +76,9 @@ impl StrategyRegistry {
         self.param_validator
             .validate(factory.definition().params(), instance_definition.params())?;
 
+        // Requirements are only known once the strategy is created
+        self.instantiate_strategy(instance_definition.clone())?;
+
         Ok(())
     }
 
@@ -90,7 +93,19 @@ impl StrategyRegistry {
                 InstantiateStrategyError::NotFound(instance_definition.strategy_name().to_string())
             })?;
 
-        factory.create(instance_definition.params())
+        let strategy = factory.create(instance_definition.params())?;
+
+        // Series candles must close together with candles of the instance
+        for (_, resolution) in strategy.series_requirements() {
+            if !resolution.is_built_from(instance_definition.resolution()) {
+                return Err(InstantiateStrategyError::UnsupportedResolution(
+                    *resolution,
+                    instance_definition.resolution(),
+                ));
+            }
+        }
+
+        Ok(strategy)
     }
 
     async fn new(
@@ -99,7 +114,16 @@ impl StrategyRegistry {
     ) -> Result<Self, ComponentError> {
         Ok(Self {
             factories: Builder::default()
+                .register(strategies::BollingerSqueezeFactory::default())
                 .register(strategies::BuyAndHoldFactory::default())
+                .register(strategies::DonchianBreakoutFactory::default())
+                .register(strategies::MaCrossoverFactory::default())
+                .register(strategies::MacdCrossoverFactory::default())
+                .register(strategies::MomentumRotationFactory::default())
+                .register(strategies::PairsTradingFactory::default())
+                .register(strategies::RsiMeanReversionFactory::default())
+                .register(strategies::StochasticReversalFactory::default())
+                .register(strategies::VwapReversionFactory::default())
                 .build(),
             param_validator: resolver.resolve::<components::ParamValidator>().await?,
         })
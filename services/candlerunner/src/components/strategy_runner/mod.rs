@@ -1,4 +1,6 @@
+#[allow(clippy::module_inception)]
 mod strategy_runner;
 mod read_market_data;
 
 pub use strategy_runner::StrategyRunner;
+pub use read_market_data::read_market_data;
@@ -1,89 +1,183 @@
-use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 
-use chrono::{prelude::*, Duration};
+use chrono::prelude::*;
 
 use crate::components;
+use crate::models::corporate_actions::{adjust_candles, PriceAdjustment};
 use crate::models::instruments::Figi;
-use crate::models::market_data::{Candle, CandlePack, CandleResolution, DataAvailability};
+use crate::models::market_data::{
+    Candle, CandlePack, CandleResolution, CandleTimeline, DataAvailability,
+};
+use crate::models::strategy::ExecutionContext;
+use crate::models::trading_calendar::TradingCalendar;
 
 fn interpolate(
     candle_resolution: CandleResolution,
     data: BTreeMap<DateTime<Utc>, Candle>,
 ) -> BTreeMap<DateTime<Utc>, Candle> {
     let now = Utc::now();
-    let candle_interval = Duration::from(candle_resolution);
 
     let mut result: BTreeMap<DateTime<Utc>, Candle> = Default::default();
 
     for (ts, candle) in data {
-        let ts = align_timestamp(candle_interval, ts);
-        if (now - ts) < candle_interval {
+        let ts = candle_resolution.align(ts);
+
+        // Incomplete candle
+        if candle_resolution.advance(ts, 1) > now {
             continue;
         }
 
-        let interpolated_candle = result.entry(ts).or_insert(candle);
+        match result.entry(ts) {
+            Entry::Vacant(entry) => {
+                entry.insert(candle);
+            }
+            Entry::Occupied(mut entry) => {
+                let interpolated_candle = entry.get_mut();
 
-        interpolated_candle.high = interpolated_candle.high.max(candle.high);
-        interpolated_candle.low = interpolated_candle.low.min(candle.low);
-        // interpolated_candle.open stays as is
-        interpolated_candle.close = candle.close;
-        interpolated_candle.volume += candle.volume;
+                interpolated_candle.high = interpolated_candle.high.max(candle.high);
+                interpolated_candle.low = interpolated_candle.low.min(candle.low);
+                // interpolated_candle.open stays as is
+                interpolated_candle.close = candle.close;
+                interpolated_candle.volume += candle.volume;
+            }
+        }
     }
 
     result
 }
 
-fn align_timestamp(candle_interval: Duration, ts: DateTime<Utc>) -> DateTime<Utc> {
-    let midnight = ts.date().and_hms(0, 0, 0);
-    let since_midnight = ts - midnight;
-    let n_candles = (since_midnight.num_minutes() / candle_interval.num_minutes()) as i32;
+/// Returns the end of continuously available data starting from `time_from`, capped by `time_to`.
+/// Days the exchange is closed on do not interrupt the data.
+/// The day `time_to` falls in is checked as well, so a day in progress holds the range back.
+fn available_up_to(
+    availability_timeline: &BTreeMap<NaiveDate, DataAvailability>,
+    calendar: Option<&TradingCalendar>,
+    time_from: DateTime<Utc>,
+    time_to: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let mut cur_date = time_from.date_naive();
+
+    while cur_date.and_time(NaiveTime::MIN).and_utc() < time_to {
+        if calendar.is_some_and(|calendar| calendar.is_closed(cur_date)) {
+            cur_date = cur_date.succ_opt().unwrap();
+            continue;
+        }
+
+        let availability = availability_timeline
+            .get(&cur_date)
+            .unwrap_or(&DataAvailability::Unavailable);
 
-    midnight + candle_interval * n_candles
+        match availability {
+            DataAvailability::Unavailable => {
+                return time_to.min(cur_date.and_time(NaiveTime::MIN).and_utc());
+            }
+            DataAvailability::Available | DataAvailability::NonTrading => (),
+            DataAvailability::PartiallyAvailable { available_up_to } => {
+                return time_to.min(*available_up_to);
+            }
+        }
+
+        cur_date = cur_date.succ_opt().unwrap();
+    }
+
+    time_to
 }
 
+/// Reads candles of the requirements packed by timestamp.
+/// Candles of each instrument are assembled from the coarsest stored series
+/// covering the longest part of the requested range.
+/// Prices are adjusted for stored corporate actions selected by `adjustment`.
 pub async fn read_market_data(
     mongo: &components::Mongo,
+    calendars: &HashMap<Figi, Arc<TradingCalendar>>,
     requirements: &[Figi],
     time_from: DateTime<Utc>,
-    mut time_to: DateTime<Utc>,
+    time_to: DateTime<Utc>,
     candle_resolution: CandleResolution,
+    adjustment: PriceAdjustment,
 ) -> anyhow::Result<BTreeMap<DateTime<Utc>, CandlePack>> {
-    let time_from = align_timestamp(candle_resolution.into(), time_from);
+    let (_, packed_candles) = read_market_data_window(
+        mongo,
+        calendars,
+        requirements,
+        time_from,
+        time_to,
+        candle_resolution,
+        adjustment,
+    )
+    .await?;
 
-    for figi in requirements {
-        let availability_timeline = mongo.read_candle_data_availability(figi).await?;
+    Ok(packed_candles)
+}
 
-        let mut cur_date = time_from.date();
+/// Same as `read_market_data`, but also returns the end of the range data was read up to.
+/// It precedes `time_to` when the data is not available further yet.
+pub async fn read_market_data_window(
+    mongo: &components::Mongo,
+    calendars: &HashMap<Figi, Arc<TradingCalendar>>,
+    requirements: &[Figi],
+    time_from: DateTime<Utc>,
+    mut time_to: DateTime<Utc>,
+    candle_resolution: CandleResolution,
+    adjustment: PriceAdjustment,
+) -> anyhow::Result<(DateTime<Utc>, BTreeMap<DateTime<Utc>, CandlePack>)> {
+    let time_from = candle_resolution.align(time_from);
 
-        while cur_date < time_to.date() {
-            let availability = availability_timeline
-                .get(&cur_date)
-                .unwrap_or(&DataAvailability::Unavailable);
+    let mut sources: Vec<(&Figi, CandleResolution)> = Vec::with_capacity(requirements.len());
 
-            match availability {
-                DataAvailability::Unavailable => {
-                    time_to = time_to.min(cur_date.and_hms(0, 0, 0));
-                    break;
-                }
-                DataAvailability::Available => (),
-                DataAvailability::PartiallyAvailable { available_up_to } => {
-                    time_to = time_to.min(*available_up_to);
-                    break;
-                }
+    for figi in requirements {
+        let mut best: Option<(CandleResolution, DateTime<Utc>)> = None;
+        let calendar = calendars.get(figi).map(AsRef::as_ref);
+
+        for source in candle_resolution.sources() {
+            let availability_timeline = mongo.read_candle_data_availability(figi, source).await?;
+            let available_to =
+                available_up_to(&availability_timeline, calendar, time_from, time_to);
+
+            if best.is_none_or(|(_, best_to)| available_to > best_to) {
+                best = Some((source, available_to));
             }
 
-            cur_date = cur_date.succ();
+            if available_to >= time_to {
+                break;
+            }
         }
+
+        let (source, available_to) = match best {
+            Some(best) => best,
+            None => return Ok((time_from, Default::default())),
+        };
+
+        time_to = time_to.min(available_to);
+        sources.push((figi, source));
     }
 
     if time_to <= time_from {
-        return Ok(Default::default());
+        return Ok((time_from, Default::default()));
     }
 
+    let actions = match adjustment.is_none() {
+        true => vec![],
+        false => mongo.read_corporate_actions(requirements).await?,
+    };
+
     let mut packed_candles: BTreeMap<DateTime<Utc>, CandlePack> = Default::default();
 
-    for figi in requirements {
-        let data = mongo.read_candles(figi, time_from, time_to).await?;
+    for (figi, source) in sources {
+        let mut data = mongo.read_candles(figi, source, time_from, time_to).await?;
+
+        if !adjustment.is_none() {
+            let figi_actions: Vec<_> = actions
+                .iter()
+                .filter(|action| action.figi == *figi)
+                .cloned()
+                .collect();
+
+            adjust_candles(&mut data, &figi_actions, adjustment);
+        }
+
         let candles_timeline = interpolate(candle_resolution, data);
 
         for (ts, candle) in candles_timeline {
@@ -92,5 +186,216 @@ pub async fn read_market_data(
         }
     }
 
-    Ok(packed_candles)
+    Ok((time_to, packed_candles))
+}
+
+/// Steps over packed candles attaching the latest candles of `series`
+/// which are closed by the end of each step
+pub fn build_contexts(
+    packed_candles: BTreeMap<DateTime<Utc>, CandlePack>,
+    resolution: CandleResolution,
+    series: &HashMap<(Figi, CandleResolution), CandleTimeline>,
+) -> Vec<ExecutionContext> {
+    let mut cursors: Vec<_> = series
+        .iter()
+        .map(|(key, timeline)| (key, timeline.iter().peekable()))
+        .collect();
+    let mut latest: HashMap<(Figi, CandleResolution), (DateTime<Utc>, Candle)> = Default::default();
+
+    packed_candles
+        .into_iter()
+        .map(|(ts, candles)| {
+            let step_end = resolution.advance(ts, 1);
+
+            for ((figi, series_resolution), cursor) in cursors.iter_mut() {
+                while let Some((start, candle)) =
+                    cursor.next_if(|(start, _)| series_resolution.advance(**start, 1) <= step_end)
+                {
+                    latest.insert((figi.clone(), *series_resolution), (*start, *candle));
+                }
+            }
+
+            let mut context = ExecutionContext::new(ts, candles);
+            context.series = latest.clone();
+            context
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::components::market_data_stream::streamed_availability;
+    use crate::models::trading_calendar::TradingDay;
+
+    #[test]
+    fn test_available_up_to() {
+        let timeline = [
+            (
+                NaiveDate::from_ymd_opt(2022, 1, 6).unwrap(),
+                DataAvailability::Available,
+            ),
+            (
+                NaiveDate::from_ymd_opt(2022, 1, 7).unwrap(),
+                DataAvailability::Available,
+            ),
+            (
+                NaiveDate::from_ymd_opt(2022, 1, 8).unwrap(),
+                DataAvailability::NonTrading,
+            ),
+            (
+                NaiveDate::from_ymd_opt(2022, 1, 10).unwrap(),
+                DataAvailability::PartiallyAvailable {
+                    available_up_to: Utc.with_ymd_and_hms(2022, 1, 10, 12, 0, 0).unwrap(),
+                },
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let time_from = Utc.with_ymd_and_hms(2022, 1, 6, 10, 0, 0).unwrap();
+        let time_to = Utc.with_ymd_and_hms(2022, 1, 11, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            available_up_to(&timeline, None, time_from, time_to),
+            Utc.with_ymd_and_hms(2022, 1, 9, 0, 0, 0).unwrap()
+        );
+
+        let closed = TradingDay {
+            is_trading_day: false,
+            start_time: None,
+            end_time: None,
+        };
+        let calendar = TradingCalendar::new(
+            [(NaiveDate::from_ymd_opt(2022, 1, 9).unwrap(), closed)]
+                .into_iter()
+                .collect(),
+        );
+
+        assert_eq!(
+            available_up_to(&timeline, Some(&calendar), time_from, time_to),
+            Utc.with_ymd_and_hms(2022, 1, 10, 12, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_streamed_source() {
+        let date = NaiveDate::from_ymd_opt(2022, 3, 3).unwrap();
+        let time_from = date.and_hms_opt(10, 0, 0).unwrap().and_utc();
+        let now = date.and_hms_opt(12, 0, 5).unwrap().and_utc();
+
+        // market-data-sync polled both sources at 11:29, the 1h candle being formed
+        // holds the polled source back until the next poll
+        let hourly = [(
+            date,
+            DataAvailability::PartiallyAvailable {
+                available_up_to: date.and_hms_opt(11, 0, 0).unwrap().and_utc(),
+            },
+        )]
+        .into_iter()
+        .collect();
+        let mut streamed = [(
+            date,
+            DataAvailability::PartiallyAvailable {
+                available_up_to: date.and_hms_opt(11, 25, 0).unwrap().and_utc(),
+            },
+        )]
+        .into_iter()
+        .collect::<BTreeMap<_, _>>();
+
+        let candle = |close: f64| Candle {
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1,
+        };
+        let five_minutes: CandleTimeline = (0..24)
+            .map(|i| {
+                let ts = CandleResolution::FiveMinutes.advance(time_from, i);
+                (ts, candle(i as f64))
+            })
+            .collect();
+
+        // The stream was subscribed during the 11:25 candle
+        let streamed_since = date.and_hms_opt(11, 25, 0).unwrap().and_utc();
+
+        for ts in five_minutes.keys().filter(|ts| **ts >= streamed_since) {
+            let candle_end = CandleResolution::FiveMinutes.advance(*ts, 1);
+            let availability = streamed[&date].clone();
+
+            if let Some(availability) =
+                streamed_availability(availability, streamed_since, candle_end)
+            {
+                streamed.insert(date, availability);
+            }
+        }
+
+        assert_eq!(
+            available_up_to(&hourly, None, time_from, now),
+            date.and_hms_opt(11, 0, 0).unwrap().and_utc()
+        );
+        assert_eq!(
+            available_up_to(&streamed, None, time_from, now),
+            date.and_hms_opt(12, 0, 0).unwrap().and_utc()
+        );
+
+        // The 11:00 candle of a 1h instance is built from the streamed candles
+        let hourly_candles = interpolate(CandleResolution::OneHour, five_minutes);
+        assert_eq!(
+            hourly_candles.keys().next_back(),
+            Some(&date.and_hms_opt(11, 0, 0).unwrap().and_utc())
+        );
+        assert_eq!(
+            hourly_candles[&date.and_hms_opt(11, 0, 0).unwrap().and_utc()].close,
+            23.0
+        );
+    }
+
+    #[test]
+    fn test_build_contexts() {
+        let figi = Figi("FIGI".to_owned());
+        let candle = |close: f64| Candle {
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 1,
+        };
+
+        let packed_candles = [9, 22, 23]
+            .into_iter()
+            .map(|hour| {
+                let pack = [(figi.clone(), candle(hour as f64))].into_iter().collect();
+                (Utc.with_ymd_and_hms(2022, 1, 4, hour, 0, 0).unwrap(), pack)
+            })
+            .collect();
+
+        let daily: CandleTimeline = [3, 4]
+            .into_iter()
+            .map(|day| {
+                (
+                    Utc.with_ymd_and_hms(2022, 1, day, 0, 0, 0).unwrap(),
+                    candle(day as f64),
+                )
+            })
+            .collect();
+        let series = [((figi.clone(), CandleResolution::OneDay), daily)]
+            .into_iter()
+            .collect();
+
+        let contexts = build_contexts(packed_candles, CandleResolution::OneHour, &series);
+
+        // The daily candle of the 4th is seen only by the step closing the day
+        let closes: Vec<_> = contexts
+            .iter()
+            .map(|context| {
+                context
+                    .series_candle(&figi, CandleResolution::OneDay)
+                    .map(|candle| candle.close)
+            })
+            .collect();
+        assert_eq!(closes, [Some(3.0), Some(3.0), Some(4.0)]);
+    }
 }
@@ -1,21 +1,66 @@
-use std::sync::Arc;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
 
-use chrono::prelude::*;
+use chrono::{prelude::*, Duration};
+use futures::{FutureExt, StreamExt};
 
 use component_store::prelude::*;
 use periodic_component::{Periodic, PeriodicComponent, PeriodicCreateFuture, PeriodicFuture};
+use tokio::sync::{broadcast, Semaphore};
+use tokio::task::JoinHandle;
 
 use crate::components;
+use crate::models::instruments::Figi;
+use crate::models::market_data::{CandleResolution, CandleTimeline};
 use crate::models::strategy::{
-    Strategy, StrategyExecution, StrategyExecutionError, StrategyExecutionStatus,
-    StrategyInstanceDefinition, StrategyState,
+    required_series, ExecutionContext, ExecutionRevision, FailureKind, Strategy, StrategyExecution,
+    StrategyExecutionError, StrategyExecutionStatus, StrategyInstanceDefinition, StrategyState,
+    SERIES_LOOKBACK_CANDLES,
 };
 
 use super::read_market_data;
 
-pub struct StrategyRunnerPeriodic {
+/// Instances with `time_to` in the past are finished once no candles arrive for this long,
+/// so that data still being synced is not missed
+const FINISH_GRACE_PERIOD_HOURS: i64 = 24;
+
+/// Execution restored from the last flushed chunk
+struct Checkpoint {
+    /// Revision of the stored execution, `None` if there is none yet
+    revision: Option<ExecutionRevision>,
+    execution: StrategyExecution,
+    last_state: StrategyState,
+
+    /// First timestamp not executed yet
+    resume_from: DateTime<Utc>,
+}
+
+/// Executes instances, shared by periodic steps and the market data events listener
+struct InstanceExecutor {
     strategy_cache: Arc<components::StrategyCache>,
+    trading_calendar_cache: Arc<components::TradingCalendarCache>,
     mongo: Arc<components::Mongo>,
+
+    /// Number of candles executed between checkpoints
+    chunk_size: i32,
+
+    /// Limits number of instances executed at once
+    permits: Semaphore,
+
+    /// Keeps an instance from being executed by a step and on an event at once
+    instance_locks: Mutex<HashMap<uuid::Uuid, Arc<tokio::sync::Mutex<()>>>>,
+
+    /// Longest time a chunk may take to execute before the instance is failed
+    execution_timeout: std::time::Duration,
+}
+
+/// Executes instances once their data is stored and polls all of them periodically,
+/// so that nothing is missed if the events are lost
+pub struct StrategyRunnerPeriodic {
+    executor: Arc<InstanceExecutor>,
+    events_task: JoinHandle<()>,
 }
 
 impl ComponentName for StrategyRunnerPeriodic {
@@ -34,7 +79,7 @@ impl Periodic for StrategyRunnerPeriodic {
         Box::pin(Self::new(resolver, config))
     }
 
-    fn step(&mut self, prev_state: Arc<Self::State>) -> PeriodicFuture<Self::State> {
+    fn step(&mut self, prev_state: Arc<Self::State>) -> PeriodicFuture<'_, Self::State> {
         Box::pin(self.step(prev_state))
     }
 }
@@ -42,26 +87,127 @@ impl Periodic for StrategyRunnerPeriodic {
 impl StrategyRunnerPeriodic {
     async fn new(
         resolver: ComponentResolver,
-        _: Box<dyn ConfigProvider>,
+        config: Box<dyn ConfigProvider>,
     ) -> Result<(Self, <Self as Periodic>::State), ComponentError> {
         let strategy_cache = resolver.resolve::<components::StrategyCache>().await?;
+        let trading_calendar_cache = resolver
+            .resolve::<components::TradingCalendarCache>()
+            .await?;
         let mongo = resolver.resolve::<components::Mongo>().await?;
+        let market_data_events = resolver.resolve::<components::MarketDataEvents>().await?;
+
+        let chunk_size = config.get_u64("chunk_size")?.clamp(1, i32::MAX as u64) as i32;
+        let max_concurrency = config.get_u64("max_concurrency")?.max(1) as usize;
+        let execution_timeout =
+            std::time::Duration::from_secs(config.get_u64("execution_timeout")?);
+
+        let executor = Arc::new(InstanceExecutor {
+            strategy_cache,
+            trading_calendar_cache,
+            mongo,
+            chunk_size,
+            permits: Semaphore::new(max_concurrency),
+            instance_locks: Default::default(),
+            execution_timeout,
+        });
+
+        let events_task = tokio::spawn(listen_events(
+            executor.clone(),
+            market_data_events.subscribe(),
+        ));
 
         Ok((
             Self {
-                strategy_cache,
-                mongo,
+                executor,
+                events_task,
             },
             <Self as Periodic>::State::default(),
         ))
     }
 
+    async fn step(
+        &mut self,
+        prev_state: Arc<<Self as Periodic>::State>,
+    ) -> anyhow::Result<Arc<<Self as Periodic>::State>> {
+        self.executor.exec_instances(|_, _| true).await;
+
+        Ok(prev_state)
+    }
+}
+
+impl Drop for StrategyRunnerPeriodic {
+    fn drop(&mut self) {
+        self.events_task.abort();
+    }
+}
+
+/// Series of an instance are assembled from the stored data,
+/// so its candles may be updated by any finer resolution it is built from
+fn is_series_updated(
+    updated: &HashSet<(Figi, CandleResolution)>,
+    figi: &Figi,
+    resolution: CandleResolution,
+) -> bool {
+    updated.iter().any(|(updated_figi, updated_resolution)| {
+        updated_figi == figi && resolution.is_built_from(*updated_resolution)
+    })
+}
+
+/// Executes instances requiring series new data is stored for
+async fn listen_events(
+    executor: Arc<InstanceExecutor>,
+    mut events: broadcast::Receiver<components::DataAvailable>,
+) {
+    // Data is synced repeatedly, only events moving availability forward wake instances
+    let mut available_up_to: HashMap<(Figi, CandleResolution), DateTime<Utc>> = Default::default();
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                // Instances of the missed events are executed by the next step
+                println!("Strategy runner missed {} market data events", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => return,
+        };
+
+        // Events arrived during the previous execution are handled at once
+        let mut updated: HashSet<(Figi, CandleResolution)> = Default::default();
+        let mut next_event = Some(event);
+
+        while let Some(event) = next_event {
+            let key = (event.figi, event.resolution);
+
+            let is_new = available_up_to
+                .get(&key)
+                .is_none_or(|known| *known < event.available_up_to);
+
+            if is_new {
+                available_up_to.insert(key.clone(), event.available_up_to);
+                updated.insert(key);
+            }
+
+            next_event = events.try_recv().ok();
+        }
+
+        if updated.is_empty() {
+            continue;
+        }
+
+        executor
+            .exec_instances(|figi, resolution| is_series_updated(&updated, figi, resolution))
+            .await;
+    }
+}
+
+impl InstanceExecutor {
     async fn init_execution(
         &self,
         strategy_id: &uuid::Uuid,
         strategy_definition: &StrategyInstanceDefinition,
-    ) -> anyhow::Result<(StrategyExecution, StrategyState)> {
-        let mut execution = self
+    ) -> anyhow::Result<Checkpoint> {
+        let stored_execution = self
             .mongo
             .read_strategy_execution(strategy_id)
             .await
@@ -71,48 +217,117 @@ impl StrategyRunnerPeriodic {
                     strategy_id,
                     err.to_string()
                 )
-            })?
-            .unwrap_or_else(|| {
-                StrategyExecution::new(
-                    StrategyExecutionStatus::Running,
-                    strategy_definition.time_from(),
-                )
-            });
+            })?;
+
+        let revision = stored_execution.as_ref().map(StrategyExecution::revision);
+
+        let mut execution = stored_execution.unwrap_or_else(|| {
+            StrategyExecution::new(
+                StrategyExecutionStatus::Running,
+                strategy_definition.time_from(),
+            )
+        });
 
-        let states = self
+        // States are flushed before the checkpoint, so some may be past it after a crash
+        let last_state = self
             .mongo
             .read_strategy_state(
                 strategy_id,
                 execution.last_execution_timestamp(),
                 strategy_definition.time_to(),
             )
-            .await?;
-
-        let (last_execution_timestamp, last_state) = states
+            .await?
             .into_iter()
-            .last()
-            .map(|(ts, state)| (ts, state))
-            .unwrap_or_else(|| {
-                (
-                    execution.last_execution_timestamp(),
-                    StrategyState::default(),
+            .next_back();
+
+        let (last_state, resume_from) = match last_state {
+            Some((ts, state)) => {
+                execution.set_last_execution_timestamp(ts);
+                (state, strategy_definition.resolution().advance(ts, 1))
+            }
+            None => (
+                StrategyState::default(),
+                execution.last_execution_timestamp(),
+            ),
+        };
+
+        Ok(Checkpoint {
+            revision,
+            execution,
+            last_state,
+            resume_from,
+        })
+    }
+
+    /// Flushes states computed since the previous checkpoint, then the checkpoint itself.
+    /// Returns `false` if the instance has been changed through the API meanwhile,
+    /// its results are outdated then and are dropped.
+    async fn write_checkpoint(
+        &self,
+        strategy_id: &uuid::Uuid,
+        revision: Option<ExecutionRevision>,
+        execution: &StrategyExecution,
+        states: Vec<(DateTime<Utc>, StrategyState)>,
+    ) -> anyhow::Result<bool> {
+        let has_states = !states.is_empty();
+
+        self.mongo
+            .write_strategy_state(strategy_id, states)
+            .await
+            .map_err(|err| {
+                anyhow::anyhow!(
+                    "Failed to write strategy states for strategy {}: {}",
+                    strategy_id,
+                    err.to_string()
                 )
-            });
+            })?;
 
-        if last_execution_timestamp > execution.last_execution_timestamp() {
-            execution.set_last_execution_timestamp(last_execution_timestamp);
+        let is_written = self
+            .mongo
+            .write_strategy_execution_if(strategy_id, execution, revision)
+            .await
+            .map_err(|err| {
+                anyhow::anyhow!(
+                    "Failed to update execution status for strategy {}: {}",
+                    strategy_id,
+                    err.to_string()
+                )
+            })?;
+
+        if is_written || !has_states {
+            return Ok(is_written);
         }
 
-        Ok((execution, last_state))
+        // States of a paused or stopped execution are picked up on resume as after a crash,
+        // but a reset may have wiped the states before these are inserted. Only the runner
+        // holding the instance lock writes states, so none of the new generation exist yet.
+        let current_generation = self
+            .mongo
+            .read_strategy_execution(strategy_id)
+            .await?
+            .map(|execution| execution.generation());
+
+        if current_generation != Some(execution.generation()) {
+            self.mongo.delete_strategy_states(strategy_id).await?;
+        }
+
+        Ok(false)
     }
 
+    /// Executes the instance up to now or its `time_to` in windows of `chunk_size` candles,
+    /// so that only one window is kept in memory and progress is saved after each one
     async fn exec_strategy(
         &self,
         strategy_id: &uuid::Uuid,
         strategy_definition: &StrategyInstanceDefinition,
-        strategy: &dyn Strategy,
+        strategy: &Arc<dyn Strategy>,
     ) -> anyhow::Result<()> {
-        let (mut execution, mut last_state) = self
+        let Checkpoint {
+            mut revision,
+            mut execution,
+            mut last_state,
+            resume_from: mut chunk_from,
+        } = self
             .init_execution(strategy_id, strategy_definition)
             .await?;
 
@@ -125,81 +340,298 @@ impl StrategyRunnerPeriodic {
             None => Utc::now(),
         };
 
-        let data_requirements = strategy.data_requirements();
-        let packed_candles = read_market_data::read_market_data(
-            self.mongo.as_ref(),
-            data_requirements,
-            execution.last_execution_timestamp(),
-            time_to,
-            strategy_definition.resolution(),
-        )
-        .await?;
-
-        let mut states: Vec<(DateTime<Utc>, StrategyState)> = Default::default();
-
-        // TODO: execute strategies in chunks
-        for (ts, candles) in packed_candles {
-            let state = strategy.execute(ts, candles, last_state);
-
-            match state {
-                Ok(state) => {
-                    states.push((ts, state.clone()));
-                    last_state = state;
-                    execution.set_last_execution_timestamp(ts);
-                }
-                Err(err) => {
-                    if let StrategyExecutionError::CriticalFailure = err {
-                        execution.set_status(StrategyExecutionStatus::Failed);
+        let resolution = strategy_definition.resolution();
+        let adjustment = strategy_definition.price_adjustment();
+        let mut executed = 0;
+
+        while chunk_from < time_to {
+            let chunk_to = resolution.advance(chunk_from, self.chunk_size).min(time_to);
+
+            let calendars = self.trading_calendar_cache.state();
+
+            let (mut read_to, mut packed_candles) = read_market_data::read_market_data_window(
+                self.mongo.as_ref(),
+                &calendars,
+                strategy.data_requirements(),
+                chunk_from,
+                chunk_to,
+                resolution,
+                adjustment,
+            )
+            .await?;
+
+            let mut series_figis: HashMap<CandleResolution, Vec<Figi>> = Default::default();
+            for (figi, series_resolution) in strategy.series_requirements() {
+                series_figis
+                    .entry(*series_resolution)
+                    .or_default()
+                    .push(figi.clone());
+            }
+
+            let mut series: HashMap<(Figi, CandleResolution), CandleTimeline> = Default::default();
+
+            for (series_resolution, figis) in series_figis {
+                let (series_read_to, series_packs) = read_market_data::read_market_data_window(
+                    self.mongo.as_ref(),
+                    &calendars,
+                    &figis,
+                    series_resolution.advance(chunk_from, -SERIES_LOOKBACK_CANDLES),
+                    chunk_to,
+                    series_resolution,
+                    adjustment,
+                )
+                .await?;
+
+                // A coarse candle still being formed does not hold the steps back,
+                // but the ones missing from the store do
+                read_to = read_to.min(series_resolution.advance(series_read_to, 1));
+
+                for (ts, pack) in series_packs {
+                    for (figi, candle) in pack {
+                        series
+                            .entry((figi, series_resolution))
+                            .or_default()
+                            .insert(ts, candle);
                     }
+                }
+            }
+
+            packed_candles.split_off(&read_to);
 
-                    println!("Strategy execution failed: {}", err);
-                    break;
+            let contexts = read_market_data::build_contexts(packed_candles, resolution, &series);
+
+            // Strategies are CPU-bound, so they are kept off the runtime threads
+            let execute_chunk = tokio::task::spawn_blocking({
+                let strategy = strategy.clone();
+                let state = std::mem::take(&mut last_state);
+
+                move || execute_chunk(strategy.as_ref(), contexts, state)
+            });
+
+            let ChunkOutcome {
+                states,
+                last_state: state,
+                failure,
+            } = match tokio::time::timeout(self.execution_timeout, execute_chunk).await {
+                Ok(outcome) => outcome?,
+                Err(_) => {
+                    // Blocking tasks can not be cancelled, the hung one is abandoned
+                    let message = format!(
+                        "Execution timed out after {} seconds",
+                        self.execution_timeout.as_secs()
+                    );
+                    println!("Strategy {} failed: {}", strategy_id, message);
+
+                    execution.fail(FailureKind::Timeout, message, None, Utc::now())?;
+                    self.write_checkpoint(strategy_id, revision, &execution, vec![])
+                        .await?;
+
+                    return Ok(());
                 }
+            };
+
+            last_state = state;
+
+            if let Some((ts, _)) = states.last() {
+                execution.set_last_execution_timestamp(*ts);
             }
+
+            let is_interrupted = match failure {
+                Some((kind, message, candle_ts)) => {
+                    println!(
+                        "Strategy {} failed at {}: {}",
+                        strategy_id, candle_ts, message
+                    );
+
+                    execution.fail(kind, message, Some(candle_ts), Utc::now())?;
+                    true
+                }
+                None => false,
+            };
+
+            executed += states.len();
+
+            if !states.is_empty() || is_interrupted {
+                if !self
+                    .write_checkpoint(strategy_id, revision, &execution, states)
+                    .await?
+                {
+                    return Ok(());
+                }
+
+                revision = Some(execution.revision());
+            }
+
+            // Data past `read_to` is not available yet, it is picked up on the next step
+            if is_interrupted || read_to < chunk_to {
+                return Ok(());
+            }
+
+            chunk_from = chunk_to;
         }
 
-        self.mongo
-            .write_strategy_execution(strategy_id, &execution)
-            .await
-            .map_err(|err| {
-                anyhow::anyhow!(
-                    "Failed to update execution status for strategy {}: {}",
-                    strategy_id,
-                    err.to_string()
-                )
-            })?;
+        if let Some(time_to) = strategy_definition.time_to() {
+            let last_ts = execution.last_execution_timestamp();
 
-        self.mongo
-            .write_strategy_state(strategy_id, states)
-            .await
-            .map_err(|err| {
-                anyhow::anyhow!(
-                    "Failed to write strategy states for strategy {}: {}",
-                    strategy_id,
-                    err.to_string()
-                )
-            })?;
+            let is_complete = resolution.advance(last_ts, 1) >= time_to
+                || (executed == 0
+                    && time_to + Duration::hours(FINISH_GRACE_PERIOD_HOURS) < Utc::now());
+
+            if is_complete {
+                execution.transition(StrategyExecutionStatus::Finished, None)?;
+                self.write_checkpoint(strategy_id, revision, &execution, vec![])
+                    .await?;
+            }
+        }
 
         Ok(())
     }
 
-    async fn step(
-        &mut self,
-        prev_state: Arc<<Self as Periodic>::State>,
-    ) -> anyhow::Result<Arc<<Self as Periodic>::State>> {
+    /// Executes instances reading any series passing the filter
+    async fn exec_instances(&self, filter: impl Fn(&Figi, CandleResolution) -> bool) {
         let strategies = self.strategy_cache.state();
+        let now = Utc::now();
 
-        for (strategy_id, (def, strategy)) in strategies.iter() {
-            if let Err(err) = self
-                .exec_strategy(strategy_id, def, strategy.as_ref())
-                .await
-            {
-                println!("Failed to execute strategy: {}", err);
-            }
+        // Live instances take free slots first, so that historical catch-up does not delay them
+        let mut instances: Vec<_> = strategies
+            .iter()
+            .filter(|(_, (def, strategy))| {
+                required_series(strategy.as_ref(), def.resolution())
+                    .any(|(figi, resolution)| filter(&figi, resolution))
+            })
+            .collect();
+        instances.sort_by_key(|(_, (def, _))| def.time_to().is_some_and(|time_to| time_to <= now));
+
+        futures::stream::iter(instances)
+            .for_each_concurrent(None, |(strategy_id, (def, strategy))| async move {
+                let lock = self.instance_lock(strategy_id);
+                let _guard = lock.lock().await;
+
+                // Semaphore is fair, so permits are granted in priority order
+                let _permit = match self.permits.acquire().await {
+                    Ok(permit) => permit,
+                    Err(_) => return,
+                };
+
+                // A panic must not stop the runner for the other instances
+                let result = AssertUnwindSafe(self.exec_strategy(strategy_id, def, strategy))
+                    .catch_unwind()
+                    .await;
+
+                match result {
+                    Ok(Ok(())) => (),
+                    Ok(Err(err)) => println!("Failed to execute strategy: {}", err),
+                    Err(panic) => println!(
+                        "Strategy {} execution panicked: {}",
+                        strategy_id,
+                        panic_message(panic)
+                    ),
+                }
+            })
+            .await;
+
+        // Deleted instances are not executed anymore
+        if let Ok(mut locks) = self.instance_locks.lock() {
+            locks.retain(|strategy_id, _| strategies.contains_key(strategy_id));
         }
+    }
 
-        Ok(prev_state)
+    fn instance_lock(&self, strategy_id: &uuid::Uuid) -> Arc<tokio::sync::Mutex<()>> {
+        match self.instance_locks.lock() {
+            Ok(mut locks) => locks.entry(*strategy_id).or_default().clone(),
+            // Poisoned by a panic, instances are not guarded against each other anymore
+            Err(_) => Default::default(),
+        }
+    }
+}
+
+/// Result of executing a window of candles
+struct ChunkOutcome {
+    states: Vec<(DateTime<Utc>, StrategyState)>,
+
+    /// State to continue from, the one of the last executed candle
+    last_state: StrategyState,
+
+    /// Failure execution stopped at along with the candle it happened on
+    failure: Option<(FailureKind, String, DateTime<Utc>)>,
+}
+
+fn execute_chunk(
+    strategy: &dyn Strategy,
+    contexts: Vec<ExecutionContext>,
+    mut last_state: StrategyState,
+) -> ChunkOutcome {
+    let mut states: Vec<(DateTime<Utc>, StrategyState)> = Vec::with_capacity(contexts.len());
+
+    for context in contexts {
+        let ts = context.ts;
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            strategy.execute(&context, last_state.clone())
+        }));
+
+        let failure = match result {
+            Ok(Ok(state)) => {
+                states.push((ts, state.clone()));
+                last_state = state;
+                continue;
+            }
+            Ok(Err(err @ StrategyExecutionError::CriticalFailure(_))) => {
+                (FailureKind::Critical, err.to_string())
+            }
+            Err(panic) => (FailureKind::Panic, panic_message(panic)),
+        };
+
+        return ChunkOutcome {
+            states,
+            last_state,
+            failure: Some((failure.0, failure.1, ts)),
+        };
+    }
+
+    ChunkOutcome {
+        states,
+        last_state,
+        failure: None,
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => "Strategy panicked".to_owned(),
+        },
     }
 }
 
 pub type StrategyRunner = PeriodicComponent<StrategyRunnerPeriodic>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_series_updated() {
+        let figi = Figi("BBG000B9XRY4".to_owned());
+        let other = Figi("BBG000BPH459".to_owned());
+
+        // Hourly candles synced for an instrument also traded by a minute instance
+        let updated = HashSet::from([(figi.clone(), CandleResolution::OneHour)]);
+        let is_updated = |figi: &Figi, resolution| is_series_updated(&updated, figi, resolution);
+
+        assert!(is_updated(&figi, CandleResolution::OneHour));
+        assert!(is_updated(&figi, CandleResolution::OneDay));
+        assert!(!is_updated(&figi, CandleResolution::OneMinute));
+        assert!(!is_updated(&figi, CandleResolution::FiveMinutes));
+        assert!(!is_updated(&other, CandleResolution::OneHour));
+
+        // Streamed minute candles are a source of every resolution
+        let updated = HashSet::from([(figi.clone(), CandleResolution::OneMinute)]);
+        let is_updated = |figi: &Figi, resolution| is_series_updated(&updated, figi, resolution);
+
+        assert!(is_updated(&figi, CandleResolution::OneMinute));
+        assert!(is_updated(&figi, CandleResolution::OneHour));
+        assert!(!is_updated(&other, CandleResolution::OneMinute));
+    }
+}
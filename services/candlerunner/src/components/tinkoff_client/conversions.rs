@@ -1,9 +1,16 @@
+use chrono::{prelude::*, Duration};
+
 use crate::generated::tinkoff_invest_api;
 use crate::models::account::AccessLevel;
-use crate::models::instruments::{Figi, Instrument, Ticker};
-use crate::models::market_data::Candle;
+use crate::models::corporate_actions::{CorporateAction, CorporateActionKind};
+use crate::models::instruments::{
+    BondDetails, Figi, FutureDetails, Instrument, InstrumentType, Ticker, TradingStatus,
+};
+use crate::models::market_data::{Candle, CandleResolution, CandleUpdate};
+use crate::models::orders::{OrderDirection, OrderStatus, OrderType, PostedOrder, StopOrderType};
+use crate::models::trading_calendar::TradingDay;
 
-const NANO: f64 = 10e-9;
+const NANO: f64 = 1e-9;
 
 impl From<tinkoff_invest_api::Share> for Instrument {
     fn from(proto: tinkoff_invest_api::Share) -> Self {
@@ -11,14 +18,183 @@ impl From<tinkoff_invest_api::Share> for Instrument {
             figi: Figi(proto.figi),
             ticker: Ticker(proto.ticker),
             display_name: proto.name,
+            instrument_type: InstrumentType::Share,
+            isin: proto.isin,
+            class_code: proto.class_code,
+            exchange: proto.exchange,
+            currency: proto.currency,
+            lot: proto.lot as i64,
+            min_price_increment: proto.min_price_increment.map(to_f64).unwrap_or_default(),
+            trading_status: to_trading_status(proto.trading_status),
+            api_trade_available: proto.api_trade_available_flag,
+            buy_available: proto.buy_available_flag,
+            sell_available: proto.sell_available_flag,
+            short_enabled: proto.short_enabled_flag,
+            bond: None,
+            future: None,
+        }
+    }
+}
+
+impl From<tinkoff_invest_api::Bond> for Instrument {
+    fn from(proto: tinkoff_invest_api::Bond) -> Self {
+        let bond = BondDetails {
+            nominal: proto.nominal.map(money_to_f64).unwrap_or_default(),
+            accrued_interest: proto.aci_value.map(money_to_f64).unwrap_or_default(),
+            coupon_quantity_per_year: proto.coupon_quantity_per_year,
+            maturity_date: proto.maturity_date.map(to_datetime),
+            floating_coupon: proto.floating_coupon_flag,
+            perpetual: proto.perpetual_flag,
+            amortization: proto.amortization_flag,
+        };
+
+        Instrument {
+            figi: Figi(proto.figi),
+            ticker: Ticker(proto.ticker),
+            display_name: proto.name,
+            instrument_type: InstrumentType::Bond,
+            isin: proto.isin,
+            class_code: proto.class_code,
+            exchange: proto.exchange,
+            currency: proto.currency,
+            lot: proto.lot as i64,
+            min_price_increment: proto.min_price_increment.map(to_f64).unwrap_or_default(),
+            trading_status: to_trading_status(proto.trading_status),
+            api_trade_available: proto.api_trade_available_flag,
+            buy_available: proto.buy_available_flag,
+            sell_available: proto.sell_available_flag,
+            short_enabled: proto.short_enabled_flag,
+            bond: Some(bond),
+            future: None,
+        }
+    }
+}
+
+impl From<tinkoff_invest_api::Etf> for Instrument {
+    fn from(proto: tinkoff_invest_api::Etf) -> Self {
+        Instrument {
+            figi: Figi(proto.figi),
+            ticker: Ticker(proto.ticker),
+            display_name: proto.name,
+            instrument_type: InstrumentType::Etf,
+            isin: proto.isin,
+            class_code: proto.class_code,
+            exchange: proto.exchange,
+            currency: proto.currency,
+            lot: proto.lot as i64,
+            min_price_increment: proto.min_price_increment.map(to_f64).unwrap_or_default(),
+            trading_status: to_trading_status(proto.trading_status),
+            api_trade_available: proto.api_trade_available_flag,
+            buy_available: proto.buy_available_flag,
+            sell_available: proto.sell_available_flag,
+            short_enabled: proto.short_enabled_flag,
+            bond: None,
+            future: None,
+        }
+    }
+}
+
+impl From<tinkoff_invest_api::Future> for Instrument {
+    fn from(proto: tinkoff_invest_api::Future) -> Self {
+        let future = FutureDetails {
+            futures_type: proto.futures_type,
+            asset_type: proto.asset_type,
+            basic_asset: proto.basic_asset,
+            basic_asset_size: proto.basic_asset_size.map(to_f64).unwrap_or_default(),
+            first_trade_date: proto.first_trade_date.map(to_datetime),
+            last_trade_date: proto.last_trade_date.map(to_datetime),
+            expiration_date: proto.expiration_date.map(to_datetime),
+        };
+
+        Instrument {
+            figi: Figi(proto.figi),
+            ticker: Ticker(proto.ticker),
+            display_name: proto.name,
+            instrument_type: InstrumentType::Future,
+            // Futures have no ISIN
+            isin: String::new(),
+            class_code: proto.class_code,
+            exchange: proto.exchange,
+            currency: proto.currency,
+            lot: proto.lot as i64,
+            min_price_increment: proto.min_price_increment.map(to_f64).unwrap_or_default(),
+            trading_status: to_trading_status(proto.trading_status),
+            api_trade_available: proto.api_trade_available_flag,
+            buy_available: proto.buy_available_flag,
+            sell_available: proto.sell_available_flag,
+            short_enabled: proto.short_enabled_flag,
+            bond: None,
+            future: Some(future),
         }
     }
 }
 
-fn to_f64(quote: tinkoff_invest_api::Quotation) -> f64 {
+impl From<tinkoff_invest_api::Currency> for Instrument {
+    fn from(proto: tinkoff_invest_api::Currency) -> Self {
+        Instrument {
+            figi: Figi(proto.figi),
+            ticker: Ticker(proto.ticker),
+            display_name: proto.name,
+            instrument_type: InstrumentType::Currency,
+            isin: proto.isin,
+            class_code: proto.class_code,
+            exchange: proto.exchange,
+            currency: proto.currency,
+            lot: proto.lot as i64,
+            min_price_increment: proto.min_price_increment.map(to_f64).unwrap_or_default(),
+            trading_status: to_trading_status(proto.trading_status),
+            api_trade_available: proto.api_trade_available_flag,
+            buy_available: proto.buy_available_flag,
+            sell_available: proto.sell_available_flag,
+            short_enabled: proto.short_enabled_flag,
+            bond: None,
+            future: None,
+        }
+    }
+}
+
+fn to_trading_status(value: i32) -> TradingStatus {
+    use tinkoff_invest_api::SecurityTradingStatus as Proto;
+
+    match Proto::from_i32(value).unwrap_or(Proto::Unspecified) {
+        Proto::Unspecified => TradingStatus::Unspecified,
+        Proto::NotAvailableForTrading => TradingStatus::NotAvailableForTrading,
+        Proto::OpeningPeriod => TradingStatus::OpeningPeriod,
+        Proto::ClosingPeriod => TradingStatus::ClosingPeriod,
+        Proto::BreakInTrading => TradingStatus::BreakInTrading,
+        Proto::NormalTrading => TradingStatus::NormalTrading,
+        Proto::ClosingAuction => TradingStatus::ClosingAuction,
+        Proto::DarkPoolAuction => TradingStatus::DarkPoolAuction,
+        Proto::DiscreteAuction => TradingStatus::DiscreteAuction,
+        Proto::OpeningAuctionPeriod => TradingStatus::OpeningAuctionPeriod,
+        Proto::TradingAtClosingAuctionPrice => TradingStatus::TradingAtClosingAuctionPrice,
+        Proto::SessionAssigned => TradingStatus::SessionAssigned,
+        Proto::SessionClose => TradingStatus::SessionClose,
+        Proto::SessionOpen => TradingStatus::SessionOpen,
+        Proto::DealerNormalTrading => TradingStatus::DealerNormalTrading,
+        Proto::DealerBreakInTrading => TradingStatus::DealerBreakInTrading,
+        Proto::DealerNotAvailableForTrading => TradingStatus::DealerNotAvailableForTrading,
+    }
+}
+
+pub fn to_f64(quote: tinkoff_invest_api::Quotation) -> f64 {
     (quote.units as f64) + (quote.nano as f64) * NANO
 }
 
+pub fn money_to_f64(money: tinkoff_invest_api::MoneyValue) -> f64 {
+    (money.units as f64) + (money.nano as f64) * NANO
+}
+
+pub fn to_quotation(value: f64) -> tinkoff_invest_api::Quotation {
+    let units = value.trunc();
+    let nano = ((value - units) / NANO).round();
+
+    tinkoff_invest_api::Quotation {
+        units: units as i64,
+        nano: nano as i32,
+    }
+}
+
 impl TryFrom<tinkoff_invest_api::HistoricCandle> for Candle {
     type Error = anyhow::Error;
 
@@ -51,6 +227,183 @@ impl TryFrom<tinkoff_invest_api::HistoricCandle> for Candle {
     }
 }
 
+impl TryFrom<CandleResolution> for tinkoff_invest_api::CandleInterval {
+    type Error = anyhow::Error;
+
+    fn try_from(resolution: CandleResolution) -> Result<Self, Self::Error> {
+        match resolution {
+            CandleResolution::OneMinute => Ok(Self::CandleInterval1Min),
+            CandleResolution::FiveMinutes => Ok(Self::CandleInterval5Min),
+            CandleResolution::FifteenMinutes => Ok(Self::CandleInterval15Min),
+            CandleResolution::OneHour => Ok(Self::Hour),
+            CandleResolution::OneDay => Ok(Self::Day),
+            _ => Err(anyhow::anyhow!(
+                "Candles of `{}` resolution are not provided",
+                resolution
+            )),
+        }
+    }
+}
+
+impl TryFrom<CandleResolution> for tinkoff_invest_api::SubscriptionInterval {
+    type Error = anyhow::Error;
+
+    fn try_from(resolution: CandleResolution) -> Result<Self, Self::Error> {
+        match resolution {
+            CandleResolution::OneMinute => Ok(Self::OneMinute),
+            CandleResolution::FiveMinutes => Ok(Self::FiveMinutes),
+            _ => Err(anyhow::anyhow!(
+                "Candles of `{}` resolution can not be streamed",
+                resolution
+            )),
+        }
+    }
+}
+
+impl TryFrom<tinkoff_invest_api::SubscriptionInterval> for CandleResolution {
+    type Error = anyhow::Error;
+
+    fn try_from(interval: tinkoff_invest_api::SubscriptionInterval) -> Result<Self, Self::Error> {
+        match interval {
+            tinkoff_invest_api::SubscriptionInterval::OneMinute => Ok(Self::OneMinute),
+            tinkoff_invest_api::SubscriptionInterval::FiveMinutes => Ok(Self::FiveMinutes),
+            tinkoff_invest_api::SubscriptionInterval::Unspecified => {
+                Err(anyhow::anyhow!("Subscription interval is not specified"))
+            }
+        }
+    }
+}
+
+impl TryFrom<tinkoff_invest_api::Candle> for CandleUpdate {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: tinkoff_invest_api::Candle) -> Result<Self, Self::Error> {
+        let resolution = CandleResolution::try_from(proto.interval())?;
+        let ts = proto
+            .time
+            .as_ref()
+            .map(|ts| Utc.timestamp_opt(ts.seconds, ts.nanos as u32).unwrap())
+            .ok_or_else(|| anyhow::anyhow!("Candle `time` field is missing"))?;
+
+        let high = proto
+            .high
+            .ok_or_else(|| anyhow::anyhow!("Candle `high` field is missing"))?;
+        let low = proto
+            .low
+            .ok_or_else(|| anyhow::anyhow!("Candle `low` field is missing"))?;
+        let open = proto
+            .open
+            .ok_or_else(|| anyhow::anyhow!("Candle `open` field is missing"))?;
+        let close = proto
+            .close
+            .ok_or_else(|| anyhow::anyhow!("Candle `close` field is missing"))?;
+
+        Ok(CandleUpdate {
+            figi: Figi(proto.figi),
+            resolution,
+            ts,
+            candle: Candle {
+                high: to_f64(high),
+                low: to_f64(low),
+                open: to_f64(open),
+                close: to_f64(close),
+                volume: proto.volume as u64,
+            },
+        })
+    }
+}
+
+fn to_datetime(ts: ::prost_types::Timestamp) -> DateTime<Utc> {
+    Utc.timestamp_opt(ts.seconds, ts.nanos as u32).unwrap()
+}
+
+impl TryFrom<tinkoff_invest_api::TradingDay> for (NaiveDate, TradingDay) {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: tinkoff_invest_api::TradingDay) -> Result<Self, Self::Error> {
+        let date = proto
+            .date
+            .map(to_datetime)
+            .ok_or_else(|| anyhow::anyhow!("TradingDay `date` field is missing"))?
+            .date_naive();
+
+        // Evening session ends after the main one if there is any
+        let end_time = proto
+            .end_time
+            .map(to_datetime)
+            .max(proto.evening_end_time.map(to_datetime));
+
+        let day = TradingDay {
+            is_trading_day: proto.is_trading_day,
+            start_time: proto.start_time.map(to_datetime),
+            end_time,
+        };
+
+        Ok((date, day))
+    }
+}
+
+/// Dividends carry no FIGI, so it is taken from the request
+pub fn to_dividend(
+    figi: &Figi,
+    proto: tinkoff_invest_api::Dividend,
+) -> anyhow::Result<CorporateAction> {
+    let last_buy_date = proto
+        .last_buy_date
+        .map(to_datetime)
+        .ok_or_else(|| anyhow::anyhow!("Dividend `last_buy_date` field is missing"))?;
+
+    Ok(CorporateAction {
+        figi: figi.clone(),
+        ex_date: last_buy_date
+            .date_naive()
+            .succ_opt()
+            .unwrap()
+            .and_time(NaiveTime::MIN)
+            .and_utc(),
+        payment_date: proto.payment_date.map(to_datetime),
+        kind: CorporateActionKind::Dividend {
+            amount: proto.dividend_net.map(money_to_f64).unwrap_or_default(),
+            close_price: proto.close_price.map(money_to_f64),
+        },
+    })
+}
+
+impl TryFrom<tinkoff_invest_api::Coupon> for CorporateAction {
+    type Error = anyhow::Error;
+
+    fn try_from(proto: tinkoff_invest_api::Coupon) -> Result<Self, Self::Error> {
+        // Bonds bought on the fix date are settled after it, so it is the ex-date
+        let fix_date = proto
+            .fix_date
+            .map(to_datetime)
+            .ok_or_else(|| anyhow::anyhow!("Coupon `fix_date` field is missing"))?;
+
+        Ok(CorporateAction {
+            figi: Figi(proto.figi),
+            ex_date: fix_date.date_naive().and_time(NaiveTime::MIN).and_utc(),
+            payment_date: proto.coupon_date.map(to_datetime),
+            kind: CorporateActionKind::Coupon {
+                amount: proto.pay_one_bond.map(money_to_f64).unwrap_or_default(),
+            },
+        })
+    }
+}
+
+/// Whether candles of the resolution can be received from market data stream
+pub fn is_streamable(resolution: CandleResolution) -> bool {
+    tinkoff_invest_api::SubscriptionInterval::try_from(resolution).is_ok()
+}
+
+/// Longest time range a single `GetCandles` request accepts for the resolution
+pub fn max_candles_window(resolution: CandleResolution) -> Duration {
+    match resolution {
+        CandleResolution::OneHour => Duration::weeks(1),
+        CandleResolution::OneDay => Duration::days(365),
+        _ => Duration::days(1),
+    }
+}
+
 impl From<tinkoff_invest_api::AccessLevel> for AccessLevel {
     fn from(value: tinkoff_invest_api::AccessLevel) -> Self {
         match value {
@@ -65,3 +418,106 @@ impl From<tinkoff_invest_api::AccessLevel> for AccessLevel {
         }
     }
 }
+
+impl From<tinkoff_invest_api::OrderExecutionReportStatus> for OrderStatus {
+    fn from(value: tinkoff_invest_api::OrderExecutionReportStatus) -> Self {
+        match value {
+            tinkoff_invest_api::OrderExecutionReportStatus::ExecutionReportStatusUnspecified => {
+                OrderStatus::Unspecified
+            }
+            tinkoff_invest_api::OrderExecutionReportStatus::ExecutionReportStatusFill => {
+                OrderStatus::Filled
+            }
+            tinkoff_invest_api::OrderExecutionReportStatus::ExecutionReportStatusRejected => {
+                OrderStatus::Rejected
+            }
+            tinkoff_invest_api::OrderExecutionReportStatus::ExecutionReportStatusCancelled => {
+                OrderStatus::Cancelled
+            }
+            tinkoff_invest_api::OrderExecutionReportStatus::ExecutionReportStatusNew => {
+                OrderStatus::New
+            }
+            tinkoff_invest_api::OrderExecutionReportStatus::ExecutionReportStatusPartiallyfill => {
+                OrderStatus::PartiallyFilled
+            }
+        }
+    }
+}
+
+impl From<tinkoff_invest_api::PostOrderResponse> for PostedOrder {
+    fn from(proto: tinkoff_invest_api::PostOrderResponse) -> Self {
+        let status = OrderStatus::from(proto.execution_report_status());
+
+        PostedOrder {
+            order_id: proto.order_id,
+            status,
+            lots_requested: proto.lots_requested,
+            lots_executed: proto.lots_executed,
+            executed_order_price: proto.executed_order_price.map(money_to_f64),
+        }
+    }
+}
+
+impl From<OrderDirection> for tinkoff_invest_api::OrderDirection {
+    fn from(value: OrderDirection) -> Self {
+        match value {
+            OrderDirection::Buy => tinkoff_invest_api::OrderDirection::Buy,
+            OrderDirection::Sell => tinkoff_invest_api::OrderDirection::Sell,
+        }
+    }
+}
+
+impl From<OrderDirection> for tinkoff_invest_api::StopOrderDirection {
+    fn from(value: OrderDirection) -> Self {
+        match value {
+            OrderDirection::Buy => tinkoff_invest_api::StopOrderDirection::Buy,
+            OrderDirection::Sell => tinkoff_invest_api::StopOrderDirection::Sell,
+        }
+    }
+}
+
+impl From<OrderType> for tinkoff_invest_api::OrderType {
+    fn from(value: OrderType) -> Self {
+        match value {
+            OrderType::Market => tinkoff_invest_api::OrderType::Market,
+            OrderType::Limit { price: _ } => tinkoff_invest_api::OrderType::Limit,
+        }
+    }
+}
+
+impl From<StopOrderType> for tinkoff_invest_api::StopOrderType {
+    fn from(value: StopOrderType) -> Self {
+        match value {
+            StopOrderType::StopLoss => tinkoff_invest_api::StopOrderType::StopLoss,
+            StopOrderType::TakeProfit => tinkoff_invest_api::StopOrderType::TakeProfit,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotation() {
+        let quotation = tinkoff_invest_api::Quotation {
+            units: 114,
+            nano: 250_000_000,
+        };
+        assert_eq!(to_f64(quotation), 114.25);
+
+        let money = tinkoff_invest_api::MoneyValue {
+            currency: "rub".to_owned(),
+            units: -3,
+            nano: -10_000_000,
+        };
+        assert_eq!(money_to_f64(money), -3.01);
+
+        // Units and nano share the sign of the value
+        let quotation = to_quotation(-0.5);
+        assert_eq!((quotation.units, quotation.nano), (0, -500_000_000));
+
+        let quotation = to_quotation(0.001);
+        assert_eq!((quotation.units, quotation.nano), (0, 1_000_000));
+    }
+}
@@ -1,5 +1,6 @@
 mod conversions;
 mod interceptor;
+#[allow(clippy::module_inception)]
 mod tinkoff_client;
 mod tinkoff_generic_client;
 mod tinkoff_sandbox_client;
@@ -1,13 +1,22 @@
-use chrono::prelude::*;
-use tonic::transport::Endpoint;
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{prelude::*, Duration};
+use futures::stream::{BoxStream, StreamExt};
+use futures::Stream;
+use tonic::transport::{Channel, Endpoint};
 
 use component_store::{init_err, prelude::*};
 
+use crate::components::{InstrumentProvider, MarketDataProvider};
 use crate::models::account::{Account, Environment};
-use crate::models::instruments::{Figi, Instrument};
-use crate::models::market_data::CandleTimeline;
+use crate::models::corporate_actions::CorporateAction;
+use crate::models::instruments::{Figi, Instrument, InstrumentType};
+use crate::models::market_data::{CandleResolution, CandleTimeline, CandleUpdate};
+use crate::models::orders::{OrderRequest, PostedOrder, StopOrderRequest};
 use crate::models::positions::AccountPositions;
+use crate::models::trading_calendar::TradingDay;
 
+use super::conversions;
 use super::tinkoff_generic_client::TinkoffGenericClient;
 use super::tinkoff_production_client::TinkoffProductionClient;
 use super::tinkoff_sandbox_client::TinkoffSandboxClient;
@@ -45,12 +54,24 @@ impl TinkoffClient {
         let sandbox_auth_token = config.get_str("sandbox_auth_token")?.to_owned();
         let production_auth_token = config.get_str("production_auth_token")?.to_owned();
 
+        // Connection is established on the first request for the service
+        // to start without network when data comes from other providers
         let channel = Endpoint::new(url.to_string())
             .map_err(init_err)?
-            .connect()
-            .await
-            .map_err(init_err)?;
+            .connect_lazy();
+
+        Ok(Self::from_channel(
+            channel,
+            sandbox_auth_token,
+            production_auth_token,
+        )?)
+    }
 
+    pub fn from_channel(
+        channel: Channel,
+        sandbox_auth_token: String,
+        production_auth_token: String,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync + 'static>> {
         let sandbox_client = TinkoffSandboxClient::new(channel.clone(), sandbox_auth_token)?;
         let production_client = TinkoffProductionClient::new(channel, production_auth_token)?;
 
@@ -67,10 +88,57 @@ impl TinkoffClient {
     pub async fn get_candles(
         &self,
         figi: &Figi,
+        resolution: CandleResolution,
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> anyhow::Result<CandleTimeline> {
-        self.production_client.get_candles(figi, from, to).await
+        self.production_client
+            .get_candles(figi, resolution, from, to)
+            .await
+    }
+
+    /// Streams updates of candles being formed for the (instrument, resolution) pairs
+    pub async fn subscribe_candles(
+        &self,
+        subscriptions: &[(Figi, CandleResolution)],
+    ) -> anyhow::Result<impl Stream<Item = anyhow::Result<CandleUpdate>>> {
+        self.production_client
+            .subscribe_candles(subscriptions)
+            .await
+    }
+
+    pub async fn get_trading_schedules(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> anyhow::Result<HashMap<String, BTreeMap<NaiveDate, TradingDay>>> {
+        self.production_client.get_trading_schedules(from, to).await
+    }
+
+    pub async fn get_last_prices(&self, figis: &[Figi]) -> anyhow::Result<HashMap<Figi, f64>> {
+        self.production_client.get_last_prices(figis).await
+    }
+
+    /// Dividends of shares and ETFs, coupons of bonds. Splits are not provided by the API
+    pub async fn get_corporate_actions(
+        &self,
+        instrument: &Instrument,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<CorporateAction>> {
+        match instrument.instrument_type {
+            InstrumentType::Share | InstrumentType::Etf => {
+                self.production_client
+                    .get_dividends(&instrument.figi, from, to)
+                    .await
+            }
+            InstrumentType::Bond => {
+                self.production_client
+                    .get_bond_coupons(&instrument.figi, from, to)
+                    .await
+            }
+            InstrumentType::Future | InstrumentType::Currency => Ok(vec![]),
+        }
     }
 
     pub async fn list_accounts(&self) -> anyhow::Result<Vec<Account>> {
@@ -94,7 +162,7 @@ impl TinkoffClient {
 
         Ok(sandbox_accounts
             .into_iter()
-            .chain(production_accounts.into_iter())
+            .chain(production_accounts)
             .collect())
     }
 
@@ -117,4 +185,214 @@ impl TinkoffClient {
         let client = self.get_client(account);
         client.list_positions(account).await
     }
+
+    pub async fn post_order(
+        &self,
+        account: &Account,
+        request: &OrderRequest,
+    ) -> anyhow::Result<PostedOrder> {
+        let client = self.get_client(account);
+        client.post_order(request).await
+    }
+
+    pub async fn post_stop_order(
+        &self,
+        account: &Account,
+        request: &StopOrderRequest,
+    ) -> anyhow::Result<String> {
+        let client = self.get_client(account);
+        client.post_stop_order(request).await
+    }
+}
+
+#[async_trait::async_trait]
+impl InstrumentProvider for TinkoffClient {
+    async fn get_instruments(&self) -> anyhow::Result<Vec<Instrument>> {
+        TinkoffClient::get_instruments(self).await
+    }
+}
+
+#[async_trait::async_trait]
+impl MarketDataProvider for TinkoffClient {
+    async fn get_candles(
+        &self,
+        figi: &Figi,
+        resolution: CandleResolution,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<CandleTimeline> {
+        TinkoffClient::get_candles(self, figi, resolution, from, to).await
+    }
+
+    fn max_candles_window(&self, resolution: CandleResolution) -> Duration {
+        conversions::max_candles_window(resolution)
+    }
+
+    fn is_streamable(&self, resolution: CandleResolution) -> bool {
+        conversions::is_streamable(resolution)
+    }
+
+    async fn subscribe_candles(
+        &self,
+        subscriptions: &[(Figi, CandleResolution)],
+    ) -> anyhow::Result<BoxStream<'static, anyhow::Result<CandleUpdate>>> {
+        let stream = TinkoffClient::subscribe_candles(self, subscriptions).await?;
+
+        Ok(stream.boxed())
+    }
+
+    async fn get_trading_schedules(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> anyhow::Result<HashMap<String, BTreeMap<NaiveDate, TradingDay>>> {
+        TinkoffClient::get_trading_schedules(self, from, to).await
+    }
+
+    async fn get_last_prices(&self, figis: &[Figi]) -> anyhow::Result<HashMap<Figi, f64>> {
+        TinkoffClient::get_last_prices(self, figis).await
+    }
+
+    async fn get_corporate_actions(
+        &self,
+        instrument: &Instrument,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<CorporateAction>> {
+        TinkoffClient::get_corporate_actions(self, instrument, from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fake_broker::{FakeBroker, FakeMarket};
+    use crate::models::account::AccountId;
+    use crate::models::instruments::InstrumentType;
+    use crate::models::orders::{OrderDirection, OrderStatus, OrderType};
+
+    #[tokio::test]
+    async fn test_fake_broker() {
+        let broker = FakeBroker::new(FakeMarket::sample());
+        broker.open_account("Fake account", 10000.0);
+        let client = broker.spawn().await;
+
+        let instruments = client.get_instruments().await.unwrap();
+        assert_eq!(instruments.len(), 2);
+        assert_eq!(instruments[0].instrument_type, InstrumentType::Share);
+        assert_eq!(instruments[1].instrument_type, InstrumentType::Bond);
+        assert_eq!(instruments[1].bond.as_ref().unwrap().nominal, 1000.0);
+        let figi = instruments[0].figi.clone();
+
+        let from = Utc.with_ymd_and_hms(2022, 3, 1, 0, 0, 0).unwrap();
+        let candles = client
+            .get_candles(
+                &figi,
+                CandleResolution::OneMinute,
+                from,
+                from + Duration::days(1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(candles.len(), 3);
+
+        let schedules = client
+            .get_trading_schedules(from.date_naive(), from.date_naive().succ_opt().unwrap())
+            .await
+            .unwrap();
+        assert!(schedules["MOEX"][&from.date_naive()].is_trading_day);
+
+        client.open_sandbox_account().await.unwrap();
+        let accounts = client.list_accounts().await.unwrap();
+        assert_eq!(accounts.len(), 2);
+
+        let account = accounts
+            .iter()
+            .find(|account| account.environment == Environment::Production)
+            .unwrap();
+
+        let order = client
+            .post_order(
+                account,
+                &OrderRequest {
+                    order_id: "order-1".to_owned(),
+                    account_id: AccountId(account.id.0.clone()),
+                    figi: figi.clone(),
+                    direction: OrderDirection::Buy,
+                    order_type: OrderType::Market,
+                    lots: 2,
+                },
+            )
+            .await
+            .unwrap();
+        assert!(matches!(order.status, OrderStatus::Filled));
+
+        let positions = client.list_positions(account).await.unwrap();
+        assert_eq!(positions.positions[0].figi, figi);
+        assert_eq!(positions.positions[0].lots, 2);
+    }
+
+    fn market_order(
+        account: &Account,
+        order_id: &str,
+        direction: OrderDirection,
+        lots: i64,
+    ) -> OrderRequest {
+        OrderRequest {
+            order_id: order_id.to_owned(),
+            account_id: account.id.clone(),
+            figi: Figi("FAKE0000001".to_owned()),
+            direction,
+            order_type: OrderType::Market,
+            lots,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fake_broker_positions() {
+        let broker = FakeBroker::new(FakeMarket::sample());
+        broker.open_account("Fake account", 10000.0);
+        let client = broker.spawn().await;
+
+        client.open_sandbox_account().await.unwrap();
+        let accounts = client.list_accounts().await.unwrap();
+
+        let account = |environment: Environment| {
+            accounts
+                .iter()
+                .find(|account| account.environment == environment)
+                .unwrap()
+        };
+        let production = account(Environment::Production);
+        let sandbox = account(Environment::Sandbox);
+
+        // Orders are filled at the last close of 103 with 10 shares per lot
+        let buy = market_order(production, "buy", OrderDirection::Buy, 2);
+        let order = client.post_order(production, &buy).await.unwrap();
+        assert_eq!(order.average_price(10), Some(103.0));
+
+        // Retried order is not filled twice
+        client.post_order(production, &buy).await.unwrap();
+
+        let sell = market_order(production, "sell", OrderDirection::Sell, 1);
+        client.post_order(production, &sell).await.unwrap();
+
+        let positions = client.list_positions(production).await.unwrap();
+        assert_eq!(positions.positions.len(), 1);
+        assert_eq!(positions.positions[0].lots, 1);
+        assert_eq!(positions.currencies.len(), 1);
+        assert!((positions.currencies[0].amount - 8970.0).abs() < 1e-6);
+
+        let positions = client.list_positions(sandbox).await.unwrap();
+        assert!(positions.positions.is_empty());
+
+        // Selling the rest closes the position
+        let sell = market_order(production, "sell-rest", OrderDirection::Sell, 1);
+        client.post_order(production, &sell).await.unwrap();
+
+        let positions = client.list_positions(production).await.unwrap();
+        assert!(positions.positions.is_empty());
+        assert!((positions.currencies[0].amount - 10000.0).abs() < 1e-6);
+    }
 }
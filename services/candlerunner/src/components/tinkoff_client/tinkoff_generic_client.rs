@@ -1,9 +1,14 @@
+use std::collections::{BTreeMap, HashMap};
+
 use chrono::prelude::*;
 
 use crate::models::account::Account;
+use crate::models::corporate_actions::CorporateAction;
 use crate::models::instruments::{Figi, Instrument};
-use crate::models::market_data::CandleTimeline;
+use crate::models::market_data::{CandleResolution, CandleTimeline};
+use crate::models::orders::{OrderRequest, PostedOrder, StopOrderRequest};
 use crate::models::positions::AccountPositions;
+use crate::models::trading_calendar::TradingDay;
 
 #[async_trait::async_trait]
 pub trait TinkoffGenericClient: Sync + Send + 'static {
@@ -11,11 +16,42 @@ pub trait TinkoffGenericClient: Sync + Send + 'static {
     async fn get_candles(
         &self,
         figi: &Figi,
+        resolution: CandleResolution,
         from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> anyhow::Result<CandleTimeline>;
 
+    /// Trading days of every exchange within [`from`; `to`]
+    async fn get_trading_schedules(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> anyhow::Result<HashMap<String, BTreeMap<NaiveDate, TradingDay>>>;
+
+    async fn get_last_prices(&self, figis: &[Figi]) -> anyhow::Result<HashMap<Figi, f64>>;
+
+    /// Dividends of a share or an ETF with ex-dates within [`from`; `to`]
+    async fn get_dividends(
+        &self,
+        figi: &Figi,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<CorporateAction>>;
+
+    /// Coupons of a bond with fix dates within [`from`; `to`]
+    async fn get_bond_coupons(
+        &self,
+        figi: &Figi,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<CorporateAction>>;
+
     async fn list_accounts(&self) -> anyhow::Result<Vec<Account>>;
 
     async fn list_positions(&self, account: &Account) -> anyhow::Result<AccountPositions>;
+
+    async fn post_order(&self, request: &OrderRequest) -> anyhow::Result<PostedOrder>;
+
+    /// Returns id of the placed stop order
+    async fn post_stop_order(&self, request: &StopOrderRequest) -> anyhow::Result<String>;
 }
@@ -1,4 +1,6 @@
-use chrono::{prelude::*, Duration};
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::prelude::*;
 use tonic::service::interceptor::InterceptedService;
 use tonic::transport::Channel;
 
@@ -7,10 +9,14 @@ use crate::generated::tinkoff_invest_api::instruments_service_client::Instrument
 use crate::generated::tinkoff_invest_api::market_data_service_client::MarketDataServiceClient;
 use crate::generated::tinkoff_invest_api::sandbox_service_client::SandboxServiceClient;
 use crate::models::account::{AccessLevel, Account, AccountId, Environment};
+use crate::models::corporate_actions::CorporateAction;
 use crate::models::instruments::{Figi, Instrument};
-use crate::models::market_data::{Candle, CandleTimeline};
+use crate::models::market_data::{Candle, CandleResolution, CandleTimeline};
+use crate::models::orders::{OrderRequest, OrderType, PostedOrder, StopOrderRequest};
 use crate::models::positions::{AccountPositions, Position, Currency};
+use crate::models::trading_calendar::TradingDay;
 
+use super::conversions;
 use super::interceptor::AuthorizationInterceptor;
 use super::tinkoff_generic_client::TinkoffGenericClient;
 
@@ -51,6 +57,7 @@ impl TinkoffSandboxClient {
         Ok(())
     }
 
+    #[allow(dead_code)]
     pub async fn pay_in(&self, account: &Account, value: f64) -> anyhow::Result<()> {
         let mut sandbox_client = SandboxServiceClient::new(self.client.clone());
 
@@ -74,16 +81,24 @@ impl TinkoffGenericClient for TinkoffSandboxClient {
     async fn get_instruments(&self) -> anyhow::Result<Vec<Instrument>> {
         let mut instruments_client = InstrumentsServiceClient::new(self.client.clone());
 
-        let request = tinkoff_invest_api::InstrumentsRequest {
+        let request = || tinkoff_invest_api::InstrumentsRequest {
             instrument_status: tinkoff_invest_api::InstrumentStatus::Base as i32,
         };
 
-        let shares_resp = instruments_client.shares(request).await?;
-        let res: Vec<_> = shares_resp
-            .into_inner()
+        let shares = instruments_client.shares(request()).await?.into_inner();
+        let bonds = instruments_client.bonds(request()).await?.into_inner();
+        let etfs = instruments_client.etfs(request()).await?.into_inner();
+        let futures = instruments_client.futures(request()).await?.into_inner();
+        let currencies = instruments_client.currencies(request()).await?.into_inner();
+
+        let res = shares
             .instruments
             .into_iter()
-            .map(From::<tinkoff_invest_api::Share>::from)
+            .map(Instrument::from)
+            .chain(bonds.instruments.into_iter().map(Instrument::from))
+            .chain(etfs.instruments.into_iter().map(Instrument::from))
+            .chain(futures.instruments.into_iter().map(Instrument::from))
+            .chain(currencies.instruments.into_iter().map(Instrument::from))
             .collect();
 
         Ok(res)
@@ -92,12 +107,14 @@ impl TinkoffGenericClient for TinkoffSandboxClient {
     async fn get_candles(
         &self,
         figi: &Figi,
+        resolution: CandleResolution,
         mut from: DateTime<Utc>,
         to: DateTime<Utc>,
     ) -> anyhow::Result<CandleTimeline> {
         let mut market_data_client = MarketDataServiceClient::new(self.client.clone());
 
-        let time_step = Duration::days(1);
+        let interval = tinkoff_invest_api::CandleInterval::try_from(resolution)?;
+        let time_step = conversions::max_candles_window(resolution);
 
         let mut timeline = CandleTimeline::default();
 
@@ -114,7 +131,7 @@ impl TinkoffGenericClient for TinkoffSandboxClient {
                     seconds: req_to.timestamp(),
                     nanos: req_to.nanosecond() as i32,
                 }),
-                interval: tinkoff_invest_api::CandleInterval::CandleInterval1Min as i32,
+                interval: interval as i32,
             };
 
             let candles_resp = market_data_client.get_candles(request).await?;
@@ -123,7 +140,7 @@ impl TinkoffGenericClient for TinkoffSandboxClient {
                 let time = proto_candle
                     .time
                     .as_ref()
-                    .map(|ts| Utc.timestamp(ts.seconds, ts.nanos as u32))
+                    .map(|ts| Utc.timestamp_opt(ts.seconds, ts.nanos as u32).unwrap())
                     .ok_or_else(|| anyhow::anyhow!("HistoricalCandle `time` field is missing"))?;
 
                 let candle = Candle::try_from(proto_candle)?;
@@ -137,6 +154,131 @@ impl TinkoffGenericClient for TinkoffSandboxClient {
         Ok(timeline)
     }
 
+    async fn get_trading_schedules(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> anyhow::Result<HashMap<String, BTreeMap<NaiveDate, TradingDay>>> {
+        let mut instruments_client = InstrumentsServiceClient::new(self.client.clone());
+
+        let request = tinkoff_invest_api::TradingSchedulesRequest {
+            // Empty exchange requests schedules of all exchanges
+            exchange: String::new(),
+            from: Some(::prost_types::Timestamp {
+                seconds: from.and_time(NaiveTime::MIN).and_utc().timestamp(),
+                nanos: 0,
+            }),
+            to: Some(::prost_types::Timestamp {
+                seconds: to.and_time(NaiveTime::MIN).and_utc().timestamp(),
+                nanos: 0,
+            }),
+        };
+
+        let resp = instruments_client
+            .trading_schedules(request)
+            .await?
+            .into_inner();
+
+        resp.exchanges
+            .into_iter()
+            .map(|schedule| {
+                let days = schedule
+                    .days
+                    .into_iter()
+                    .map(<(NaiveDate, TradingDay)>::try_from)
+                    .collect::<anyhow::Result<_>>()?;
+
+                Ok((schedule.exchange, days))
+            })
+            .collect()
+    }
+
+    async fn get_last_prices(&self, figis: &[Figi]) -> anyhow::Result<HashMap<Figi, f64>> {
+        let mut market_data_client = MarketDataServiceClient::new(self.client.clone());
+
+        let request = tinkoff_invest_api::GetLastPricesRequest {
+            figi: figis.iter().map(|figi| figi.0.clone()).collect(),
+        };
+
+        let resp = market_data_client
+            .get_last_prices(request)
+            .await?
+            .into_inner();
+
+        let res: HashMap<_, _> = resp
+            .last_prices
+            .into_iter()
+            .filter_map(|proto| {
+                let price = proto.price.map(conversions::to_f64)?;
+                Some((Figi(proto.figi), price))
+            })
+            .collect();
+
+        Ok(res)
+    }
+
+    async fn get_dividends(
+        &self,
+        figi: &Figi,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<CorporateAction>> {
+        let mut instruments_client = InstrumentsServiceClient::new(self.client.clone());
+
+        let request = tinkoff_invest_api::GetDividendsRequest {
+            figi: figi.0.clone(),
+            from: Some(::prost_types::Timestamp {
+                seconds: from.timestamp(),
+                nanos: 0,
+            }),
+            to: Some(::prost_types::Timestamp {
+                seconds: to.timestamp(),
+                nanos: 0,
+            }),
+        };
+
+        let resp = instruments_client
+            .get_dividends(request)
+            .await?
+            .into_inner();
+
+        resp.dividends
+            .into_iter()
+            .map(|proto| conversions::to_dividend(figi, proto))
+            .collect()
+    }
+
+    async fn get_bond_coupons(
+        &self,
+        figi: &Figi,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<CorporateAction>> {
+        let mut instruments_client = InstrumentsServiceClient::new(self.client.clone());
+
+        let request = tinkoff_invest_api::GetBondCouponsRequest {
+            figi: figi.0.clone(),
+            from: Some(::prost_types::Timestamp {
+                seconds: from.timestamp(),
+                nanos: 0,
+            }),
+            to: Some(::prost_types::Timestamp {
+                seconds: to.timestamp(),
+                nanos: 0,
+            }),
+        };
+
+        let resp = instruments_client
+            .get_bond_coupons(request)
+            .await?
+            .into_inner();
+
+        resp.events
+            .into_iter()
+            .map(CorporateAction::try_from)
+            .collect()
+    }
+
     async fn list_accounts(&self) -> anyhow::Result<Vec<Account>> {
         let mut sandbox_client = SandboxServiceClient::new(self.client.clone());
 
@@ -193,4 +335,32 @@ impl TinkoffGenericClient for TinkoffSandboxClient {
             positions: positions.collect(),
         })
     }
+
+    async fn post_order(&self, request: &OrderRequest) -> anyhow::Result<PostedOrder> {
+        let mut sandbox_client = SandboxServiceClient::new(self.client.clone());
+
+        let price = match request.order_type {
+            OrderType::Market => None,
+            OrderType::Limit { price } => Some(conversions::to_quotation(price)),
+        };
+
+        let resp = sandbox_client
+            .post_sandbox_order(tinkoff_invest_api::PostOrderRequest {
+                figi: request.figi.0.clone(),
+                quantity: request.lots,
+                price,
+                direction: tinkoff_invest_api::OrderDirection::from(request.direction) as i32,
+                account_id: request.account_id.0.clone(),
+                order_type: tinkoff_invest_api::OrderType::from(request.order_type) as i32,
+                order_id: request.order_id.clone(),
+            })
+            .await?
+            .into_inner();
+
+        Ok(PostedOrder::from(resp))
+    }
+
+    async fn post_stop_order(&self, _: &StopOrderRequest) -> anyhow::Result<String> {
+        Err(anyhow::anyhow!("Stop orders are not supported in sandbox"))
+    }
 }
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use component_store::prelude::*;
+use periodic_component::{Periodic, PeriodicComponent, PeriodicCreateFuture, PeriodicFuture};
+
+use crate::components;
+use crate::models::instruments::Figi;
+use crate::models::trading_calendar::TradingCalendar;
+
+pub struct TradingCalendarCachePeriodic {
+    mongo: Arc<components::Mongo>,
+    instrument_cache: Arc<components::InstrumentCache>,
+}
+
+impl ComponentName for TradingCalendarCachePeriodic {
+    fn component_name() -> &'static str {
+        "trading-calendar-cache"
+    }
+}
+
+impl Periodic for TradingCalendarCachePeriodic {
+    /// Trading calendars of instrument exchanges
+    type State = HashMap<Figi, Arc<TradingCalendar>>;
+
+    fn init(
+        resolver: ComponentResolver,
+        _: Box<dyn ConfigProvider>,
+    ) -> PeriodicCreateFuture<(Self, Self::State)> {
+        Box::pin(async move {
+            let mongo = resolver.resolve::<components::Mongo>().await?;
+            let instrument_cache = resolver.resolve::<components::InstrumentCache>().await?;
+
+            // Ensures that trading-calendar-sync is initialized before cache initialization.
+            let _ = resolver
+                .resolve::<components::TradingCalendarSync>()
+                .await?;
+
+            let periodic = TradingCalendarCachePeriodic {
+                mongo,
+                instrument_cache,
+            };
+            let init_state = Self::State::default();
+
+            Ok((periodic, init_state))
+        })
+    }
+
+    fn step(&mut self, _: Arc<Self::State>) -> PeriodicFuture<'_, Self::State> {
+        Box::pin(async move {
+            let calendars: HashMap<String, Arc<TradingCalendar>> = self
+                .mongo
+                .read_trading_calendars()
+                .await?
+                .into_iter()
+                .map(|(exchange, calendar)| (exchange, Arc::new(calendar)))
+                .collect();
+
+            let instruments = self.instrument_cache.state();
+
+            let state = instruments
+                .values()
+                .filter_map(|instrument| {
+                    let calendar = calendars.get(&instrument.exchange)?;
+                    Some((instrument.figi.clone(), calendar.clone()))
+                })
+                .collect();
+
+            Ok(Arc::new(state))
+        })
+    }
+}
+
+pub type TradingCalendarCache = PeriodicComponent<TradingCalendarCachePeriodic>;
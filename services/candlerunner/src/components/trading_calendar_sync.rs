@@ -0,0 +1,124 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use chrono::{prelude::*, Duration};
+use component_store::prelude::*;
+use periodic_component::{Periodic, PeriodicComponent};
+
+use crate::components;
+
+/// Longest time range trading schedules are requested for at once
+const MAX_SCHEDULE_DAYS: i64 = 14;
+
+pub struct TradingCalendarSyncPeriodic {
+    data_provider: Arc<components::DataProvider>,
+    mongo: Arc<components::Mongo>,
+    days_back: i64,
+    days_ahead: i64,
+}
+
+impl ComponentName for TradingCalendarSyncPeriodic {
+    fn component_name() -> &'static str {
+        "trading-calendar-sync"
+    }
+}
+
+impl Periodic for TradingCalendarSyncPeriodic {
+    type State = ();
+
+    fn init(
+        resolver: ComponentResolver,
+        config: Box<dyn ConfigProvider>,
+    ) -> periodic_component::PeriodicCreateFuture<(Self, Self::State)> {
+        Box::pin(async move {
+            let data_provider = resolver.resolve::<components::DataProvider>().await?;
+            let mongo = resolver.resolve::<components::Mongo>().await?;
+
+            let days_back = config.get_u64("days_back")? as i64;
+            let days_ahead = config.get_u64("days_ahead")? as i64;
+
+            let periodic = Self {
+                data_provider,
+                mongo,
+                days_back,
+                days_ahead,
+            };
+
+            Ok((periodic, ()))
+        })
+    }
+
+    fn step(
+        &mut self,
+        state: Arc<Self::State>,
+    ) -> periodic_component::PeriodicFuture<'_, Self::State> {
+        Box::pin(async move {
+            let calendars = self.mongo.read_trading_calendars().await?;
+            let today = Utc::now().date_naive();
+
+            // Past schedules do not change, so only missing days are fetched.
+            // Upcoming days are refetched as holidays might be announced.
+            let mut dates: BTreeSet<NaiveDate> = BTreeSet::default();
+            let mut date = today - Duration::days(self.days_back);
+
+            while date <= today + Duration::days(self.days_ahead) {
+                if date >= today
+                    || calendars
+                        .values()
+                        .all(|calendar| calendar.day(date).is_none())
+                {
+                    dates.insert(date);
+                }
+
+                date = date.succ_opt().unwrap();
+            }
+
+            for (from, to) in group_into_ranges(dates, MAX_SCHEDULE_DAYS) {
+                if let Err(err) = self.sync_trading_schedules(from, to).await {
+                    println!(
+                        "Failed to fetch trading schedules for [{}; {}]: {}",
+                        from, to, err
+                    );
+                }
+            }
+
+            Ok(state)
+        })
+    }
+}
+
+impl TradingCalendarSyncPeriodic {
+    async fn sync_trading_schedules(&self, from: NaiveDate, to: NaiveDate) -> anyhow::Result<()> {
+        let schedules = self
+            .data_provider
+            .market_data()
+            .get_trading_schedules(from, to)
+            .await?;
+
+        for (exchange, days) in schedules {
+            self.mongo.write_trading_days(&exchange, days).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Groups consecutive dates into ranges of at most `max_days` days
+fn group_into_ranges(dates: BTreeSet<NaiveDate>, max_days: i64) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut ranges: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+
+    for date in dates {
+        match ranges.last_mut() {
+            Some((from, to))
+                if date == to.succ_opt().unwrap() && (date - *from).num_days() < max_days =>
+            {
+                *to = date;
+            }
+            _ => ranges.push((date, date)),
+        }
+    }
+
+    ranges
+}
+
+pub type TradingCalendarSync = PeriodicComponent<TradingCalendarSyncPeriodic>;
@@ -0,0 +1,459 @@
+use std::collections::BTreeMap;
+
+use chrono::prelude::*;
+use tonic::Status;
+
+use crate::generated::tinkoff_invest_api;
+use crate::generated::tinkoff_invest_api::{OrderDirection, OrderExecutionReportStatus, OrderType};
+
+use super::conversions::{from_quotation, money, timestamp, CURRENCY};
+
+/// Price and lot size of an instrument at the time an order is placed
+pub struct Quote {
+    pub last_price: Option<f64>,
+    pub lot: i64,
+}
+
+struct FakeAccount {
+    name: String,
+    sandbox: bool,
+    is_open: bool,
+    money: f64,
+
+    /// Lots by FIGI
+    positions: BTreeMap<String, i64>,
+    orders: BTreeMap<String, tinkoff_invest_api::OrderState>,
+    stop_orders: BTreeMap<String, tinkoff_invest_api::StopOrder>,
+}
+
+/// Accounts of the fake broker. Sandbox and production accounts are kept apart
+/// the same way the real API does.
+#[derive(Default)]
+pub struct Book {
+    accounts: BTreeMap<String, FakeAccount>,
+    last_id: u64,
+}
+
+impl Book {
+    fn next_id(&mut self, prefix: &str) -> String {
+        self.last_id += 1;
+        format!("{}-{}", prefix, self.last_id)
+    }
+
+    pub fn open_account(&mut self, sandbox: bool, name: &str, money: f64) -> String {
+        let id = self.next_id("account");
+
+        self.accounts.insert(
+            id.clone(),
+            FakeAccount {
+                name: name.to_owned(),
+                sandbox,
+                is_open: true,
+                money,
+                positions: Default::default(),
+                orders: Default::default(),
+                stop_orders: Default::default(),
+            },
+        );
+
+        id
+    }
+
+    pub fn close_account(&mut self, sandbox: bool, account_id: &str) -> Result<(), Status> {
+        self.account_mut(sandbox, account_id)?.is_open = false;
+
+        Ok(())
+    }
+
+    fn account_mut(&mut self, sandbox: bool, account_id: &str) -> Result<&mut FakeAccount, Status> {
+        self.accounts
+            .get_mut(account_id)
+            .filter(|account| account.sandbox == sandbox && account.is_open)
+            .ok_or_else(|| Status::not_found(format!("Account `{}` not found", account_id)))
+    }
+
+    pub fn accounts(&self, sandbox: bool) -> Vec<tinkoff_invest_api::Account> {
+        self.accounts
+            .iter()
+            .filter(|(_, account)| account.sandbox == sandbox && account.is_open)
+            .map(|(id, account)| tinkoff_invest_api::Account {
+                id: id.clone(),
+                r#type: tinkoff_invest_api::AccountType::Tinkoff as i32,
+                name: account.name.clone(),
+                status: tinkoff_invest_api::AccountStatus::Open as i32,
+                access_level: tinkoff_invest_api::AccessLevel::AccountAccessLevelFullAccess as i32,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    pub fn pay_in(
+        &mut self,
+        sandbox: bool,
+        account_id: &str,
+        amount: f64,
+    ) -> Result<tinkoff_invest_api::MoneyValue, Status> {
+        let account = self.account_mut(sandbox, account_id)?;
+        account.money += amount;
+
+        Ok(money(account.money))
+    }
+
+    pub fn positions(
+        &mut self,
+        sandbox: bool,
+        account_id: &str,
+    ) -> Result<tinkoff_invest_api::PositionsResponse, Status> {
+        let account = self.account_mut(sandbox, account_id)?;
+
+        Ok(tinkoff_invest_api::PositionsResponse {
+            money: vec![money(account.money)],
+            securities: account
+                .positions
+                .iter()
+                .map(|(figi, lots)| tinkoff_invest_api::PositionsSecurities {
+                    figi: figi.clone(),
+                    balance: *lots,
+                    blocked: 0,
+                })
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Fills market orders and marketable limit orders at the last price at once.
+    /// Other limit orders stay new until cancelled.
+    pub fn post_order(
+        &mut self,
+        sandbox: bool,
+        request: &tinkoff_invest_api::PostOrderRequest,
+        quote: Quote,
+    ) -> Result<tinkoff_invest_api::PostOrderResponse, Status> {
+        if request.quantity <= 0 {
+            return Err(Status::invalid_argument("Quantity must be positive"));
+        }
+
+        let direction = OrderDirection::from_i32(request.direction)
+            .filter(|direction| *direction != OrderDirection::Unspecified)
+            .ok_or_else(|| Status::invalid_argument("Order direction is not specified"))?;
+
+        let order_type = OrderType::from_i32(request.order_type)
+            .filter(|order_type| *order_type != OrderType::Unspecified)
+            .ok_or_else(|| Status::invalid_argument("Order type is not specified"))?;
+
+        let order_id = match request.order_id.is_empty() {
+            true => self.next_id("order"),
+            false => request.order_id.clone(),
+        };
+
+        let account = self.account_mut(sandbox, &request.account_id)?;
+
+        // Order id is an idempotency key
+        if let Some(state) = account.orders.get(&order_id) {
+            return Ok(post_order_response(state));
+        }
+
+        let limit_price = request.price.as_ref().map(from_quotation);
+        let amount = |price: f64| price * (request.quantity * quote.lot) as f64;
+
+        let status = match (quote.last_price, order_type, limit_price) {
+            (None, _, _) => OrderExecutionReportStatus::ExecutionReportStatusRejected,
+            (_, OrderType::Limit, None) => {
+                return Err(Status::invalid_argument("Limit order has no price"))
+            }
+            (Some(last_price), OrderType::Limit, Some(limit_price))
+                if (direction == OrderDirection::Buy && limit_price < last_price)
+                    || (direction == OrderDirection::Sell && limit_price > last_price) =>
+            {
+                OrderExecutionReportStatus::ExecutionReportStatusNew
+            }
+            (Some(last_price), _, _)
+                if direction == OrderDirection::Buy && amount(last_price) > account.money =>
+            {
+                OrderExecutionReportStatus::ExecutionReportStatusRejected
+            }
+            (Some(_), _, _) => OrderExecutionReportStatus::ExecutionReportStatusFill,
+        };
+
+        let executed_price = match status {
+            OrderExecutionReportStatus::ExecutionReportStatusFill => quote.last_price,
+            _ => None,
+        };
+
+        if let Some(price) = executed_price {
+            let lots = match direction {
+                OrderDirection::Buy => request.quantity,
+                _ => -request.quantity,
+            };
+
+            account.money -= amount(price) * lots.signum() as f64;
+
+            let position = account.positions.entry(request.figi.clone()).or_default();
+            *position += lots;
+
+            if *position == 0 {
+                account.positions.remove(&request.figi);
+            }
+        }
+
+        let initial_price = limit_price.or(quote.last_price).unwrap_or_default();
+
+        let state = tinkoff_invest_api::OrderState {
+            order_id: order_id.clone(),
+            execution_report_status: status as i32,
+            lots_requested: request.quantity,
+            lots_executed: executed_price.map_or(0, |_| request.quantity),
+            initial_order_price: Some(money(amount(initial_price))),
+            executed_order_price: executed_price.map(|price| money(amount(price))),
+            total_order_amount: Some(money(amount(executed_price.unwrap_or(initial_price)))),
+            initial_commission: Some(money(0.0)),
+            executed_commission: Some(money(0.0)),
+            figi: request.figi.clone(),
+            direction: request.direction,
+            initial_security_price: Some(money(initial_price)),
+            currency: CURRENCY.to_owned(),
+            order_type: request.order_type,
+            order_date: Some(timestamp(Utc::now())),
+            ..Default::default()
+        };
+
+        let response = post_order_response(&state);
+        account.orders.insert(order_id, state);
+
+        Ok(response)
+    }
+
+    pub fn orders(
+        &mut self,
+        sandbox: bool,
+        account_id: &str,
+    ) -> Result<Vec<tinkoff_invest_api::OrderState>, Status> {
+        let account = self.account_mut(sandbox, account_id)?;
+
+        Ok(account
+            .orders
+            .values()
+            .filter(|state| {
+                state.execution_report_status
+                    == OrderExecutionReportStatus::ExecutionReportStatusNew as i32
+            })
+            .cloned()
+            .collect())
+    }
+
+    pub fn order_state(
+        &mut self,
+        sandbox: bool,
+        account_id: &str,
+        order_id: &str,
+    ) -> Result<tinkoff_invest_api::OrderState, Status> {
+        let account = self.account_mut(sandbox, account_id)?;
+
+        account
+            .orders
+            .get(order_id)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("Order `{}` not found", order_id)))
+    }
+
+    pub fn cancel_order(
+        &mut self,
+        sandbox: bool,
+        account_id: &str,
+        order_id: &str,
+    ) -> Result<(), Status> {
+        let account = self.account_mut(sandbox, account_id)?;
+
+        let state = account
+            .orders
+            .get_mut(order_id)
+            .filter(|state| {
+                state.execution_report_status
+                    == OrderExecutionReportStatus::ExecutionReportStatusNew as i32
+            })
+            .ok_or_else(|| Status::not_found(format!("Active order `{}` not found", order_id)))?;
+
+        state.execution_report_status =
+            OrderExecutionReportStatus::ExecutionReportStatusCancelled as i32;
+
+        Ok(())
+    }
+
+    /// Stop orders are accepted and listed but never triggered
+    pub fn post_stop_order(
+        &mut self,
+        request: &tinkoff_invest_api::PostStopOrderRequest,
+    ) -> Result<String, Status> {
+        if request.quantity <= 0 {
+            return Err(Status::invalid_argument("Quantity must be positive"));
+        }
+
+        let stop_order_id = self.next_id("stop-order");
+        let account = self.account_mut(false, &request.account_id)?;
+
+        let stop_order = tinkoff_invest_api::StopOrder {
+            stop_order_id: stop_order_id.clone(),
+            lots_requested: request.quantity,
+            figi: request.figi.clone(),
+            direction: request.direction,
+            currency: CURRENCY.to_owned(),
+            order_type: request.stop_order_type,
+            create_date: Some(timestamp(Utc::now())),
+            expiration_time: request.expire_date.clone(),
+            price: request
+                .price
+                .as_ref()
+                .map(|price| money(from_quotation(price))),
+            stop_price: request
+                .stop_price
+                .as_ref()
+                .map(|price| money(from_quotation(price))),
+            ..Default::default()
+        };
+
+        account
+            .stop_orders
+            .insert(stop_order_id.clone(), stop_order);
+
+        Ok(stop_order_id)
+    }
+
+    pub fn stop_orders(
+        &mut self,
+        account_id: &str,
+    ) -> Result<Vec<tinkoff_invest_api::StopOrder>, Status> {
+        let account = self.account_mut(false, account_id)?;
+
+        Ok(account.stop_orders.values().cloned().collect())
+    }
+
+    pub fn cancel_stop_order(
+        &mut self,
+        account_id: &str,
+        stop_order_id: &str,
+    ) -> Result<(), Status> {
+        let account = self.account_mut(false, account_id)?;
+
+        account
+            .stop_orders
+            .remove(stop_order_id)
+            .map(|_| ())
+            .ok_or_else(|| Status::not_found(format!("Stop order `{}` not found", stop_order_id)))
+    }
+}
+
+fn post_order_response(
+    state: &tinkoff_invest_api::OrderState,
+) -> tinkoff_invest_api::PostOrderResponse {
+    tinkoff_invest_api::PostOrderResponse {
+        order_id: state.order_id.clone(),
+        execution_report_status: state.execution_report_status,
+        lots_requested: state.lots_requested,
+        lots_executed: state.lots_executed,
+        initial_order_price: state.initial_order_price.clone(),
+        executed_order_price: state.executed_order_price.clone(),
+        total_order_amount: state.total_order_amount.clone(),
+        initial_commission: state.initial_commission.clone(),
+        executed_commission: state.executed_commission.clone(),
+        figi: state.figi.clone(),
+        direction: state.direction,
+        initial_security_price: state.initial_security_price.clone(),
+        order_type: state.order_type,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::conversions::{from_money, quotation};
+    use super::*;
+
+    fn order(
+        account_id: &str,
+        order_id: &str,
+        direction: OrderDirection,
+        price: Option<f64>,
+    ) -> tinkoff_invest_api::PostOrderRequest {
+        tinkoff_invest_api::PostOrderRequest {
+            figi: "FIGI".to_owned(),
+            quantity: 2,
+            price: price.map(quotation),
+            direction: direction as i32,
+            account_id: account_id.to_owned(),
+            order_type: match price {
+                Some(_) => OrderType::Limit as i32,
+                None => OrderType::Market as i32,
+            },
+            order_id: order_id.to_owned(),
+        }
+    }
+
+    fn quote() -> Quote {
+        Quote {
+            last_price: Some(100.0),
+            lot: 10,
+        }
+    }
+
+    #[test]
+    fn test_post_order() {
+        let mut book = Book::default();
+        let account_id = book.open_account(false, "Account", 3000.0);
+
+        let buy = book
+            .post_order(
+                false,
+                &order(&account_id, "1", OrderDirection::Buy, None),
+                quote(),
+            )
+            .unwrap();
+        assert_eq!(
+            buy.execution_report_status,
+            OrderExecutionReportStatus::ExecutionReportStatusFill as i32
+        );
+
+        // Same order id is not executed twice
+        book.post_order(
+            false,
+            &order(&account_id, "1", OrderDirection::Buy, None),
+            quote(),
+        )
+        .unwrap();
+
+        let positions = book.positions(false, &account_id).unwrap();
+        assert_eq!(positions.securities[0].balance, 2);
+        assert_eq!(from_money(&positions.money[0]), 1000.0);
+
+        // Not enough money
+        let rejected = book
+            .post_order(
+                false,
+                &order(&account_id, "2", OrderDirection::Buy, None),
+                quote(),
+            )
+            .unwrap();
+        assert_eq!(
+            rejected.execution_report_status,
+            OrderExecutionReportStatus::ExecutionReportStatusRejected as i32
+        );
+
+        let limit = book
+            .post_order(
+                false,
+                &order(&account_id, "3", OrderDirection::Sell, Some(110.0)),
+                quote(),
+            )
+            .unwrap();
+        assert_eq!(
+            limit.execution_report_status,
+            OrderExecutionReportStatus::ExecutionReportStatusNew as i32
+        );
+        assert_eq!(book.orders(false, &account_id).unwrap().len(), 1);
+
+        book.cancel_order(false, &account_id, "3").unwrap();
+        assert!(book.orders(false, &account_id).unwrap().is_empty());
+
+        // Production accounts are not visible in sandbox
+        assert!(book.positions(true, &account_id).is_err());
+    }
+}
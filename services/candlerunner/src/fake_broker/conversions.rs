@@ -0,0 +1,202 @@
+use chrono::prelude::*;
+use tonic::Status;
+
+use crate::generated::tinkoff_invest_api;
+use crate::models::instruments::Instrument;
+use crate::models::market_data::{Candle, CandleResolution};
+use crate::models::trading_calendar::TradingDay;
+
+const NANO: f64 = 1.0e-9;
+
+/// Currency of account money
+pub const CURRENCY: &str = "rub";
+
+pub fn quotation(value: f64) -> tinkoff_invest_api::Quotation {
+    let units = value.trunc();
+
+    tinkoff_invest_api::Quotation {
+        units: units as i64,
+        nano: ((value - units) / NANO).round() as i32,
+    }
+}
+
+pub fn money(value: f64) -> tinkoff_invest_api::MoneyValue {
+    let quotation = quotation(value);
+
+    tinkoff_invest_api::MoneyValue {
+        currency: CURRENCY.to_owned(),
+        units: quotation.units,
+        nano: quotation.nano,
+    }
+}
+
+pub fn from_quotation(value: &tinkoff_invest_api::Quotation) -> f64 {
+    value.units as f64 + value.nano as f64 * NANO
+}
+
+pub fn from_money(value: &tinkoff_invest_api::MoneyValue) -> f64 {
+    value.units as f64 + value.nano as f64 * NANO
+}
+
+pub fn timestamp(ts: DateTime<Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: ts.timestamp(),
+        nanos: ts.timestamp_subsec_nanos() as i32,
+    }
+}
+
+pub fn from_timestamp(
+    ts: &Option<prost_types::Timestamp>,
+    field: &str,
+) -> Result<DateTime<Utc>, Status> {
+    let ts = ts
+        .as_ref()
+        .ok_or_else(|| Status::invalid_argument(format!("`{}` is missing", field)))?;
+
+    Utc.timestamp_opt(ts.seconds, ts.nanos as u32)
+        .single()
+        .ok_or_else(|| Status::invalid_argument(format!("`{}` is out of range", field)))
+}
+
+pub fn resolution(
+    interval: tinkoff_invest_api::CandleInterval,
+) -> Result<CandleResolution, Status> {
+    match interval {
+        tinkoff_invest_api::CandleInterval::CandleInterval1Min => Ok(CandleResolution::OneMinute),
+        tinkoff_invest_api::CandleInterval::CandleInterval5Min => Ok(CandleResolution::FiveMinutes),
+        tinkoff_invest_api::CandleInterval::CandleInterval15Min => {
+            Ok(CandleResolution::FifteenMinutes)
+        }
+        tinkoff_invest_api::CandleInterval::Hour => Ok(CandleResolution::OneHour),
+        tinkoff_invest_api::CandleInterval::Day => Ok(CandleResolution::OneDay),
+        tinkoff_invest_api::CandleInterval::Unspecified => {
+            Err(Status::invalid_argument("Candle interval is not specified"))
+        }
+    }
+}
+
+pub fn share(instrument: &Instrument) -> tinkoff_invest_api::Share {
+    tinkoff_invest_api::Share {
+        figi: instrument.figi.0.clone(),
+        ticker: instrument.ticker.0.clone(),
+        name: instrument.display_name.clone(),
+        exchange: instrument.exchange.clone(),
+        lot: instrument.lot as i32,
+        currency: currency(instrument),
+        min_price_increment: Some(quotation(instrument.min_price_increment)),
+        api_trade_available_flag: true,
+        buy_available_flag: true,
+        sell_available_flag: true,
+        ..Default::default()
+    }
+}
+
+pub fn bond(instrument: &Instrument) -> tinkoff_invest_api::Bond {
+    let details = instrument.bond.clone().unwrap_or_default();
+
+    tinkoff_invest_api::Bond {
+        figi: instrument.figi.0.clone(),
+        ticker: instrument.ticker.0.clone(),
+        name: instrument.display_name.clone(),
+        exchange: instrument.exchange.clone(),
+        lot: instrument.lot as i32,
+        currency: currency(instrument),
+        min_price_increment: Some(quotation(instrument.min_price_increment)),
+        nominal: Some(money(details.nominal)),
+        aci_value: Some(money(details.accrued_interest)),
+        coupon_quantity_per_year: details.coupon_quantity_per_year,
+        maturity_date: details.maturity_date.map(timestamp),
+        api_trade_available_flag: true,
+        buy_available_flag: true,
+        sell_available_flag: true,
+        ..Default::default()
+    }
+}
+
+pub fn etf(instrument: &Instrument) -> tinkoff_invest_api::Etf {
+    tinkoff_invest_api::Etf {
+        figi: instrument.figi.0.clone(),
+        ticker: instrument.ticker.0.clone(),
+        name: instrument.display_name.clone(),
+        exchange: instrument.exchange.clone(),
+        lot: instrument.lot as i32,
+        currency: currency(instrument),
+        min_price_increment: Some(quotation(instrument.min_price_increment)),
+        api_trade_available_flag: true,
+        buy_available_flag: true,
+        sell_available_flag: true,
+        ..Default::default()
+    }
+}
+
+pub fn future(instrument: &Instrument) -> tinkoff_invest_api::Future {
+    let details = instrument.future.clone().unwrap_or_default();
+
+    tinkoff_invest_api::Future {
+        figi: instrument.figi.0.clone(),
+        ticker: instrument.ticker.0.clone(),
+        name: instrument.display_name.clone(),
+        exchange: instrument.exchange.clone(),
+        lot: instrument.lot as i32,
+        currency: currency(instrument),
+        min_price_increment: Some(quotation(instrument.min_price_increment)),
+        futures_type: details.futures_type,
+        asset_type: details.asset_type,
+        basic_asset: details.basic_asset,
+        basic_asset_size: Some(quotation(details.basic_asset_size)),
+        expiration_date: details.expiration_date.map(timestamp),
+        api_trade_available_flag: true,
+        buy_available_flag: true,
+        sell_available_flag: true,
+        ..Default::default()
+    }
+}
+
+pub fn currency_instrument(instrument: &Instrument) -> tinkoff_invest_api::Currency {
+    tinkoff_invest_api::Currency {
+        figi: instrument.figi.0.clone(),
+        ticker: instrument.ticker.0.clone(),
+        name: instrument.display_name.clone(),
+        exchange: instrument.exchange.clone(),
+        lot: instrument.lot as i32,
+        currency: currency(instrument),
+        min_price_increment: Some(quotation(instrument.min_price_increment)),
+        api_trade_available_flag: true,
+        buy_available_flag: true,
+        sell_available_flag: true,
+        ..Default::default()
+    }
+}
+
+fn currency(instrument: &Instrument) -> String {
+    match instrument.currency.is_empty() {
+        true => CURRENCY.to_owned(),
+        false => instrument.currency.clone(),
+    }
+}
+
+pub fn historic_candle(
+    ts: DateTime<Utc>,
+    candle: &Candle,
+    is_complete: bool,
+) -> tinkoff_invest_api::HistoricCandle {
+    tinkoff_invest_api::HistoricCandle {
+        open: Some(quotation(candle.open)),
+        high: Some(quotation(candle.high)),
+        low: Some(quotation(candle.low)),
+        close: Some(quotation(candle.close)),
+        volume: candle.volume as i64,
+        time: Some(timestamp(ts)),
+        is_complete,
+    }
+}
+
+pub fn trading_day(date: NaiveDate, day: &TradingDay) -> tinkoff_invest_api::TradingDay {
+    tinkoff_invest_api::TradingDay {
+        date: Some(timestamp(date.and_time(NaiveTime::MIN).and_utc())),
+        is_trading_day: day.is_trading_day,
+        start_time: day.start_time.map(timestamp),
+        end_time: day.end_time.map(timestamp),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use chrono::prelude::*;
+
+use crate::candle_files;
+use crate::models::instruments::{Figi, Instrument};
+use crate::models::market_data::{CandleResolution, CandleTimeline};
+use crate::models::trading_calendar::TradingDay;
+
+/// Scripted market the fake broker serves
+#[derive(Default)]
+pub struct FakeMarket {
+    pub instruments: Vec<Instrument>,
+    pub candles: HashMap<(Figi, CandleResolution), CandleTimeline>,
+
+    /// Trading days by exchange
+    pub trading_days: HashMap<String, BTreeMap<NaiveDate, TradingDay>>,
+}
+
+impl FakeMarket {
+    /// Loads market from a directory laid out as for the `files` data provider:
+    /// `instruments.json`, `candles/<figi>/<resolution>.csv` and optional
+    /// `trading_days.json` with trading days by exchange and date
+    pub fn load(directory: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::File::open(directory.join("instruments.json"))?;
+        let instruments: Vec<Instrument> = serde_json::from_reader(std::io::BufReader::new(file))?;
+
+        let mut candles = HashMap::new();
+
+        for instrument in &instruments {
+            for resolution in CandleResolution::STORED {
+                let path = directory
+                    .join("candles")
+                    .join(&instrument.figi.0)
+                    .join(format!("{}.csv", resolution));
+
+                if path.exists() {
+                    let timeline = candle_files::read_csv(std::fs::File::open(path)?)?;
+                    candles.insert((instrument.figi.clone(), resolution), timeline);
+                }
+            }
+        }
+
+        let trading_days_path = directory.join("trading_days.json");
+
+        let trading_days = match trading_days_path.exists() {
+            true => serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(
+                trading_days_path,
+            )?))?,
+            false => Default::default(),
+        };
+
+        Ok(Self {
+            instruments,
+            candles,
+            trading_days,
+        })
+    }
+
+    pub fn instrument(&self, figi: &Figi) -> Option<&Instrument> {
+        self.instruments
+            .iter()
+            .find(|instrument| &instrument.figi == figi)
+    }
+
+    /// Close of the latest candle started before `now` at the finest resolution scripted
+    pub fn last_price(&self, figi: &Figi, now: DateTime<Utc>) -> Option<f64> {
+        CandleResolution::STORED.into_iter().find_map(|resolution| {
+            let (_, candle) = self
+                .candles
+                .get(&(figi.clone(), resolution))?
+                .range(..now)
+                .next_back()?;
+
+            Some(candle.close)
+        })
+    }
+}
+
+#[cfg(test)]
+impl FakeMarket {
+    /// Share with a lot of 10 and a bond, both traded on MOEX.
+    /// The share has minute candles on March 1 and 3 2022, closing at 100 to 103;
+    /// the exchange is closed on March 2.
+    pub fn sample() -> Self {
+        use chrono::Duration;
+
+        use crate::models::instruments::{BondDetails, InstrumentType, Ticker};
+        use crate::models::market_data::Candle;
+
+        let figi = Figi("FAKE0000001".to_owned());
+        let first_day = NaiveDate::from_ymd_opt(2022, 3, 1).unwrap();
+        let last_day = NaiveDate::from_ymd_opt(2022, 3, 3).unwrap();
+
+        let candle_times = (0..3)
+            .map(|i| first_day.and_hms_opt(10, i, 0).unwrap().and_utc())
+            .chain([last_day.and_hms_opt(10, 0, 0).unwrap().and_utc()]);
+
+        let candles = candle_times
+            .enumerate()
+            .map(|(i, ts)| {
+                let price = 100.0 + i as f64;
+                let candle = Candle {
+                    open: price,
+                    high: price + 0.5,
+                    low: price - 0.5,
+                    close: price,
+                    volume: 10,
+                };
+
+                (ts, candle)
+            })
+            .collect();
+
+        let trading_day = |date: NaiveDate| TradingDay {
+            is_trading_day: true,
+            start_time: Some(date.and_hms_opt(10, 0, 0).unwrap().and_utc()),
+            end_time: Some(date.and_hms_opt(10, 0, 0).unwrap().and_utc() + Duration::hours(8)),
+        };
+
+        let closed_day = TradingDay {
+            is_trading_day: false,
+            start_time: None,
+            end_time: None,
+        };
+
+        Self {
+            instruments: vec![
+                Instrument {
+                    figi: figi.clone(),
+                    ticker: Ticker("FAKE".to_owned()),
+                    display_name: "Fake".to_owned(),
+                    exchange: "MOEX".to_owned(),
+                    lot: 10,
+                    min_price_increment: 0.01,
+                    ..Default::default()
+                },
+                Instrument {
+                    figi: Figi("FAKE0000002".to_owned()),
+                    ticker: Ticker("FAKEBOND".to_owned()),
+                    display_name: "Fake bond".to_owned(),
+                    instrument_type: InstrumentType::Bond,
+                    exchange: "MOEX".to_owned(),
+                    lot: 1,
+                    min_price_increment: 0.01,
+                    bond: Some(BondDetails {
+                        nominal: 1000.0,
+                        coupon_quantity_per_year: 2,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            ],
+            candles: HashMap::from([((figi, CandleResolution::OneMinute), candles)]),
+            trading_days: HashMap::from([(
+                "MOEX".to_owned(),
+                BTreeMap::from([
+                    (first_day, trading_day(first_day)),
+                    (first_day.succ_opt().unwrap(), closed_day),
+                    (last_day, trading_day(last_day)),
+                ]),
+            )]),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Errors are reported to the client as gRPC statuses, as the generated services require
+#![allow(clippy::result_large_err)]
+
+mod book;
+mod conversions;
+mod market;
+mod services;
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::prelude::*;
+use tokio::net::TcpListener;
+use tonic::transport::Server;
+
+use crate::generated::tinkoff_invest_api::instruments_service_server::InstrumentsServiceServer;
+use crate::generated::tinkoff_invest_api::market_data_service_server::MarketDataServiceServer;
+use crate::generated::tinkoff_invest_api::operations_service_server::OperationsServiceServer;
+use crate::generated::tinkoff_invest_api::orders_service_server::OrdersServiceServer;
+use crate::generated::tinkoff_invest_api::sandbox_service_server::SandboxServiceServer;
+use crate::generated::tinkoff_invest_api::stop_orders_service_server::StopOrdersServiceServer;
+use crate::generated::tinkoff_invest_api::users_service_server::UsersServiceServer;
+use crate::models::instruments::Figi;
+
+use book::{Book, Quote};
+
+pub use market::FakeMarket;
+
+/// In-process broker serving the Tinkoff Invest API over plain HTTP/2.
+/// Candles, instruments and trading days come from a scripted market;
+/// orders are filled at the last candle close. Authorization is not checked.
+#[derive(Clone)]
+pub struct FakeBroker {
+    market: Arc<FakeMarket>,
+    book: Arc<Mutex<Book>>,
+}
+
+impl FakeBroker {
+    pub fn new(market: FakeMarket) -> Self {
+        Self {
+            market: Arc::new(market),
+            book: Default::default(),
+        }
+    }
+
+    /// Opens a production account holding `money`, returns its id
+    pub fn open_account(&self, name: &str, money: f64) -> String {
+        self.book().open_account(false, name, money)
+    }
+
+    fn book(&self) -> MutexGuard<'_, Book> {
+        self.book.lock().expect("fake broker book is poisoned")
+    }
+
+    fn quote(&self, figi: &str) -> Quote {
+        let figi = Figi(figi.to_owned());
+
+        Quote {
+            last_price: self.market.last_price(&figi, Utc::now()),
+            lot: self
+                .market
+                .instrument(&figi)
+                .map_or(1, |instrument| instrument.lot.max(1)),
+        }
+    }
+
+    /// Serves connections accepted by the listener until the future is dropped
+    pub async fn serve(self, listener: TcpListener) -> anyhow::Result<()> {
+        let incoming = Box::pin(futures::stream::unfold(listener, |listener| async move {
+            let connection = listener.accept().await.map(|(stream, _)| stream);
+            Some((connection, listener))
+        }));
+
+        Server::builder()
+            .add_service(InstrumentsServiceServer::new(self.clone()))
+            .add_service(MarketDataServiceServer::new(self.clone()))
+            .add_service(OperationsServiceServer::new(self.clone()))
+            .add_service(OrdersServiceServer::new(self.clone()))
+            .add_service(SandboxServiceServer::new(self.clone()))
+            .add_service(StopOrdersServiceServer::new(self.clone()))
+            .add_service(UsersServiceServer::new(self))
+            .serve_with_incoming(incoming)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl FakeBroker {
+    /// Serves the broker on a free local port, returns a client connected to it
+    pub async fn spawn(&self) -> crate::components::TinkoffClient {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(self.clone().serve(listener));
+
+        let channel = tonic::transport::Endpoint::new(url).unwrap().connect_lazy();
+
+        crate::components::TinkoffClient::from_channel(
+            channel,
+            "sandbox".to_owned(),
+            "production".to_owned(),
+        )
+        .unwrap()
+    }
+
+    /// Stop orders of a production account, none are ever triggered
+    pub fn stop_orders(
+        &self,
+        account_id: &str,
+    ) -> Vec<crate::generated::tinkoff_invest_api::StopOrder> {
+        self.book().stop_orders(account_id).unwrap()
+    }
+}
@@ -0,0 +1,559 @@
+use chrono::prelude::*;
+use tonic::{Request, Response, Status};
+
+use crate::generated::tinkoff_invest_api;
+use crate::generated::tinkoff_invest_api::instruments_service_server::InstrumentsService;
+use crate::generated::tinkoff_invest_api::market_data_service_server::MarketDataService;
+use crate::generated::tinkoff_invest_api::operations_service_server::OperationsService;
+use crate::generated::tinkoff_invest_api::orders_service_server::OrdersService;
+use crate::generated::tinkoff_invest_api::sandbox_service_server::SandboxService;
+use crate::generated::tinkoff_invest_api::stop_orders_service_server::StopOrdersService;
+use crate::generated::tinkoff_invest_api::users_service_server::UsersService;
+use crate::models::instruments::{Figi, Instrument, InstrumentType};
+
+use super::conversions;
+use super::FakeBroker;
+
+type RpcResult<T> = Result<Response<T>, Status>;
+
+fn unimplemented<T>(method: &str) -> RpcResult<T> {
+    Err(Status::unimplemented(format!(
+        "`{}` is not supported by fake broker",
+        method
+    )))
+}
+
+impl FakeBroker {
+    fn instruments<T>(
+        &self,
+        instrument_type: InstrumentType,
+        convert: impl Fn(&Instrument) -> T,
+    ) -> Vec<T> {
+        self.market
+            .instruments
+            .iter()
+            .filter(|instrument| instrument.instrument_type == instrument_type)
+            .map(convert)
+            .collect()
+    }
+}
+
+#[tonic::async_trait]
+impl InstrumentsService for FakeBroker {
+    async fn trading_schedules(
+        &self,
+        request: Request<tinkoff_invest_api::TradingSchedulesRequest>,
+    ) -> RpcResult<tinkoff_invest_api::TradingSchedulesResponse> {
+        let request = request.into_inner();
+        let from = conversions::from_timestamp(&request.from, "from")?.date_naive();
+        let to = conversions::from_timestamp(&request.to, "to")?.date_naive();
+
+        let exchanges = self
+            .market
+            .trading_days
+            .iter()
+            .filter(|(exchange, _)| request.exchange.is_empty() || **exchange == request.exchange)
+            .map(|(exchange, days)| tinkoff_invest_api::TradingSchedule {
+                exchange: exchange.clone(),
+                days: days
+                    .range(from..=to)
+                    .map(|(date, day)| conversions::trading_day(*date, day))
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Response::new(
+            tinkoff_invest_api::TradingSchedulesResponse { exchanges },
+        ))
+    }
+
+    async fn bond_by(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentRequest>,
+    ) -> RpcResult<tinkoff_invest_api::BondResponse> {
+        unimplemented("BondBy")
+    }
+
+    async fn bonds(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::BondsResponse> {
+        Ok(Response::new(tinkoff_invest_api::BondsResponse {
+            instruments: self.instruments(InstrumentType::Bond, conversions::bond),
+        }))
+    }
+
+    async fn get_bond_coupons(
+        &self,
+        _: Request<tinkoff_invest_api::GetBondCouponsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetBondCouponsResponse> {
+        // Fake market has no corporate actions
+        Ok(Response::new(tinkoff_invest_api::GetBondCouponsResponse {
+            events: vec![],
+        }))
+    }
+
+    async fn currency_by(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentRequest>,
+    ) -> RpcResult<tinkoff_invest_api::CurrencyResponse> {
+        unimplemented("CurrencyBy")
+    }
+
+    async fn currencies(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::CurrenciesResponse> {
+        Ok(Response::new(tinkoff_invest_api::CurrenciesResponse {
+            instruments: self
+                .instruments(InstrumentType::Currency, conversions::currency_instrument),
+        }))
+    }
+
+    async fn etf_by(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentRequest>,
+    ) -> RpcResult<tinkoff_invest_api::EtfResponse> {
+        unimplemented("EtfBy")
+    }
+
+    async fn etfs(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::EtfsResponse> {
+        Ok(Response::new(tinkoff_invest_api::EtfsResponse {
+            instruments: self.instruments(InstrumentType::Etf, conversions::etf),
+        }))
+    }
+
+    async fn future_by(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentRequest>,
+    ) -> RpcResult<tinkoff_invest_api::FutureResponse> {
+        unimplemented("FutureBy")
+    }
+
+    async fn futures(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::FuturesResponse> {
+        Ok(Response::new(tinkoff_invest_api::FuturesResponse {
+            instruments: self.instruments(InstrumentType::Future, conversions::future),
+        }))
+    }
+
+    async fn share_by(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentRequest>,
+    ) -> RpcResult<tinkoff_invest_api::ShareResponse> {
+        unimplemented("ShareBy")
+    }
+
+    async fn shares(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::SharesResponse> {
+        Ok(Response::new(tinkoff_invest_api::SharesResponse {
+            instruments: self.instruments(InstrumentType::Share, conversions::share),
+        }))
+    }
+
+    async fn get_accrued_interests(
+        &self,
+        _: Request<tinkoff_invest_api::GetAccruedInterestsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetAccruedInterestsResponse> {
+        unimplemented("GetAccruedInterests")
+    }
+
+    async fn get_futures_margin(
+        &self,
+        _: Request<tinkoff_invest_api::GetFuturesMarginRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetFuturesMarginResponse> {
+        unimplemented("GetFuturesMargin")
+    }
+
+    async fn get_instrument_by(
+        &self,
+        _: Request<tinkoff_invest_api::InstrumentRequest>,
+    ) -> RpcResult<tinkoff_invest_api::InstrumentResponse> {
+        unimplemented("GetInstrumentBy")
+    }
+
+    async fn get_dividends(
+        &self,
+        _: Request<tinkoff_invest_api::GetDividendsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetDividendsResponse> {
+        Ok(Response::new(tinkoff_invest_api::GetDividendsResponse {
+            dividends: vec![],
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl MarketDataService for FakeBroker {
+    async fn get_candles(
+        &self,
+        request: Request<tinkoff_invest_api::GetCandlesRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetCandlesResponse> {
+        let request = request.into_inner();
+        let from = conversions::from_timestamp(&request.from, "from")?;
+        let to = conversions::from_timestamp(&request.to, "to")?;
+        let resolution = conversions::resolution(request.interval())?;
+        let now = Utc::now();
+
+        let candles = match self.market.candles.get(&(Figi(request.figi), resolution)) {
+            Some(candles) => candles
+                .range(from..to)
+                .filter(|(ts, _)| **ts <= now)
+                .map(|(ts, candle)| {
+                    conversions::historic_candle(*ts, candle, resolution.advance(*ts, 1) <= now)
+                })
+                .collect(),
+            None => vec![],
+        };
+
+        Ok(Response::new(tinkoff_invest_api::GetCandlesResponse {
+            candles,
+        }))
+    }
+
+    async fn get_last_prices(
+        &self,
+        request: Request<tinkoff_invest_api::GetLastPricesRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetLastPricesResponse> {
+        let now = Utc::now();
+
+        let last_prices = request
+            .into_inner()
+            .figi
+            .into_iter()
+            .filter_map(|figi| {
+                let price = self.market.last_price(&Figi(figi.clone()), now)?;
+
+                Some(tinkoff_invest_api::LastPrice {
+                    figi,
+                    price: Some(conversions::quotation(price)),
+                    time: Some(conversions::timestamp(now)),
+                })
+            })
+            .collect();
+
+        Ok(Response::new(tinkoff_invest_api::GetLastPricesResponse {
+            last_prices,
+        }))
+    }
+
+    async fn get_order_book(
+        &self,
+        _: Request<tinkoff_invest_api::GetOrderBookRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetOrderBookResponse> {
+        unimplemented("GetOrderBook")
+    }
+
+    async fn get_trading_status(
+        &self,
+        _: Request<tinkoff_invest_api::GetTradingStatusRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetTradingStatusResponse> {
+        unimplemented("GetTradingStatus")
+    }
+}
+
+#[tonic::async_trait]
+impl OperationsService for FakeBroker {
+    async fn get_operations(
+        &self,
+        _: Request<tinkoff_invest_api::OperationsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::OperationsResponse> {
+        unimplemented("GetOperations")
+    }
+
+    async fn get_portfolio(
+        &self,
+        _: Request<tinkoff_invest_api::PortfolioRequest>,
+    ) -> RpcResult<tinkoff_invest_api::PortfolioResponse> {
+        unimplemented("GetPortfolio")
+    }
+
+    async fn get_positions(
+        &self,
+        request: Request<tinkoff_invest_api::PositionsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::PositionsResponse> {
+        let positions = self
+            .book()
+            .positions(false, &request.into_inner().account_id)?;
+
+        Ok(Response::new(positions))
+    }
+
+    async fn get_withdraw_limits(
+        &self,
+        _: Request<tinkoff_invest_api::WithdrawLimitsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::WithdrawLimitsResponse> {
+        unimplemented("GetWithdrawLimits")
+    }
+
+    async fn get_broker_report(
+        &self,
+        _: Request<tinkoff_invest_api::BrokerReportRequest>,
+    ) -> RpcResult<tinkoff_invest_api::BrokerReportResponse> {
+        unimplemented("GetBrokerReport")
+    }
+
+    async fn get_dividends_foreign_issuer(
+        &self,
+        _: Request<tinkoff_invest_api::GetDividendsForeignIssuerRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetDividendsForeignIssuerResponse> {
+        unimplemented("GetDividendsForeignIssuer")
+    }
+}
+
+#[tonic::async_trait]
+impl OrdersService for FakeBroker {
+    async fn post_order(
+        &self,
+        request: Request<tinkoff_invest_api::PostOrderRequest>,
+    ) -> RpcResult<tinkoff_invest_api::PostOrderResponse> {
+        let request = request.into_inner();
+        let quote = self.quote(&request.figi);
+        let response = self.book().post_order(false, &request, quote)?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn cancel_order(
+        &self,
+        request: Request<tinkoff_invest_api::CancelOrderRequest>,
+    ) -> RpcResult<tinkoff_invest_api::CancelOrderResponse> {
+        let request = request.into_inner();
+        self.book()
+            .cancel_order(false, &request.account_id, &request.order_id)?;
+
+        Ok(Response::new(tinkoff_invest_api::CancelOrderResponse {
+            time: Some(conversions::timestamp(Utc::now())),
+        }))
+    }
+
+    async fn get_order_state(
+        &self,
+        request: Request<tinkoff_invest_api::GetOrderStateRequest>,
+    ) -> RpcResult<tinkoff_invest_api::OrderState> {
+        let request = request.into_inner();
+        let state = self
+            .book()
+            .order_state(false, &request.account_id, &request.order_id)?;
+
+        Ok(Response::new(state))
+    }
+
+    async fn get_orders(
+        &self,
+        request: Request<tinkoff_invest_api::GetOrdersRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetOrdersResponse> {
+        let orders = self
+            .book()
+            .orders(false, &request.into_inner().account_id)?;
+
+        Ok(Response::new(tinkoff_invest_api::GetOrdersResponse {
+            orders,
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl StopOrdersService for FakeBroker {
+    async fn post_stop_order(
+        &self,
+        request: Request<tinkoff_invest_api::PostStopOrderRequest>,
+    ) -> RpcResult<tinkoff_invest_api::PostStopOrderResponse> {
+        let stop_order_id = self.book().post_stop_order(&request.into_inner())?;
+
+        Ok(Response::new(tinkoff_invest_api::PostStopOrderResponse {
+            stop_order_id,
+        }))
+    }
+
+    async fn get_stop_orders(
+        &self,
+        request: Request<tinkoff_invest_api::GetStopOrdersRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetStopOrdersResponse> {
+        let stop_orders = self.book().stop_orders(&request.into_inner().account_id)?;
+
+        Ok(Response::new(tinkoff_invest_api::GetStopOrdersResponse {
+            stop_orders,
+        }))
+    }
+
+    async fn cancel_stop_order(
+        &self,
+        request: Request<tinkoff_invest_api::CancelStopOrderRequest>,
+    ) -> RpcResult<tinkoff_invest_api::CancelStopOrderResponse> {
+        let request = request.into_inner();
+        self.book()
+            .cancel_stop_order(&request.account_id, &request.stop_order_id)?;
+
+        Ok(Response::new(tinkoff_invest_api::CancelStopOrderResponse {
+            time: Some(conversions::timestamp(Utc::now())),
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl UsersService for FakeBroker {
+    async fn get_accounts(
+        &self,
+        _: Request<tinkoff_invest_api::GetAccountsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetAccountsResponse> {
+        Ok(Response::new(tinkoff_invest_api::GetAccountsResponse {
+            accounts: self.book().accounts(false),
+        }))
+    }
+
+    async fn get_margin_attributes(
+        &self,
+        _: Request<tinkoff_invest_api::GetMarginAttributesRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetMarginAttributesResponse> {
+        unimplemented("GetMarginAttributes")
+    }
+
+    async fn get_user_tariff(
+        &self,
+        _: Request<tinkoff_invest_api::GetUserTariffRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetUserTariffResponse> {
+        unimplemented("GetUserTariff")
+    }
+
+    async fn get_info(
+        &self,
+        _: Request<tinkoff_invest_api::GetInfoRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetInfoResponse> {
+        Ok(Response::new(tinkoff_invest_api::GetInfoResponse {
+            tariff: "fake".to_owned(),
+            ..Default::default()
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl SandboxService for FakeBroker {
+    async fn open_sandbox_account(
+        &self,
+        _: Request<tinkoff_invest_api::OpenSandboxAccountRequest>,
+    ) -> RpcResult<tinkoff_invest_api::OpenSandboxAccountResponse> {
+        let account_id = self.book().open_account(true, "Sandbox", 0.0);
+
+        Ok(Response::new(
+            tinkoff_invest_api::OpenSandboxAccountResponse { account_id },
+        ))
+    }
+
+    async fn get_sandbox_accounts(
+        &self,
+        _: Request<tinkoff_invest_api::GetAccountsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetAccountsResponse> {
+        Ok(Response::new(tinkoff_invest_api::GetAccountsResponse {
+            accounts: self.book().accounts(true),
+        }))
+    }
+
+    async fn close_sandbox_account(
+        &self,
+        request: Request<tinkoff_invest_api::CloseSandboxAccountRequest>,
+    ) -> RpcResult<tinkoff_invest_api::CloseSandboxAccountResponse> {
+        self.book()
+            .close_account(true, &request.into_inner().account_id)?;
+
+        Ok(Response::new(
+            tinkoff_invest_api::CloseSandboxAccountResponse {},
+        ))
+    }
+
+    async fn post_sandbox_order(
+        &self,
+        request: Request<tinkoff_invest_api::PostOrderRequest>,
+    ) -> RpcResult<tinkoff_invest_api::PostOrderResponse> {
+        let request = request.into_inner();
+        let quote = self.quote(&request.figi);
+        let response = self.book().post_order(true, &request, quote)?;
+
+        Ok(Response::new(response))
+    }
+
+    async fn get_sandbox_orders(
+        &self,
+        request: Request<tinkoff_invest_api::GetOrdersRequest>,
+    ) -> RpcResult<tinkoff_invest_api::GetOrdersResponse> {
+        let orders = self.book().orders(true, &request.into_inner().account_id)?;
+
+        Ok(Response::new(tinkoff_invest_api::GetOrdersResponse {
+            orders,
+        }))
+    }
+
+    async fn cancel_sandbox_order(
+        &self,
+        request: Request<tinkoff_invest_api::CancelOrderRequest>,
+    ) -> RpcResult<tinkoff_invest_api::CancelOrderResponse> {
+        let request = request.into_inner();
+        self.book()
+            .cancel_order(true, &request.account_id, &request.order_id)?;
+
+        Ok(Response::new(tinkoff_invest_api::CancelOrderResponse {
+            time: Some(conversions::timestamp(Utc::now())),
+        }))
+    }
+
+    async fn get_sandbox_order_state(
+        &self,
+        request: Request<tinkoff_invest_api::GetOrderStateRequest>,
+    ) -> RpcResult<tinkoff_invest_api::OrderState> {
+        let request = request.into_inner();
+        let state = self
+            .book()
+            .order_state(true, &request.account_id, &request.order_id)?;
+
+        Ok(Response::new(state))
+    }
+
+    async fn get_sandbox_positions(
+        &self,
+        request: Request<tinkoff_invest_api::PositionsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::PositionsResponse> {
+        let positions = self
+            .book()
+            .positions(true, &request.into_inner().account_id)?;
+
+        Ok(Response::new(positions))
+    }
+
+    async fn get_sandbox_operations(
+        &self,
+        _: Request<tinkoff_invest_api::OperationsRequest>,
+    ) -> RpcResult<tinkoff_invest_api::OperationsResponse> {
+        unimplemented("GetSandboxOperations")
+    }
+
+    async fn get_sandbox_portfolio(
+        &self,
+        _: Request<tinkoff_invest_api::PortfolioRequest>,
+    ) -> RpcResult<tinkoff_invest_api::PortfolioResponse> {
+        unimplemented("GetSandboxPortfolio")
+    }
+
+    async fn sandbox_pay_in(
+        &self,
+        request: Request<tinkoff_invest_api::SandboxPayInRequest>,
+    ) -> RpcResult<tinkoff_invest_api::SandboxPayInResponse> {
+        let request = request.into_inner();
+        let amount = request
+            .amount
+            .as_ref()
+            .map(conversions::from_money)
+            .ok_or_else(|| Status::invalid_argument("`amount` is missing"))?;
+
+        let balance = self.book().pay_in(true, &request.account_id, amount)?;
+
+        Ok(Response::new(tinkoff_invest_api::SandboxPayInResponse {
+            balance: Some(balance),
+        }))
+    }
+}
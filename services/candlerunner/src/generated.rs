@@ -1,3 +1,4 @@
+#[allow(clippy::all)]
 pub mod tinkoff_invest_api {
     tonic::include_proto!("tinkoff.public.invest.api.contract.v1");
 }
@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_atr_ns;
+use crate::utils::id_generator::IdGenerator;
+
+/// Average true range with Wilder's smoothing
+pub struct Atr {
+    figi: Figi,
+    period: usize,
+}
+
+impl Atr {
+    pub fn new(figi: Figi, period: usize) -> Self {
+        Self {
+            figi,
+            period: period.max(1),
+        }
+    }
+}
+
+impl InstanceId for Atr {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("period", (self.period as u64).to_le_bytes());
+
+        generator.generate(get_atr_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AtrState {
+    prev_close: Option<f64>,
+
+    /// True ranges of the first period, averaged once period is complete
+    warmup: Vec<f64>,
+    value: Option<f64>,
+}
+
+impl ExtractIndicatorValue for AtrState {
+    type ValueType = f64;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Atr {
+    type Input = Candle;
+    type ValueType = f64;
+    type State = AtrState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        let true_range = match state.prev_close.replace(input.close) {
+            Some(prev_close) => (input.high - input.low)
+                .max((input.high - prev_close).abs())
+                .max((input.low - prev_close).abs()),
+            None => input.high - input.low,
+        };
+
+        let period = self.period as f64;
+
+        state.value = match state.value {
+            Some(prev) => Some((prev * (period - 1.0) + true_range) / period),
+            None => {
+                state.warmup.push(true_range);
+
+                match state.warmup.len() >= self.period {
+                    true => {
+                        let seed = state.warmup.iter().sum::<f64>() / period;
+                        state.warmup.clear();
+                        Some(seed)
+                    }
+                    false => None,
+                }
+            }
+        };
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candle};
+
+    /// Worked example of Wilder's definition ("New Concepts in Technical Trading Systems", 1978),
+    /// seeded the way StockCharts ChartSchool describes it: the first true range is high minus low,
+    /// the first ATR is the average of the first `period` true ranges
+    #[test]
+    fn test_atr() {
+        let atr = Atr::new(Figi("FIGI".to_owned()), 3);
+        let mut state = AtrState::default();
+
+        let expected = [
+            // TR = 12 - 10 = 2
+            None,
+            // TR = max(12.5 - 11.5, |12.5 - 11|, |11.5 - 11|) = 1.5
+            None,
+            // TR = max(13 - 12, |13 - 12|, |12 - 12|) = 1, ATR = (2 + 1.5 + 1) / 3
+            Some(1.5),
+            // TR = max(12 - 9, |12 - 12.5|, |9 - 12.5|) = 3.5, ATR = (1.5 * 2 + 3.5) / 3
+            Some(13.0 / 6.0),
+            // TR = max(11 - 10.5, |11 - 9.5|, |10.5 - 9.5|) = 1.5, ATR = (13 / 6 * 2 + 1.5) / 3
+            Some(35.0 / 18.0),
+        ];
+
+        let candles = [
+            candle(12.0, 10.0, 11.0, 0),
+            candle(12.5, 11.5, 12.0, 0),
+            candle(13.0, 12.0, 12.5, 0),
+            candle(12.0, 9.0, 9.5, 0),
+            candle(11.0, 10.5, 10.5, 0),
+        ];
+
+        for (candle, expected) in candles.iter().zip(expected) {
+            state = atr.update(state, candle);
+
+            match expected {
+                Some(expected) => assert_close(state.extract_value().unwrap(), expected),
+                None => assert!(state.extract_value().is_none()),
+            }
+        }
+    }
+}
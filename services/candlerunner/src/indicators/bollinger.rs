@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_bollinger_ns;
+use crate::utils::id_generator::IdGenerator;
+
+use super::push_window;
+
+/// Bollinger bands: simple moving average of close prices
+/// plus/minus `width` population standard deviations
+pub struct Bollinger {
+    figi: Figi,
+    period: usize,
+    width: f64,
+}
+
+impl Bollinger {
+    pub fn new(figi: Figi, period: usize, width: f64) -> Self {
+        Self {
+            figi,
+            period: period.max(1),
+            width,
+        }
+    }
+}
+
+impl InstanceId for Bollinger {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("period", (self.period as u64).to_le_bytes());
+        generator.add("width", self.width.to_le_bytes());
+
+        generator.generate(get_bollinger_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BollingerValue {
+    pub lower: f64,
+    pub middle: f64,
+    pub upper: f64,
+}
+
+impl BollingerValue {
+    /// Distance between bands relative to middle band
+    pub fn bandwidth(&self) -> f64 {
+        (self.upper - self.lower) / self.middle
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct BollingerState {
+    window: VecDeque<f64>,
+    value: Option<BollingerValue>,
+}
+
+impl ExtractIndicatorValue for BollingerState {
+    type ValueType = BollingerValue;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Bollinger {
+    type Input = Candle;
+    type ValueType = BollingerValue;
+    type State = BollingerState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        if !push_window(&mut state.window, input.close, self.period) {
+            return state;
+        }
+
+        let period = self.period as f64;
+        let middle = state.window.iter().sum::<f64>() / period;
+        let variance = state
+            .window
+            .iter()
+            .map(|close| (close - middle).powi(2))
+            .sum::<f64>()
+            / period;
+        let offset = self.width * variance.sqrt();
+
+        state.value = Some(BollingerValue {
+            lower: middle - offset,
+            middle,
+            upper: middle + offset,
+        });
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candles};
+
+    #[test]
+    fn test_bollinger() {
+        let bollinger = Bollinger::new(Figi("FIGI".to_owned()), 20, 2.0);
+        let mut state = BollingerState::default();
+
+        for (i, candle) in candles().iter().enumerate() {
+            state = bollinger.update(state, candle);
+
+            match i {
+                0..=18 => assert!(state.extract_value().is_none()),
+                19 => {
+                    let value = state.extract_value().unwrap();
+                    assert_close(value.lower, 43.70267177834978);
+                    assert_close(value.middle, 45.409);
+                    assert_close(value.upper, 47.115328221650216);
+                }
+                _ => (),
+            }
+        }
+
+        let value = state.extract_value().unwrap();
+        assert_close(value.lower, 42.86184973152178);
+        assert_close(value.middle, 45.241);
+        assert_close(value.upper, 47.62015026847822);
+    }
+}
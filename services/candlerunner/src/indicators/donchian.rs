@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_donchian_ns;
+use crate::utils::id_generator::IdGenerator;
+
+use super::push_window;
+
+/// Donchian channel: highest high and lowest low of the last `period` candles
+pub struct Donchian {
+    figi: Figi,
+    period: usize,
+}
+
+impl Donchian {
+    pub fn new(figi: Figi, period: usize) -> Self {
+        Self {
+            figi,
+            period: period.max(1),
+        }
+    }
+}
+
+impl InstanceId for Donchian {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("period", (self.period as u64).to_le_bytes());
+
+        generator.generate(get_donchian_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DonchianValue {
+    pub lower: f64,
+    pub middle: f64,
+    pub upper: f64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct DonchianState {
+    /// (high, low) pairs
+    window: VecDeque<(f64, f64)>,
+    value: Option<DonchianValue>,
+}
+
+impl ExtractIndicatorValue for DonchianState {
+    type ValueType = DonchianValue;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Donchian {
+    type Input = Candle;
+    type ValueType = DonchianValue;
+    type State = DonchianState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        if !push_window(&mut state.window, (input.high, input.low), self.period) {
+            return state;
+        }
+
+        let upper = state
+            .window
+            .iter()
+            .map(|(high, _)| *high)
+            .fold(f64::MIN, f64::max);
+        let lower = state
+            .window
+            .iter()
+            .map(|(_, low)| *low)
+            .fold(f64::MAX, f64::min);
+
+        state.value = Some(DonchianValue {
+            lower,
+            middle: (lower + upper) / 2.0,
+            upper,
+        });
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candles};
+
+    #[test]
+    fn test_donchian() {
+        let donchian = Donchian::new(Figi("FIGI".to_owned()), 20);
+        let mut state = DonchianState::default();
+
+        for (i, candle) in candles().iter().enumerate() {
+            state = donchian.update(state, candle);
+
+            match i {
+                0..=18 => assert!(state.extract_value().is_none()),
+                19 => {
+                    let value = state.extract_value().unwrap();
+                    assert_close(value.lower, 43.01);
+                    assert_close(value.middle, 45.06);
+                    assert_close(value.upper, 47.11);
+                }
+                _ => (),
+            }
+        }
+
+        let value = state.extract_value().unwrap();
+        assert_close(value.lower, 42.06);
+        assert_close(value.middle, 44.605);
+        assert_close(value.upper, 47.15);
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_ema_ns;
+use crate::utils::id_generator::IdGenerator;
+
+/// Exponential moving average of close prices.
+/// Seeded with simple average of the first `period` values.
+pub struct Ema {
+    figi: Figi,
+    period: usize,
+}
+
+impl Ema {
+    pub fn new(figi: Figi, period: usize) -> Self {
+        Self {
+            figi,
+            period: period.max(1),
+        }
+    }
+}
+
+impl InstanceId for Ema {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("period", (self.period as u64).to_le_bytes());
+
+        generator.generate(get_ema_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct EmaState {
+    warmup: Vec<f64>,
+    value: Option<f64>,
+}
+
+impl EmaState {
+    /// Feeds the next value and returns the updated average
+    pub(super) fn push(&mut self, period: usize, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(prev) => {
+                let alpha = 2.0 / (period as f64 + 1.0);
+                Some(alpha * value + (1.0 - alpha) * prev)
+            }
+            None => {
+                self.warmup.push(value);
+
+                match self.warmup.len() >= period {
+                    true => {
+                        let seed = self.warmup.iter().sum::<f64>() / period as f64;
+                        self.warmup.clear();
+                        Some(seed)
+                    }
+                    false => None,
+                }
+            }
+        };
+
+        self.value
+    }
+}
+
+impl ExtractIndicatorValue for EmaState {
+    type ValueType = f64;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Ema {
+    type Input = Candle;
+    type ValueType = f64;
+    type State = EmaState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        state.push(self.period, input.close);
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candles};
+
+    #[test]
+    fn test_ema() {
+        let ema = Ema::new(Figi("FIGI".to_owned()), 10);
+        let mut state = EmaState::default();
+
+        for (i, candle) in candles().iter().enumerate() {
+            state = ema.update(state, candle);
+
+            match i {
+                0..=8 => assert!(state.extract_value().is_none()),
+                9 => assert_close(state.extract_value().unwrap(), 44.779),
+                _ => (),
+            }
+        }
+
+        assert_close(state.extract_value().unwrap(), 44.11929901522181);
+    }
+}
@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_macd_ns;
+use crate::utils::id_generator::IdGenerator;
+
+use super::ema::EmaState;
+
+/// Moving average convergence/divergence of close prices
+pub struct Macd {
+    figi: Figi,
+    fast_period: usize,
+    slow_period: usize,
+    signal_period: usize,
+}
+
+impl Macd {
+    pub fn new(figi: Figi, fast_period: usize, slow_period: usize, signal_period: usize) -> Self {
+        Self {
+            figi,
+            fast_period: fast_period.max(1),
+            slow_period: slow_period.max(1),
+            signal_period: signal_period.max(1),
+        }
+    }
+}
+
+impl InstanceId for Macd {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("fastPeriod", (self.fast_period as u64).to_le_bytes());
+        generator.add("slowPeriod", (self.slow_period as u64).to_le_bytes());
+        generator.add("signalPeriod", (self.signal_period as u64).to_le_bytes());
+
+        generator.generate(get_macd_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MacdValue {
+    /// Difference between fast and slow averages
+    pub macd: f64,
+
+    /// Average of MACD line
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct MacdState {
+    fast: EmaState,
+    slow: EmaState,
+    signal: EmaState,
+    value: Option<MacdValue>,
+}
+
+impl ExtractIndicatorValue for MacdState {
+    type ValueType = MacdValue;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Macd {
+    type Input = Candle;
+    type ValueType = MacdValue;
+    type State = MacdState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        let fast = state.fast.push(self.fast_period, input.close);
+        let slow = state.slow.push(self.slow_period, input.close);
+
+        let macd = match (fast, slow) {
+            (Some(fast), Some(slow)) => fast - slow,
+            _ => return state,
+        };
+
+        state.value = state
+            .signal
+            .push(self.signal_period, macd)
+            .map(|signal| MacdValue {
+                macd,
+                signal,
+                histogram: macd - signal,
+            });
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candles};
+
+    #[test]
+    fn test_macd() {
+        let macd = Macd::new(Figi("FIGI".to_owned()), 5, 10, 4);
+        let mut state = MacdState::default();
+
+        for (i, candle) in candles().iter().enumerate() {
+            state = macd.update(state, candle);
+
+            match i {
+                0..=11 => assert!(state.extract_value().is_none()),
+                12 => {
+                    let value = state.extract_value().unwrap();
+                    assert_close(value.macd, 0.45772166483391175);
+                    assert_close(value.signal, 0.599332617231056);
+                    assert_close(value.histogram, -0.14161095239714427);
+                }
+                _ => (),
+            }
+        }
+
+        let value = state.extract_value().unwrap();
+        assert_close(value.macd, -0.6082205441401882);
+        assert_close(value.signal, -0.54101124231374);
+        assert_close(value.histogram, -0.06720930182644824);
+    }
+}
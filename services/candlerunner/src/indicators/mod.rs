@@ -0,0 +1,87 @@
+mod atr;
+mod bollinger;
+mod donchian;
+mod ema;
+mod macd;
+mod obv;
+mod roc;
+mod rsi;
+mod sma;
+mod spread;
+mod stochastic;
+mod vwap;
+mod wma;
+
+use std::collections::VecDeque;
+
+pub use atr::Atr;
+pub use bollinger::Bollinger;
+pub use donchian::Donchian;
+pub use ema::Ema;
+pub use macd::Macd;
+pub use obv::Obv;
+pub use roc::Roc;
+pub use rsi::Rsi;
+pub use sma::Sma;
+pub use spread::Spread;
+pub use stochastic::Stochastic;
+pub use vwap::Vwap;
+pub use wma::Wma;
+
+/// Appends value to a rolling window of at most `period` items.
+/// Returns `true` when the window is full.
+fn push_window<T>(window: &mut VecDeque<T>, value: T, period: usize) -> bool {
+    window.push_back(value);
+
+    while window.len() > period {
+        window.pop_front();
+    }
+
+    window.len() == period
+}
+
+#[cfg(test)]
+mod test_data {
+    use crate::models::market_data::Candle;
+
+    /// Close prices of the classic Wilder's RSI example
+    const CLOSES: [f64; 33] = [
+        44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61,
+        46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64, 46.21, 46.25, 45.71, 46.45, 45.78, 45.35,
+        44.03, 44.18, 44.22, 44.57, 43.42, 42.66, 43.13,
+    ];
+
+    pub fn candles() -> Vec<Candle> {
+        CLOSES
+            .iter()
+            .enumerate()
+            .map(|(i, close)| Candle {
+                open: if i > 0 { CLOSES[i - 1] } else { *close },
+                high: close + 0.5 + (i % 3) as f64 * 0.1,
+                low: close - 0.5 - (i % 2) as f64 * 0.1,
+                close: *close,
+                volume: 1000 + 100 * (i % 5) as u64,
+            })
+            .collect()
+    }
+
+    /// Candle of a worked example, only high, low, close and volume matter
+    pub fn candle(high: f64, low: f64, close: f64, volume: u64) -> Candle {
+        Candle {
+            open: close,
+            high,
+            low,
+            close,
+            volume,
+        }
+    }
+
+    pub fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_obv_ns;
+use crate::utils::id_generator::IdGenerator;
+
+/// On-balance volume, starts from zero at the first candle
+pub struct Obv {
+    figi: Figi,
+}
+
+impl Obv {
+    pub fn new(figi: Figi) -> Self {
+        Self { figi }
+    }
+}
+
+impl InstanceId for Obv {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+
+        generator.generate(get_obv_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ObvState {
+    prev_close: Option<f64>,
+    value: Option<f64>,
+}
+
+impl ExtractIndicatorValue for ObvState {
+    type ValueType = f64;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Obv {
+    type Input = Candle;
+    type ValueType = f64;
+    type State = ObvState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        let obv = state.value.unwrap_or_default();
+        let volume = input.volume as f64;
+
+        state.value = match state.prev_close.replace(input.close) {
+            Some(prev_close) if input.close > prev_close => Some(obv + volume),
+            Some(prev_close) if input.close < prev_close => Some(obv - volume),
+            _ => Some(obv),
+        };
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candle};
+
+    /// Ten day example of Investopedia's "On-Balance Volume (OBV)" article
+    #[test]
+    fn test_obv() {
+        let obv = Obv::new(Figi("FIGI".to_owned()));
+        let mut state = ObvState::default();
+
+        let days = [
+            (10.00, 25_200, 0.0),
+            (10.15, 30_000, 30_000.0),
+            (10.17, 25_600, 55_600.0),
+            (10.13, 32_000, 23_600.0),
+            (10.11, 23_000, 600.0),
+            (10.15, 40_000, 40_600.0),
+            (10.20, 36_000, 76_600.0),
+            (10.20, 20_500, 76_600.0),
+            (10.22, 23_000, 99_600.0),
+            (10.21, 27_500, 72_100.0),
+        ];
+
+        for (close, volume, expected) in days {
+            state = obv.update(state, &candle(close, close, close, volume));
+
+            assert_close(state.extract_value().unwrap(), expected);
+        }
+    }
+}
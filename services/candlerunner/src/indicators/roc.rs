@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_roc_ns;
+use crate::utils::id_generator::IdGenerator;
+
+use super::push_window;
+
+/// Rate of change: relative change of close price over the last `period` candles
+pub struct Roc {
+    figi: Figi,
+    period: usize,
+}
+
+impl Roc {
+    pub fn new(figi: Figi, period: usize) -> Self {
+        Self {
+            figi,
+            period: period.max(1),
+        }
+    }
+}
+
+impl InstanceId for Roc {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("period", (self.period as u64).to_le_bytes());
+
+        generator.generate(get_roc_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct RocState {
+    window: VecDeque<f64>,
+    value: Option<f64>,
+}
+
+impl ExtractIndicatorValue for RocState {
+    type ValueType = f64;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Roc {
+    type Input = Candle;
+    type ValueType = f64;
+    type State = RocState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        if !push_window(&mut state.window, input.close, self.period + 1) {
+            return state;
+        }
+
+        let first = state.window[0];
+        if first > 0.0 {
+            state.value = Some(input.close / first - 1.0);
+        }
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candles};
+
+    #[test]
+    fn test_roc() {
+        let roc = Roc::new(Figi("FIGI".to_owned()), 10);
+        let mut state = RocState::default();
+
+        for (i, candle) in candles().iter().enumerate() {
+            state = roc.update(state, candle);
+
+            match i {
+                0..=9 => assert!(state.extract_value().is_none()),
+                10 => assert_close(state.extract_value().unwrap(), 0.034957149300856916),
+                _ => (),
+            }
+        }
+
+        assert_close(state.extract_value().unwrap(), -0.05644279151170417);
+    }
+}
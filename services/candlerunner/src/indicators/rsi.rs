@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_rsi_ns;
+use crate::utils::id_generator::IdGenerator;
+
+/// Relative strength index of close prices with Wilder's smoothing, within [0; 100]
+pub struct Rsi {
+    figi: Figi,
+    period: usize,
+}
+
+impl Rsi {
+    pub fn new(figi: Figi, period: usize) -> Self {
+        Self {
+            figi,
+            period: period.max(1),
+        }
+    }
+}
+
+impl InstanceId for Rsi {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("period", (self.period as u64).to_le_bytes());
+
+        generator.generate(get_rsi_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RsiState {
+    prev_close: Option<f64>,
+
+    /// Number of price changes seen, saturates at period
+    changes: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+    value: Option<f64>,
+}
+
+impl ExtractIndicatorValue for RsiState {
+    type ValueType = f64;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Rsi {
+    type Input = Candle;
+    type ValueType = f64;
+    type State = RsiState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        let prev_close = match state.prev_close.replace(input.close) {
+            Some(prev_close) => prev_close,
+            None => return state,
+        };
+
+        let change = input.close - prev_close;
+        let gain = change.max(0.0);
+        let loss = (-change).max(0.0);
+        let period = self.period as f64;
+
+        if state.changes < self.period {
+            // Simple average over the first period
+            state.changes += 1;
+            state.avg_gain += gain / period;
+            state.avg_loss += loss / period;
+
+            if state.changes < self.period {
+                return state;
+            }
+        } else {
+            state.avg_gain = (state.avg_gain * (period - 1.0) + gain) / period;
+            state.avg_loss = (state.avg_loss * (period - 1.0) + loss) / period;
+        }
+
+        state.value = match state.avg_loss > 0.0 {
+            true => Some(100.0 - 100.0 / (1.0 + state.avg_gain / state.avg_loss)),
+            false => Some(100.0),
+        };
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candles};
+
+    #[test]
+    fn test_rsi() {
+        let rsi = Rsi::new(Figi("FIGI".to_owned()), 14);
+        let mut state = RsiState::default();
+
+        for (i, candle) in candles().iter().enumerate() {
+            state = rsi.update(state, candle);
+
+            match i {
+                0..=13 => assert!(state.extract_value().is_none()),
+                14 => assert_close(state.extract_value().unwrap(), 70.46413502109705),
+                _ => (),
+            }
+        }
+
+        assert_close(state.extract_value().unwrap(), 37.788771982057824);
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_sma_ns;
+use crate::utils::id_generator::IdGenerator;
+
+use super::push_window;
+
+/// Simple moving average of close prices
+pub struct Sma {
+    figi: Figi,
+    period: usize,
+}
+
+impl Sma {
+    pub fn new(figi: Figi, period: usize) -> Self {
+        Self {
+            figi,
+            period: period.max(1),
+        }
+    }
+}
+
+impl InstanceId for Sma {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("period", (self.period as u64).to_le_bytes());
+
+        generator.generate(get_sma_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct SmaState {
+    window: VecDeque<f64>,
+    value: Option<f64>,
+}
+
+impl ExtractIndicatorValue for SmaState {
+    type ValueType = f64;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Sma {
+    type Input = Candle;
+    type ValueType = f64;
+    type State = SmaState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        state.value = match push_window(&mut state.window, input.close, self.period) {
+            true => Some(state.window.iter().sum::<f64>() / self.period as f64),
+            false => None,
+        };
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candles};
+
+    #[test]
+    fn test_sma() {
+        let sma = Sma::new(Figi("FIGI".to_owned()), 10);
+        let mut state = SmaState::default();
+
+        for (i, candle) in candles().iter().enumerate() {
+            state = sma.update(state, candle);
+
+            match i {
+                0..=8 => assert!(state.extract_value().is_none()),
+                9 => assert_close(state.extract_value().unwrap(), 44.779),
+                _ => (),
+            }
+        }
+
+        assert_close(state.extract_value().unwrap(), 44.379);
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::namespaces::get_spread_ns;
+use crate::utils::id_generator::IdGenerator;
+
+use super::push_window;
+
+/// Spread between log prices of two instruments `ln(first) - hedge_ratio * ln(second)`.
+/// Hedge ratio is estimated by least squares over the last `period` price pairs,
+/// z-score is measured against spreads of the same window.
+pub struct Spread {
+    first: Figi,
+    second: Figi,
+    period: usize,
+}
+
+impl Spread {
+    pub fn new(first: Figi, second: Figi, period: usize) -> Self {
+        Self {
+            first,
+            second,
+            period: period.max(2),
+        }
+    }
+}
+
+impl InstanceId for Spread {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("first", self.first.0.as_bytes());
+        generator.add("second", self.second.0.as_bytes());
+        generator.add("period", (self.period as u64).to_le_bytes());
+
+        generator.generate(get_spread_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SpreadValue {
+    pub hedge_ratio: f64,
+    pub spread: f64,
+    pub z_score: f64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct SpreadState {
+    /// Log prices of (first, second) instruments
+    window: VecDeque<(f64, f64)>,
+    value: Option<SpreadValue>,
+}
+
+impl ExtractIndicatorValue for SpreadState {
+    type ValueType = SpreadValue;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Spread {
+    /// Close prices of (first, second) instruments
+    type Input = (f64, f64);
+    type ValueType = SpreadValue;
+    type State = SpreadState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        let (first, second) = *input;
+        if first <= 0.0 || second <= 0.0 {
+            return state;
+        }
+
+        if !push_window(&mut state.window, (first.ln(), second.ln()), self.period) {
+            return state;
+        }
+
+        let period = self.period as f64;
+        let mean_first = state.window.iter().map(|(first, _)| first).sum::<f64>() / period;
+        let mean_second = state.window.iter().map(|(_, second)| second).sum::<f64>() / period;
+
+        let (covariance, variance) =
+            state
+                .window
+                .iter()
+                .fold((0.0, 0.0), |(covariance, variance), (first, second)| {
+                    (
+                        covariance + (first - mean_first) * (second - mean_second),
+                        variance + (second - mean_second).powi(2),
+                    )
+                });
+
+        // Hedge ratio is undefined while second instrument price is flat
+        if variance <= 0.0 {
+            state.value = None;
+            return state;
+        }
+
+        let hedge_ratio = covariance / variance;
+        let spreads = state
+            .window
+            .iter()
+            .map(|(first, second)| first - hedge_ratio * second)
+            .collect::<Vec<f64>>();
+
+        let mean = spreads.iter().sum::<f64>() / period;
+        let std = (spreads
+            .iter()
+            .map(|spread| (spread - mean).powi(2))
+            .sum::<f64>()
+            / period)
+            .sqrt();
+
+        let spread = spreads[spreads.len() - 1];
+        let z_score = match std > 0.0 {
+            true => (spread - mean) / std,
+            false => 0.0,
+        };
+
+        state.value = Some(SpreadValue {
+            hedge_ratio,
+            spread,
+            z_score,
+        });
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candles};
+
+    const OFFSETS: [f64; 8] = [0.3, -0.2, 0.1, 0.4, -0.3, 0.0, 0.2, -0.1];
+
+    #[test]
+    fn test_spread() {
+        let spread = Spread::new(Figi("FIRST".to_owned()), Figi("SECOND".to_owned()), 10);
+        let mut state = SpreadState::default();
+
+        for (i, candle) in candles().iter().enumerate() {
+            let first = 2.0 * candle.close + OFFSETS[i % OFFSETS.len()];
+            state = spread.update(state, &(first, candle.close));
+
+            match i {
+                0..=8 => assert!(state.extract_value().is_none()),
+                9 => {
+                    let value = state.extract_value().unwrap();
+                    assert_close(value.hedge_ratio, 0.9710736765075992);
+                    assert_close(value.spread, 0.8017734660338771);
+                    assert_close(value.z_score, -0.7400267377970907);
+                }
+                _ => (),
+            }
+        }
+
+        let value = state.extract_value().unwrap();
+        assert_close(value.hedge_ratio, 0.9825456397384342);
+        assert_close(value.spread, 0.7623210356630379);
+        assert_close(value.z_score, 0.9215112911019913);
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_stochastic_ns;
+use crate::utils::id_generator::IdGenerator;
+
+use super::push_window;
+
+/// Stochastic oscillator: position of close within high-low range
+/// of the last `k_period` candles, within [0; 100]
+pub struct Stochastic {
+    figi: Figi,
+    k_period: usize,
+    d_period: usize,
+}
+
+impl Stochastic {
+    pub fn new(figi: Figi, k_period: usize, d_period: usize) -> Self {
+        Self {
+            figi,
+            k_period: k_period.max(1),
+            d_period: d_period.max(1),
+        }
+    }
+}
+
+impl InstanceId for Stochastic {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("kPeriod", (self.k_period as u64).to_le_bytes());
+        generator.add("dPeriod", (self.d_period as u64).to_le_bytes());
+
+        generator.generate(get_stochastic_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StochasticValue {
+    /// Fast line, %K
+    pub k: f64,
+
+    /// Simple average of %K, %D
+    pub d: f64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct StochasticState {
+    /// (high, low) pairs
+    ranges: VecDeque<(f64, f64)>,
+    k_values: VecDeque<f64>,
+    value: Option<StochasticValue>,
+}
+
+impl ExtractIndicatorValue for StochasticState {
+    type ValueType = StochasticValue;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Stochastic {
+    type Input = Candle;
+    type ValueType = StochasticValue;
+    type State = StochasticState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        if !push_window(&mut state.ranges, (input.high, input.low), self.k_period) {
+            return state;
+        }
+
+        let highest = state
+            .ranges
+            .iter()
+            .map(|(high, _)| *high)
+            .fold(f64::MIN, f64::max);
+        let lowest = state
+            .ranges
+            .iter()
+            .map(|(_, low)| *low)
+            .fold(f64::MAX, f64::min);
+
+        // Flat range gives no direction
+        let k = match highest > lowest {
+            true => 100.0 * (input.close - lowest) / (highest - lowest),
+            false => 50.0,
+        };
+
+        if !push_window(&mut state.k_values, k, self.d_period) {
+            return state;
+        }
+
+        state.value = Some(StochasticValue {
+            k,
+            d: state.k_values.iter().sum::<f64>() / self.d_period as f64,
+        });
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candle};
+
+    /// Worked example of Lane's definition as given by StockCharts ChartSchool:
+    /// %K = 100 * (close - lowest low) / (highest high - lowest low), %D = SMA of %K
+    #[test]
+    fn test_stochastic() {
+        let stochastic = Stochastic::new(Figi("FIGI".to_owned()), 3, 2);
+        let mut state = StochasticState::default();
+
+        let expected = [
+            None,
+            None,
+            // %K = 100 * (11 - 8) / (12 - 8), no %D yet
+            None,
+            // %K = 100 * (10 - 9) / (12 - 9), %D = (75 + 100 / 3) / 2
+            Some((100.0 / 3.0, 325.0 / 6.0)),
+            // %K = 100 * (8.5 - 8) / (12 - 8), %D = (100 / 3 + 12.5) / 2
+            Some((12.5, 275.0 / 12.0)),
+        ];
+
+        let candles = [
+            candle(10.0, 8.0, 9.0, 0),
+            candle(11.0, 9.0, 10.5, 0),
+            candle(12.0, 10.0, 11.0, 0),
+            candle(11.5, 9.5, 10.0, 0),
+            candle(11.0, 8.0, 8.5, 0),
+        ];
+
+        for (candle, expected) in candles.iter().zip(expected) {
+            state = stochastic.update(state, candle);
+
+            match expected {
+                Some((k, d)) => {
+                    let value = state.extract_value().unwrap();
+                    assert_close(value.k, k);
+                    assert_close(value.d, d);
+                }
+                None => assert!(state.extract_value().is_none()),
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_vwap_ns;
+use crate::utils::id_generator::IdGenerator;
+
+use super::push_window;
+
+/// Volume weighted average of typical price `(high + low + close) / 3`
+/// over the last `period` candles
+pub struct Vwap {
+    figi: Figi,
+    period: usize,
+}
+
+impl Vwap {
+    pub fn new(figi: Figi, period: usize) -> Self {
+        Self {
+            figi,
+            period: period.max(1),
+        }
+    }
+}
+
+impl InstanceId for Vwap {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("period", (self.period as u64).to_le_bytes());
+
+        generator.generate(get_vwap_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct VwapState {
+    /// (typical price, volume) pairs
+    window: VecDeque<(f64, f64)>,
+    value: Option<f64>,
+}
+
+impl ExtractIndicatorValue for VwapState {
+    type ValueType = f64;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Vwap {
+    type Input = Candle;
+    type ValueType = f64;
+    type State = VwapState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        let typical_price = (input.high + input.low + input.close) / 3.0;
+
+        if !push_window(
+            &mut state.window,
+            (typical_price, input.volume as f64),
+            self.period,
+        ) {
+            return state;
+        }
+
+        let volume: f64 = state.window.iter().map(|(_, volume)| volume).sum();
+        let turnover: f64 = state
+            .window
+            .iter()
+            .map(|(price, volume)| price * volume)
+            .sum();
+
+        // Last value is kept when there were no trades
+        if volume > 0.0 {
+            state.value = Some(turnover / volume);
+        }
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candle};
+
+    /// Worked example of the definition: sum of typical price times volume over sum of volume
+    #[test]
+    fn test_vwap() {
+        let vwap = Vwap::new(Figi("FIGI".to_owned()), 3);
+        let mut state = VwapState::default();
+
+        let expected = [
+            // Typical price 10, volume 100
+            None,
+            // Typical price 11, volume 200
+            None,
+            // Typical price 12, volume 100: (1000 + 2200 + 1200) / 400
+            Some(11.0),
+            // Typical price 9, volume 400: (2200 + 1200 + 3600) / 700
+            Some(10.0),
+            // No trades in the candle: (1200 + 3600) / 500
+            Some(9.6),
+            // 3600 / 400
+            Some(9.0),
+            // No trades in the window, the last value is kept
+            Some(9.0),
+        ];
+
+        let candles = [
+            candle(11.0, 9.0, 10.0, 100),
+            candle(12.0, 10.0, 11.0, 200),
+            candle(13.0, 11.0, 12.0, 100),
+            candle(10.0, 8.0, 9.0, 400),
+            candle(9.0, 9.0, 9.0, 0),
+            candle(9.0, 9.0, 9.0, 0),
+            candle(9.0, 9.0, 9.0, 0),
+        ];
+
+        for (candle, expected) in candles.iter().zip(expected) {
+            state = vwap.update(state, candle);
+
+            match expected {
+                Some(expected) => assert_close(state.extract_value().unwrap(), expected),
+                None => assert!(state.extract_value().is_none()),
+            }
+        }
+    }
+}
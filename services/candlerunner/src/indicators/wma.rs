@@ -0,0 +1,102 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::indicator::{ExtractIndicatorValue, Indicator};
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::market_data::Candle;
+use crate::models::namespaces::get_wma_ns;
+use crate::utils::id_generator::IdGenerator;
+
+use super::push_window;
+
+/// Linearly weighted moving average of close prices; the latest close has weight `period`
+pub struct Wma {
+    figi: Figi,
+    period: usize,
+}
+
+impl Wma {
+    pub fn new(figi: Figi, period: usize) -> Self {
+        Self {
+            figi,
+            period: period.max(1),
+        }
+    }
+}
+
+impl InstanceId for Wma {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("figi", self.figi.0.as_bytes());
+        generator.add("period", (self.period as u64).to_le_bytes());
+
+        generator.generate(get_wma_ns())
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct WmaState {
+    window: VecDeque<f64>,
+    value: Option<f64>,
+}
+
+impl ExtractIndicatorValue for WmaState {
+    type ValueType = f64;
+
+    fn extract_value(&self) -> Option<Self::ValueType> {
+        self.value
+    }
+}
+
+impl Indicator for Wma {
+    type Input = Candle;
+    type ValueType = f64;
+    type State = WmaState;
+
+    fn update(&self, mut state: Self::State, input: &Self::Input) -> Self::State {
+        state.value = match push_window(&mut state.window, input.close, self.period) {
+            true => {
+                let weighted: f64 = state
+                    .window
+                    .iter()
+                    .enumerate()
+                    .map(|(i, close)| (i + 1) as f64 * close)
+                    .sum();
+                let total_weight = (self.period * (self.period + 1) / 2) as f64;
+
+                Some(weighted / total_weight)
+            }
+            false => None,
+        };
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::indicators::test_data::{assert_close, candles};
+
+    #[test]
+    fn test_wma() {
+        let wma = Wma::new(Figi("FIGI".to_owned()), 10);
+        let mut state = WmaState::default();
+
+        for (i, candle) in candles().iter().enumerate() {
+            state = wma.update(state, candle);
+
+            match i {
+                0..=8 => assert!(state.extract_value().is_none()),
+                9 => assert_close(state.extract_value().unwrap(), 45.135636363636365),
+                _ => (),
+            }
+        }
+
+        assert_close(state.extract_value().unwrap(), 43.83618181818182);
+    }
+}
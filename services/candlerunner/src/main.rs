@@ -1,22 +1,170 @@
+mod backtest;
+mod candle_files;
 mod components;
+mod fake_broker;
 mod generated;
+mod indicators;
 mod models;
+mod position_managers;
 mod service;
 mod strategies;
 mod utils;
 
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
-use clap::Parser;
+use chrono::prelude::*;
+use clap::{Parser, Subcommand};
 
 use component_store::{ComponentStore, ConfigProvider};
 use yaml_config_provider::YamlConfigProvider;
 
+use models::corporate_actions::PriceAdjustment;
+use models::instruments::Figi;
+use models::market_data::CandleResolution;
+
 #[derive(Parser, Debug)]
 struct Args {
     #[clap(short, long, parse(from_os_str))]
     /// The path to the config file
-    config: std::path::PathBuf,
+    config: PathBuf,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Replaces stored candles with ones from a CSV or Parquet file
+    ImportCandles {
+        #[clap(long)]
+        figi: String,
+
+        #[clap(long)]
+        /// Stored resolution, e.g. `oneMinute`
+        resolution: CandleResolution,
+
+        #[clap(long, parse(from_os_str))]
+        /// CSV or `.parquet` file with `ts,open,high,low,close,volume` columns
+        input: PathBuf,
+
+        #[clap(long)]
+        /// Start of the replaced range, defaults to the midnight of the first candle
+        from: Option<DateTime<Utc>>,
+
+        #[clap(long)]
+        /// End of the replaced range, defaults to the end of the last candle
+        to: Option<DateTime<Utc>>,
+    },
+
+    /// Writes candles of any resolution to a CSV or Parquet file
+    ExportCandles {
+        #[clap(long)]
+        figi: String,
+
+        #[clap(long)]
+        resolution: CandleResolution,
+
+        #[clap(long, parse(from_os_str))]
+        output: PathBuf,
+
+        #[clap(long)]
+        from: DateTime<Utc>,
+
+        #[clap(long)]
+        to: DateTime<Utc>,
+
+        #[clap(long)]
+        /// Adjust prices before splits
+        split_adjusted: bool,
+
+        #[clap(long)]
+        /// Adjust prices before dividend ex-dates
+        dividend_adjusted: bool,
+    },
+
+    /// Serves the Tinkoff Invest API over a scripted market, point `tinkoff-client.url` to it
+    FakeBroker {
+        #[clap(long, default_value = "127.0.0.1:27002")]
+        address: SocketAddr,
+
+        #[clap(long, parse(from_os_str))]
+        /// Directory laid out as for the `files` data provider
+        directory: PathBuf,
+
+        #[clap(long, default_value = "1000000")]
+        /// Money on the production account opened at start
+        cash: f64,
+    },
+}
+
+async fn run_fake_broker(address: SocketAddr, directory: PathBuf, cash: f64) -> anyhow::Result<()> {
+    let broker = fake_broker::FakeBroker::new(fake_broker::FakeMarket::load(&directory)?);
+    let account_id = broker.open_account("Fake account", cash);
+    let listener = tokio::net::TcpListener::bind(address).await?;
+
+    println!(
+        "Fake broker listening on {} with account `{}`",
+        listener.local_addr()?,
+        account_id
+    );
+
+    broker.serve(listener).await
+}
+
+async fn run_command(command: Command, config: Box<YamlConfigProvider>) -> anyhow::Result<()> {
+    let component_store = ComponentStore::builder()
+        .register::<components::Mongo>()?
+        .build(config)
+        .await?;
+
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let result = match command {
+        Command::ImportCandles {
+            figi,
+            resolution,
+            input,
+            from,
+            to,
+        } => {
+            let format = candle_files::CandleFileFormat::of_path(&input);
+            let candles = candle_files::read_candles(format, std::fs::read(input)?.into())?;
+            let range = candle_files::import_range(resolution, &candles, from, to)?;
+
+            candle_files::import_candles(&mongo, &Figi(figi), resolution, candles, range)
+                .await
+                .map(|_| ())
+        }
+        Command::ExportCandles {
+            figi,
+            resolution,
+            output,
+            from,
+            to,
+            split_adjusted,
+            dividend_adjusted,
+        } => {
+            let adjustment = PriceAdjustment {
+                splits: split_adjusted,
+                dividends: dividend_adjusted,
+            };
+            let candles =
+                candle_files::export_candles(&mongo, &Figi(figi), resolution, from, to, adjustment)
+                    .await?;
+
+            let format = candle_files::CandleFileFormat::of_path(&output);
+
+            candle_files::write_candles(format, std::fs::File::create(output)?, &candles)
+        }
+        Command::FakeBroker { .. } => unreachable!("fake broker does not need components"),
+    };
+
+    component_store.destroy().await;
+
+    result
 }
 
 #[tokio::main]
@@ -24,21 +172,43 @@ async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
     let config = Box::new(YamlConfigProvider::new(args.config)?);
 
+    match args.command {
+        Some(Command::FakeBroker {
+            address,
+            directory,
+            cash,
+        }) => return run_fake_broker(address, directory, cash).await,
+        Some(command) => return run_command(command, config).await,
+        None => {}
+    }
+
     let service_config = config.get_subconfig("service")?;
     let addr: SocketAddr = service_config.get_str("address")?.parse()?;
 
     let component_store = ComponentStore::builder()
         .register::<components::AccountsCache>()?
+        .register::<components::BacktestRunner>()?
+        .register::<components::CorporateActionSync>()?
+        .register::<components::DataProvider>()?
+        .register::<components::DataVerifier>()?
         .register::<components::InstrumentCache>()?
         .register::<components::InstrumentSync>()?
+        .register::<components::MarketDataEvents>()?
+        .register::<components::MarketDataStream>()?
         .register::<components::MarketDataSync>()?
         .register::<components::Mongo>()?
+        .register::<components::OrderExecutor>()?
         .register::<components::ParamValidator>()?
+        .register::<components::PositionManagerCache>()?
+        .register::<components::PositionManagerRegistry>()?
+        .register::<components::PositionManagerRunner>()?
         .register::<components::PositionsCache>()?
         .register::<components::StrategyCache>()?
         .register::<components::StrategyRegistry>()?
         .register::<components::StrategyRunner>()?
         .register::<components::TinkoffClient>()?
+        .register::<components::TradingCalendarCache>()?
+        .register::<components::TradingCalendarSync>()?
         .build(config)
         .await?;
 
@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::models::instruments::Figi;
+use crate::models::orders::OrderDirection;
+use crate::models::strategy::StrategyExecution;
+
+/// Price at which simulated orders are filled
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum FillModel {
+    /// Orders are filled at the open of the candle following the signal;
+    /// stop orders are triggered by candle close
+    NextOpen,
+
+    /// Orders are filled at the close of the signal candle;
+    /// stop orders are triggered by candle close
+    Close,
+
+    /// Orders are filled at the open of the candle following the signal;
+    /// stop orders are triggered as soon as candle high/low touches stop price
+    HighLowTouch,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestSettings {
+    /// Starting cash balance
+    initial_cash: f64,
+
+    /// Commission as a fraction of trade value
+    #[serde(default)]
+    commission: f64,
+
+    /// Adverse price move as a fraction of fill price
+    #[serde(default)]
+    slippage: f64,
+
+    fill_model: FillModel,
+}
+
+#[derive(Error, Debug)]
+pub enum InvalidBacktestSettings {
+    #[error("Initial cash must be positive")]
+    InitialCash,
+    #[error("Commission must be within [0; 1)")]
+    Commission,
+    #[error("Slippage must be within [0; 1)")]
+    Slippage,
+}
+
+impl BacktestSettings {
+    pub fn validate(&self) -> Result<(), InvalidBacktestSettings> {
+        if self.initial_cash.is_nan() || self.initial_cash <= 0.0 {
+            return Err(InvalidBacktestSettings::InitialCash);
+        }
+
+        if !(0.0..1.0).contains(&self.commission) {
+            return Err(InvalidBacktestSettings::Commission);
+        }
+
+        if !(0.0..1.0).contains(&self.slippage) {
+            return Err(InvalidBacktestSettings::Slippage);
+        }
+
+        Ok(())
+    }
+
+    pub fn initial_cash(&self) -> f64 {
+        self.initial_cash
+    }
+
+    pub fn commission(&self) -> f64 {
+        self.commission
+    }
+
+    pub fn slippage(&self) -> f64 {
+        self.slippage
+    }
+
+    pub fn fill_model(&self) -> FillModel {
+        self.fill_model
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum TradeReason {
+    Signal,
+    StopLoss,
+    TakeProfit,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestTrade {
+    pub ts: DateTime<Utc>,
+    pub figi: Figi,
+    pub direction: OrderDirection,
+    pub lots: i64,
+
+    /// Quantity in instrument units
+    pub quantity: i64,
+
+    /// Fill price per instrument unit, slippage included
+    pub price: f64,
+    pub commission: f64,
+    pub reason: TradeReason,
+}
+
+/// Dividend or coupon received for a position held before the ex-date
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestIncome {
+    pub ex_date: DateTime<Utc>,
+
+    /// Income is counted in equity since the ex-date and in cash since this date
+    pub payment_date: DateTime<Utc>,
+    pub figi: Figi,
+
+    /// Quantity held in instrument units, negative for short positions
+    pub quantity: i64,
+
+    /// Total amount, paid out for short positions
+    pub amount: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EquityPoint {
+    pub ts: DateTime<Utc>,
+    pub cash: f64,
+
+    /// Cash plus positions valued at candle close
+    pub equity: f64,
+}
+
+/// Strategy states a stored backtest result was computed from
+#[derive(Debug, Clone, Copy)]
+pub struct BacktestProgress {
+    /// Generation of the instance execution, see `StrategyExecution::generation`
+    pub generation: u32,
+    pub signals_up_to: DateTime<Utc>,
+}
+
+impl BacktestProgress {
+    /// Whether no states were computed since, states of a reset execution are never covered
+    pub fn is_current(&self, execution: &StrategyExecution, last_signal_ts: DateTime<Utc>) -> bool {
+        self.generation == execution.generation() && self.signals_up_to >= last_signal_ts
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktestResult {
+    /// Generation of the instance execution the states were taken from
+    #[serde(default)]
+    pub generation: u32,
+
+    /// Timestamp of the last strategy state taken into account
+    pub signals_up_to: DateTime<Utc>,
+    pub trades: Vec<BacktestTrade>,
+    pub equity_curve: Vec<EquityPoint>,
+
+    /// Final positions in lots
+    pub positions: BTreeMap<Figi, i64>,
+    pub cash: f64,
+
+    #[serde(default)]
+    pub income: Vec<BacktestIncome>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::models::strategy::StrategyExecutionStatus;
+
+    #[test]
+    fn test_reset_invalidates_backtest() {
+        let time_from = Utc.with_ymd_and_hms(2022, 1, 3, 10, 0, 0).unwrap();
+        let mut execution = StrategyExecution::new(StrategyExecutionStatus::Running, time_from);
+
+        let progress = BacktestProgress {
+            generation: execution.generation(),
+            signals_up_to: Utc.with_ymd_and_hms(2022, 1, 3, 12, 0, 0).unwrap(),
+        };
+        assert!(progress.is_current(
+            &execution,
+            Utc.with_ymd_and_hms(2022, 1, 3, 11, 0, 0).unwrap()
+        ));
+        assert!(!progress.is_current(
+            &execution,
+            Utc.with_ymd_and_hms(2022, 1, 3, 13, 0, 0).unwrap()
+        ));
+
+        // Results of the previous generation are stale whatever states are computed since
+        execution.reset(time_from);
+        assert!(!progress.is_current(
+            &execution,
+            Utc.with_ymd_and_hms(2022, 1, 3, 11, 0, 0).unwrap()
+        ));
+    }
+}
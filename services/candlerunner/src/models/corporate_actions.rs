@@ -0,0 +1,254 @@
+use std::cmp::Reverse;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::models::instruments::Figi;
+use crate::models::market_data::CandleTimeline;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum CorporateActionKind {
+    /// Cash paid per instrument unit
+    #[serde(rename_all = "camelCase")]
+    Dividend {
+        amount: f64,
+
+        /// Close before the ex-date the dividend is related to.
+        /// Close of the last candle before the ex-date is used when missing
+        close_price: Option<f64>,
+    },
+
+    /// Cash paid per bond. Bond prices are quoted without accrued interest
+    /// and are never adjusted for coupons
+    Coupon { amount: f64 },
+
+    /// Each instrument unit turns into `ratio` units, e.g. 10 for a 1:10 split
+    Split { ratio: f64 },
+}
+
+impl CorporateActionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CorporateActionKind::Dividend { .. } => "dividend",
+            CorporateActionKind::Coupon { .. } => "coupon",
+            CorporateActionKind::Split { .. } => "split",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CorporateAction {
+    pub figi: Figi,
+
+    /// Start of the first day the instrument is traded without the right
+    pub ex_date: DateTime<Utc>,
+
+    /// Cash is received on the ex-date when missing
+    pub payment_date: Option<DateTime<Utc>>,
+
+    #[serde(flatten)]
+    pub kind: CorporateActionKind,
+}
+
+#[derive(Error, Debug)]
+pub enum InvalidCorporateAction {
+    #[error("Split ratio must be positive")]
+    SplitRatio,
+    #[error("Amount must be positive")]
+    Amount,
+    #[error("Payment date must not precede ex-date")]
+    PaymentDate,
+}
+
+impl CorporateAction {
+    pub fn validate(&self) -> Result<(), InvalidCorporateAction> {
+        match self.kind {
+            CorporateActionKind::Split { ratio } if ratio.is_nan() || ratio <= 0.0 => {
+                return Err(InvalidCorporateAction::SplitRatio);
+            }
+            CorporateActionKind::Dividend { amount, .. }
+            | CorporateActionKind::Coupon { amount }
+                if amount.is_nan() || amount <= 0.0 =>
+            {
+                return Err(InvalidCorporateAction::Amount);
+            }
+            _ => (),
+        }
+
+        if self
+            .payment_date
+            .is_some_and(|payment_date| payment_date < self.ex_date)
+        {
+            return Err(InvalidCorporateAction::PaymentDate);
+        }
+
+        Ok(())
+    }
+}
+
+/// Corporate actions historical prices are adjusted for
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceAdjustment {
+    #[serde(default)]
+    pub splits: bool,
+
+    #[serde(default)]
+    pub dividends: bool,
+}
+
+impl PriceAdjustment {
+    pub fn is_none(&self) -> bool {
+        !self.splits && !self.dividends
+    }
+
+    pub fn applies_to(&self, kind: &CorporateActionKind) -> bool {
+        match kind {
+            CorporateActionKind::Dividend { .. } => self.dividends,
+            CorporateActionKind::Coupon { .. } => false,
+            CorporateActionKind::Split { .. } => self.splits,
+        }
+    }
+}
+
+/// Adjusts candles backwards: prices before each action's ex-date are scaled
+/// so that they are comparable with the latest ones, which stay as traded
+pub fn adjust_candles(
+    candles: &mut CandleTimeline,
+    actions: &[CorporateAction],
+    adjustment: PriceAdjustment,
+) {
+    let mut actions: Vec<_> = actions
+        .iter()
+        .filter(|action| adjustment.applies_to(&action.kind))
+        .collect();
+    actions.sort_by_key(|action| Reverse(action.ex_date));
+
+    let mut actions = actions.into_iter().peekable();
+    let mut price_factor = 1.0;
+    let mut volume_factor = 1.0;
+
+    for (ts, candle) in candles.iter_mut().rev() {
+        while let Some(action) = actions.next_if(|action| action.ex_date > *ts) {
+            match action.kind {
+                CorporateActionKind::Split { ratio } if ratio > 0.0 => {
+                    price_factor /= ratio;
+                    volume_factor *= ratio;
+                }
+                CorporateActionKind::Dividend {
+                    amount,
+                    close_price,
+                } => {
+                    let close = close_price.unwrap_or(candle.close);
+
+                    if amount > 0.0 && close > amount {
+                        price_factor *= (close - amount) / close;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        candle.open *= price_factor;
+        candle.high *= price_factor;
+        candle.low *= price_factor;
+        candle.close *= price_factor;
+        candle.volume = (candle.volume as f64 * volume_factor).round() as u64;
+    }
+}
+
+/// Number of units one unit held at `ts` has turned into by now
+pub fn split_ratio_after(actions: &[CorporateAction], figi: &Figi, ts: DateTime<Utc>) -> f64 {
+    actions
+        .iter()
+        .filter(|action| &action.figi == figi && action.ex_date > ts)
+        .filter_map(|action| match action.kind {
+            CorporateActionKind::Split { ratio } if ratio > 0.0 => Some(ratio),
+            _ => None,
+        })
+        .product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::models::market_data::Candle;
+
+    fn candle(price: f64) -> Candle {
+        Candle {
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 10,
+        }
+    }
+
+    #[test]
+    fn test_adjust_candles() {
+        let figi = Figi("FIGI".to_owned());
+        let day = |d| Utc.with_ymd_and_hms(2022, 1, d, 10, 0, 0).unwrap();
+
+        let mut candles: CandleTimeline = [
+            (day(3), candle(200.0)),
+            (day(4), candle(210.0)),
+            (day(5), candle(100.0)),
+            (day(6), candle(95.0)),
+        ]
+        .into_iter()
+        .collect();
+
+        let actions = [
+            CorporateAction {
+                figi: figi.clone(),
+                ex_date: Utc.with_ymd_and_hms(2022, 1, 5, 0, 0, 0).unwrap(),
+                payment_date: None,
+                kind: CorporateActionKind::Split { ratio: 2.0 },
+            },
+            CorporateAction {
+                figi: figi.clone(),
+                ex_date: Utc.with_ymd_and_hms(2022, 1, 6, 0, 0, 0).unwrap(),
+                payment_date: None,
+                kind: CorporateActionKind::Dividend {
+                    amount: 5.0,
+                    close_price: None,
+                },
+            },
+        ];
+
+        let mut split_adjusted = candles.clone();
+        adjust_candles(
+            &mut split_adjusted,
+            &actions,
+            PriceAdjustment {
+                splits: true,
+                dividends: false,
+            },
+        );
+
+        let closes: Vec<_> = split_adjusted.values().map(|candle| candle.close).collect();
+        assert_eq!(closes, [100.0, 105.0, 100.0, 95.0]);
+        assert_eq!(split_adjusted[&day(3)].volume, 20);
+        assert_eq!(split_adjusted[&day(6)].volume, 10);
+
+        adjust_candles(
+            &mut candles,
+            &actions,
+            PriceAdjustment {
+                splits: true,
+                dividends: true,
+            },
+        );
+
+        // Dividend of 5 on the close of 100 scales earlier prices by 0.95
+        let closes: Vec<_> = candles.values().map(|candle| candle.close).collect();
+        assert_eq!(closes, [95.0, 99.75, 95.0, 95.0]);
+
+        assert_eq!(split_ratio_after(&actions, &figi, day(4)), 2.0);
+        assert_eq!(split_ratio_after(&actions, &figi, day(5)), 1.0);
+    }
+}
@@ -0,0 +1,71 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::instruments::Figi;
+use crate::models::market_data::CandleResolution;
+
+/// Problem found in stored candles
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CandleAnomaly {
+    /// Candles of a trading session are missing in [`from`; `to`)
+    #[serde(rename_all = "camelCase")]
+    Gap {
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        missing_candles: usize,
+    },
+
+    /// Consecutive candles [`from`; `to`] have no volume
+    #[serde(rename_all = "camelCase")]
+    ZeroVolumeRun {
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        candles: usize,
+    },
+
+    /// Prices of the candle are inconsistent, e.g. high is below low
+    InvalidPrices { ts: DateTime<Utc> },
+
+    /// Candle is stored more than once
+    DuplicateTimestamp { ts: DateTime<Utc>, copies: usize },
+}
+
+impl CandleAnomaly {
+    /// Whether fetching the day again is expected to fix the anomaly.
+    /// Zero volume runs are legit for illiquid instruments.
+    /// Duplicates are read as a single candle, so they are only reported.
+    pub fn requires_refetch(&self) -> bool {
+        !matches!(
+            self,
+            CandleAnomaly::ZeroVolumeRun { .. } | CandleAnomaly::DuplicateTimestamp { .. }
+        )
+    }
+}
+
+/// Anomalies found in stored candles of an instrument on a day
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DayVerification {
+    pub figi: Figi,
+    pub resolution: CandleResolution,
+    pub date: NaiveDate,
+
+    /// Number of stored candles, duplicates included
+    pub candles: usize,
+    pub anomalies: Vec<CandleAnomaly>,
+
+    /// Day was marked unavailable to be fetched again
+    pub requeued: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DataQualityReport {
+    /// Time of the last verification, none until the first one finishes
+    pub verified_at: Option<DateTime<Utc>>,
+    pub verified_days: usize,
+
+    /// Days with anomalies only
+    pub days: Vec<DayVerification>,
+}
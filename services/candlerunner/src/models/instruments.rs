@@ -1,14 +1,131 @@
+use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Figi(pub String);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Ticker(pub String);
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum InstrumentType {
+    #[default]
+    Share,
+    Bond,
+    Etf,
+    Future,
+    Currency,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TradingStatus {
+    #[default]
+    Unspecified,
+    NotAvailableForTrading,
+    OpeningPeriod,
+    ClosingPeriod,
+    BreakInTrading,
+    NormalTrading,
+    ClosingAuction,
+    DarkPoolAuction,
+    DiscreteAuction,
+    OpeningAuctionPeriod,
+    TradingAtClosingAuctionPrice,
+    SessionAssigned,
+    SessionClose,
+    SessionOpen,
+    DealerNormalTrading,
+    DealerBreakInTrading,
+    DealerNotAvailableForTrading,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BondDetails {
+    /// Face value in instrument currency
+    pub nominal: f64,
+
+    /// Accrued coupon interest at the time of sync
+    pub accrued_interest: f64,
+    pub coupon_quantity_per_year: i32,
+    pub maturity_date: Option<DateTime<Utc>>,
+    pub floating_coupon: bool,
+    pub perpetual: bool,
+    pub amortization: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FutureDetails {
+    /// `physical_delivery` or `cash_settlement`
+    pub futures_type: String,
+
+    /// `commodity`, `currency`, `security` or `index`
+    pub asset_type: String,
+    pub basic_asset: String,
+
+    /// Amount of the basic asset in one contract
+    pub basic_asset_size: f64,
+    pub first_trade_date: Option<DateTime<Utc>>,
+    pub last_trade_date: Option<DateTime<Utc>>,
+    pub expiration_date: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Instrument {
     pub figi: Figi,
     pub ticker: Ticker,
     pub display_name: String,
+
+    #[serde(default)]
+    pub instrument_type: InstrumentType,
+
+    #[serde(default)]
+    pub isin: String,
+
+    /// Trading section, e.g. `TQBR`
+    #[serde(default)]
+    pub class_code: String,
+
+    /// Exchange the instrument is traded on, trading calendar is looked up by it
+    #[serde(default)]
+    pub exchange: String,
+
+    /// Settlement currency, lowercase ISO code
+    #[serde(default)]
+    pub currency: String,
+
+    /// Number of instrument units in one lot
+    #[serde(default)]
+    pub lot: i64,
+
+    /// Minimal price step, i.e. tick size
+    #[serde(default)]
+    pub min_price_increment: f64,
+
+    /// Trading status as of the last instrument sync
+    #[serde(default)]
+    pub trading_status: TradingStatus,
+
+    #[serde(default)]
+    pub api_trade_available: bool,
+
+    #[serde(default)]
+    pub buy_available: bool,
+
+    #[serde(default)]
+    pub sell_available: bool,
+
+    #[serde(default)]
+    pub short_enabled: bool,
+
+    /// Present for bonds only
+    #[serde(default)]
+    pub bond: Option<BondDetails>,
+
+    /// Present for futures only
+    #[serde(default)]
+    pub future: Option<FutureDetails>,
 }
@@ -2,6 +2,7 @@ use std::collections::{BTreeMap, HashMap};
 
 use chrono::{prelude::*, Duration};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::models::instruments::Figi;
 
@@ -20,36 +21,173 @@ pub struct Candle {
 pub type CandleTimeline = BTreeMap<DateTime<Utc>, Candle>;
 pub type CandlePack = HashMap<Figi, Candle>;
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum CandleResolution {
     OneMinute,
+    TwoMinutes,
+    FiveMinutes,
+    TenMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
     OneHour,
+    FourHours,
     OneDay,
+
+    /// Weeks start on Monday
+    OneWeek,
+    OneMonth,
 }
 
-impl ToString for CandleResolution {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for CandleResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            CandleResolution::OneMinute => "oneMinute".to_string(),
-            CandleResolution::OneHour => "oneHour".to_string(),
-            CandleResolution::OneDay => "oneDay".to_string(),
+            CandleResolution::OneMinute => write!(f, "oneMinute"),
+            CandleResolution::TwoMinutes => write!(f, "twoMinutes"),
+            CandleResolution::FiveMinutes => write!(f, "fiveMinutes"),
+            CandleResolution::TenMinutes => write!(f, "tenMinutes"),
+            CandleResolution::FifteenMinutes => write!(f, "fifteenMinutes"),
+            CandleResolution::ThirtyMinutes => write!(f, "thirtyMinutes"),
+            CandleResolution::OneHour => write!(f, "oneHour"),
+            CandleResolution::FourHours => write!(f, "fourHours"),
+            CandleResolution::OneDay => write!(f, "oneDay"),
+            CandleResolution::OneWeek => write!(f, "oneWeek"),
+            CandleResolution::OneMonth => write!(f, "oneMonth"),
         }
     }
 }
 
-impl From<CandleResolution> for Duration {
-    fn from(val: CandleResolution) -> Self {
+#[derive(Error, Debug)]
+#[error("Unknown candle resolution `{0}`")]
+pub struct UnknownResolutionError(String);
+
+impl std::str::FromStr for CandleResolution {
+    type Err = UnknownResolutionError;
+
+    /// Parses resolution names used in the API, e.g. `fiveMinutes`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_owned()))
+            .map_err(|_| UnknownResolutionError(s.to_owned()))
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Candle resolution `{0}` has no fixed duration")]
+pub struct VariableDurationError(CandleResolution);
+
+impl TryFrom<CandleResolution> for Duration {
+    type Error = VariableDurationError;
+
+    fn try_from(val: CandleResolution) -> Result<Self, Self::Error> {
         match val {
-            CandleResolution::OneMinute => Duration::minutes(1),
-            CandleResolution::OneHour => Duration::hours(1),
-            CandleResolution::OneDay => Duration::days(1),
+            CandleResolution::OneMinute => Ok(Duration::minutes(1)),
+            CandleResolution::TwoMinutes => Ok(Duration::minutes(2)),
+            CandleResolution::FiveMinutes => Ok(Duration::minutes(5)),
+            CandleResolution::TenMinutes => Ok(Duration::minutes(10)),
+            CandleResolution::FifteenMinutes => Ok(Duration::minutes(15)),
+            CandleResolution::ThirtyMinutes => Ok(Duration::minutes(30)),
+            CandleResolution::OneHour => Ok(Duration::hours(1)),
+            CandleResolution::FourHours => Ok(Duration::hours(4)),
+            CandleResolution::OneDay => Ok(Duration::days(1)),
+            CandleResolution::OneWeek => Ok(Duration::weeks(1)),
+            CandleResolution::OneMonth => Err(VariableDurationError(val)),
+        }
+    }
+}
+
+impl CandleResolution {
+    /// Resolutions candles are fetched from data provider at, finest first
+    pub const STORED: [CandleResolution; 5] = [
+        CandleResolution::OneMinute,
+        CandleResolution::FiveMinutes,
+        CandleResolution::FifteenMinutes,
+        CandleResolution::OneHour,
+        CandleResolution::OneDay,
+    ];
+
+    /// Checks whether candles of this resolution can be assembled from `source` candles,
+    /// i.e. every candle boundary of this resolution is a boundary of `source` candle
+    pub fn is_built_from(&self, source: CandleResolution) -> bool {
+        let source = match Duration::try_from(source) {
+            Ok(duration) => duration.num_minutes(),
+            Err(_) => return *self == source,
+        };
+
+        // Longer candles are aligned to midnight
+        let interval = match Duration::try_from(*self) {
+            Ok(duration) => duration.num_minutes(),
+            Err(_) => Duration::days(1).num_minutes(),
+        };
+
+        interval % source == 0 && Duration::days(1).num_minutes() % source == 0
+    }
+
+    /// Stored resolutions this resolution can be assembled from, coarsest first
+    pub fn sources(&self) -> impl Iterator<Item = CandleResolution> + '_ {
+        Self::STORED
+            .into_iter()
+            .rev()
+            .filter(|source| self.is_built_from(*source))
+    }
+
+    /// Returns start of the candle `ts` belongs to.
+    /// Intraday candles are aligned to midnight UTC.
+    pub fn align(&self, ts: DateTime<Utc>) -> DateTime<Utc> {
+        let midnight = ts.date_naive().and_time(NaiveTime::MIN).and_utc();
+
+        match self {
+            CandleResolution::OneDay => midnight,
+            CandleResolution::OneWeek => {
+                midnight - Duration::days(ts.weekday().num_days_from_monday() as i64)
+            }
+            CandleResolution::OneMonth => Utc
+                .with_ymd_and_hms(ts.year(), ts.month(), 1, 0, 0, 0)
+                .unwrap(),
+            _ => {
+                let interval = Duration::try_from(*self)
+                    .expect("intraday resolution has fixed duration")
+                    .num_minutes();
+                let since_midnight = (ts - midnight).num_minutes();
+
+                midnight + Duration::minutes(since_midnight / interval * interval)
+            }
+        }
+    }
+
+    /// Returns start of the candle `n` candles after the one `ts` belongs to
+    pub fn advance(&self, ts: DateTime<Utc>, n: i32) -> DateTime<Utc> {
+        let start = self.align(ts);
+
+        match Duration::try_from(*self) {
+            Ok(interval) => start + interval * n,
+            Err(_) => {
+                let months = start.year() * 12 + start.month0() as i32 + n;
+                Utc.with_ymd_and_hms(
+                    months.div_euclid(12),
+                    months.rem_euclid(12) as u32 + 1,
+                    1,
+                    0,
+                    0,
+                    0,
+                )
+                .unwrap()
+            }
         }
     }
 }
 
+/// Latest state of a candle received from a market data stream.
+/// Candle stays incomplete until its period ends.
+#[derive(Clone, Debug)]
+pub struct CandleUpdate {
+    pub figi: Figi,
+    pub resolution: CandleResolution,
+    pub ts: DateTime<Utc>,
+    pub candle: Candle,
+}
+
 /// Data availability on a trading day
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum DataAvailability {
     /// Data was not fetched from data provider
@@ -60,4 +198,103 @@ pub enum DataAvailability {
 
     /// Data is partially available [00:00; `available_up_to`)
     PartiallyAvailable { available_up_to: DateTime<Utc> },
+
+    /// Exchange was closed, there is no data for the day
+    NonTrading,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align() {
+        let ts = Utc.with_ymd_and_hms(2022, 3, 17, 13, 47, 12).unwrap();
+
+        assert_eq!(
+            CandleResolution::FifteenMinutes.align(ts),
+            Utc.with_ymd_and_hms(2022, 3, 17, 13, 45, 0).unwrap()
+        );
+        assert_eq!(
+            CandleResolution::FourHours.align(ts),
+            Utc.with_ymd_and_hms(2022, 3, 17, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            CandleResolution::OneWeek.align(ts),
+            Utc.with_ymd_and_hms(2022, 3, 14, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            CandleResolution::OneMonth.align(ts),
+            Utc.with_ymd_and_hms(2022, 3, 1, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let resolutions = [
+            CandleResolution::OneMinute,
+            CandleResolution::TwoMinutes,
+            CandleResolution::FiveMinutes,
+            CandleResolution::TenMinutes,
+            CandleResolution::FifteenMinutes,
+            CandleResolution::ThirtyMinutes,
+            CandleResolution::OneHour,
+            CandleResolution::FourHours,
+            CandleResolution::OneDay,
+            CandleResolution::OneWeek,
+            CandleResolution::OneMonth,
+        ];
+
+        // Instance ids are generated from displayed resolutions, so they match serialized ones
+        for resolution in resolutions {
+            assert_eq!(
+                serde_json::to_value(resolution).unwrap(),
+                serde_json::Value::String(resolution.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_sources() {
+        assert_eq!(
+            CandleResolution::ThirtyMinutes
+                .sources()
+                .collect::<Vec<_>>(),
+            vec![
+                CandleResolution::FifteenMinutes,
+                CandleResolution::FiveMinutes,
+                CandleResolution::OneMinute
+            ]
+        );
+        assert_eq!(
+            CandleResolution::OneMonth.sources().next(),
+            Some(CandleResolution::OneDay)
+        );
+        assert_eq!(
+            CandleResolution::TwoMinutes.sources().collect::<Vec<_>>(),
+            vec![CandleResolution::OneMinute]
+        );
+    }
+
+    #[test]
+    fn test_advance() {
+        let ts = Utc.with_ymd_and_hms(2022, 11, 30, 23, 59, 0).unwrap();
+
+        assert_eq!(
+            CandleResolution::TwoMinutes.advance(ts, 1),
+            Utc.with_ymd_and_hms(2022, 12, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            CandleResolution::OneWeek.advance(ts, 1),
+            Utc.with_ymd_and_hms(2022, 12, 5, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            CandleResolution::OneMonth.advance(ts, 2),
+            Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            CandleResolution::OneMonth.advance(ts, -11),
+            Utc.with_ymd_and_hms(2021, 12, 1, 0, 0, 0).unwrap()
+        );
+    }
 }
@@ -1,9 +1,17 @@
 pub mod account;
+pub mod backtest;
+pub mod corporate_actions;
+pub mod data_quality;
 pub mod indicator;
 pub mod instance_id;
 pub mod instruments;
 pub mod market_data;
+pub mod orders;
 pub mod params;
+pub mod position_manager;
 pub mod positions;
+pub mod report;
 pub mod strategy;
+pub mod strategy_history;
+pub mod trading_calendar;
 pub mod namespaces;
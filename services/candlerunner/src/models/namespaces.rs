@@ -1,26 +1,118 @@
+use std::sync::OnceLock;
+
 use uuid::Uuid;
 
-static mut STRATEGY_INSTANCE_NS: Option<Uuid> = None;
-static mut PLACE_ORDER_SETTINGS_NS: Option<Uuid> = None;
-static mut PARAMS_SET_NS: Option<Uuid> = None;
+static STRATEGY_INSTANCE_NS: OnceLock<Uuid> = OnceLock::new();
+static PLACE_ORDER_SETTINGS_NS: OnceLock<Uuid> = OnceLock::new();
+static PARAMS_SET_NS: OnceLock<Uuid> = OnceLock::new();
+static ORDER_NS: OnceLock<Uuid> = OnceLock::new();
+static POSITION_MANAGER_INSTANCE_NS: OnceLock<Uuid> = OnceLock::new();
+static SMA_NS: OnceLock<Uuid> = OnceLock::new();
+static EMA_NS: OnceLock<Uuid> = OnceLock::new();
+static WMA_NS: OnceLock<Uuid> = OnceLock::new();
+static RSI_NS: OnceLock<Uuid> = OnceLock::new();
+static MACD_NS: OnceLock<Uuid> = OnceLock::new();
+static BOLLINGER_NS: OnceLock<Uuid> = OnceLock::new();
+static ATR_NS: OnceLock<Uuid> = OnceLock::new();
+static STOCHASTIC_NS: OnceLock<Uuid> = OnceLock::new();
+static OBV_NS: OnceLock<Uuid> = OnceLock::new();
+static VWAP_NS: OnceLock<Uuid> = OnceLock::new();
+static DONCHIAN_NS: OnceLock<Uuid> = OnceLock::new();
+static ROC_NS: OnceLock<Uuid> = OnceLock::new();
+static SPREAD_NS: OnceLock<Uuid> = OnceLock::new();
 
 pub fn get_strategy_instance_ns() -> &'static Uuid {
-    unsafe {
-        STRATEGY_INSTANCE_NS
-            .get_or_insert_with(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"strategyInstanceId"))
-    }
+    STRATEGY_INSTANCE_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"strategyInstanceId"))
 }
 
 pub fn get_place_order_settings_ns() -> &'static Uuid {
-    unsafe {
-        PLACE_ORDER_SETTINGS_NS
-            .get_or_insert_with(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"placeOrderSettings"))
-    }
+    PLACE_ORDER_SETTINGS_NS
+        .get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"placeOrderSettings"))
 }
 
 pub fn get_params_set_ns() -> &'static Uuid {
-    unsafe {
-        PARAMS_SET_NS
-            .get_or_insert_with(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"paramsSet"))
+    PARAMS_SET_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"paramsSet"))
+}
+
+pub fn get_order_ns() -> &'static Uuid {
+    ORDER_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"order"))
+}
+
+pub fn get_position_manager_instance_ns() -> &'static Uuid {
+    POSITION_MANAGER_INSTANCE_NS
+        .get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"positionManagerInstanceId"))
+}
+
+pub fn get_sma_ns() -> &'static Uuid {
+    SMA_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"smaIndicator"))
+}
+
+pub fn get_ema_ns() -> &'static Uuid {
+    EMA_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"emaIndicator"))
+}
+
+pub fn get_wma_ns() -> &'static Uuid {
+    WMA_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"wmaIndicator"))
+}
+
+pub fn get_rsi_ns() -> &'static Uuid {
+    RSI_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"rsiIndicator"))
+}
+
+pub fn get_macd_ns() -> &'static Uuid {
+    MACD_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"macdIndicator"))
+}
+
+pub fn get_bollinger_ns() -> &'static Uuid {
+    BOLLINGER_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"bollingerIndicator"))
+}
+
+pub fn get_atr_ns() -> &'static Uuid {
+    ATR_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"atrIndicator"))
+}
+
+pub fn get_stochastic_ns() -> &'static Uuid {
+    STOCHASTIC_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"stochasticIndicator"))
+}
+
+pub fn get_obv_ns() -> &'static Uuid {
+    OBV_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"obvIndicator"))
+}
+
+pub fn get_vwap_ns() -> &'static Uuid {
+    VWAP_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"vwapIndicator"))
+}
+
+pub fn get_donchian_ns() -> &'static Uuid {
+    DONCHIAN_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"donchianIndicator"))
+}
+
+pub fn get_roc_ns() -> &'static Uuid {
+    ROC_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"rocIndicator"))
+}
+
+pub fn get_spread_ns() -> &'static Uuid {
+    SPREAD_NS.get_or_init(|| Uuid::new_v5(&Uuid::NAMESPACE_OID, b"spreadIndicator"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_ids_namespaces() {
+        // Ids of stored instances are derived from these, they must never change
+        assert_eq!(
+            get_strategy_instance_ns().to_string(),
+            "996c0b41-8996-537f-a0ee-93c056634c36"
+        );
+        assert_eq!(
+            get_place_order_settings_ns().to_string(),
+            "fde9ccb6-824f-52f7-bf85-26814389aa19"
+        );
+        assert_eq!(
+            get_params_set_ns().to_string(),
+            "b189acbd-c4a3-5db0-bdfc-9a92b69e5b03"
+        );
     }
 }
@@ -0,0 +1,190 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::account::AccountId;
+use crate::models::instruments::Figi;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderDirection {
+    Buy,
+    Sell,
+}
+
+impl OrderDirection {
+    pub fn opposite(&self) -> Self {
+        match self {
+            OrderDirection::Buy => OrderDirection::Sell,
+            OrderDirection::Sell => OrderDirection::Buy,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderType {
+    Market,
+    Limit { price: f64 },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum StopOrderType {
+    StopLoss,
+    TakeProfit,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderRequest {
+    /// Idempotency key, at most 36 characters long
+    pub order_id: String,
+    pub account_id: AccountId,
+    pub figi: Figi,
+    pub direction: OrderDirection,
+    pub order_type: OrderType,
+
+    /// Quantity in lots
+    pub lots: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct StopOrderRequest {
+    pub account_id: AccountId,
+    pub figi: Figi,
+    pub direction: OrderDirection,
+    pub stop_order_type: StopOrderType,
+    pub stop_price: f64,
+
+    /// Quantity in lots
+    pub lots: i64,
+
+    /// Stop order is cancelled by broker after this moment
+    pub expire_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderStatus {
+    Unspecified,
+    New,
+    Filled,
+    PartiallyFilled,
+    Rejected,
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PostedOrder {
+    pub order_id: String,
+    pub status: OrderStatus,
+    pub lots_requested: i64,
+    pub lots_executed: i64,
+
+    /// Total executed amount as reported by broker
+    pub executed_order_price: Option<f64>,
+}
+
+impl PostedOrder {
+    /// Average fill price per instrument unit, `None` until some lots are executed
+    pub fn average_price(&self, lot: i64) -> Option<f64> {
+        let quantity = self.lots_executed * lot;
+
+        match self.executed_order_price {
+            Some(amount) if quantity > 0 => Some(amount / quantity as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Record of an order placed on behalf of a strategy instance
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRecord {
+    pub ts: DateTime<Utc>,
+    pub signal_ts: DateTime<Utc>,
+    pub account_id: AccountId,
+    pub figi: Figi,
+    pub direction: OrderDirection,
+    pub lots: i64,
+    pub order: PostedOrder,
+}
+
+/// Strategy states order executor has placed orders for
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderExecutionCursor {
+    /// Signals up to this timestamp are executed
+    pub executed_up_to: DateTime<Utc>,
+
+    /// Signals being executed and the last instrument orders are placed for.
+    /// Instruments are processed in FIGI order, so the preceding ones are done as well.
+    pub partially_executed: Option<(DateTime<Utc>, Figi)>,
+}
+
+impl OrderExecutionCursor {
+    pub fn new(executed_up_to: DateTime<Utc>) -> Self {
+        Self {
+            executed_up_to,
+            partially_executed: None,
+        }
+    }
+
+    /// Whether orders for the instrument have been placed on signals at `signal_ts`
+    pub fn is_executed(&self, signal_ts: DateTime<Utc>, figi: &Figi) -> bool {
+        if signal_ts <= self.executed_up_to {
+            return true;
+        }
+
+        matches!(
+            &self.partially_executed,
+            Some((ts, last_figi)) if *ts == signal_ts && figi <= last_figi
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order_execution_cursor() {
+        let executed_up_to = Utc.with_ymd_and_hms(2022, 3, 17, 10, 0, 0).unwrap();
+        let signal_ts = Utc.with_ymd_and_hms(2022, 3, 17, 10, 5, 0).unwrap();
+        let first = Figi("BBG000B9XRY4".to_owned());
+        let second = Figi("BBG000BPH459".to_owned());
+
+        let cursor = OrderExecutionCursor {
+            executed_up_to,
+            partially_executed: Some((signal_ts, first.clone())),
+        };
+
+        assert!(cursor.is_executed(executed_up_to, &second));
+        assert!(cursor.is_executed(signal_ts, &first));
+        assert!(!cursor.is_executed(signal_ts, &second));
+        // Newer signals supersede the partially executed ones
+        assert!(!cursor.is_executed(
+            Utc.with_ymd_and_hms(2022, 3, 17, 10, 10, 0).unwrap(),
+            &first
+        ));
+
+        let cursor = OrderExecutionCursor::new(signal_ts);
+        assert!(cursor.is_executed(signal_ts, &second));
+    }
+
+    #[test]
+    fn test_average_price() {
+        let mut order = PostedOrder {
+            order_id: "order".to_owned(),
+            status: OrderStatus::New,
+            lots_requested: 2,
+            lots_executed: 0,
+            executed_order_price: None,
+        };
+        assert_eq!(order.average_price(10), None);
+
+        order.status = OrderStatus::Filled;
+        order.lots_executed = 2;
+        order.executed_order_price = Some(3000.0);
+        assert_eq!(order.average_price(10), Some(150.0));
+    }
+}
@@ -79,6 +79,10 @@ pub struct ParamDefinition {
     description: String,
     param_type: ParamType,
     default_value: Option<ParamValue>,
+
+    /// Parameter may be omitted even if it has no default value
+    #[serde(default)]
+    optional: bool,
 }
 
 impl ParamDefinition {
@@ -93,13 +97,20 @@ impl ParamDefinition {
             description: description.to_string(),
             param_type,
             default_value,
+            optional: false,
         }
     }
 
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    #[allow(dead_code)]
     pub fn description(&self) -> &str {
         &self.description
     }
@@ -111,6 +122,10 @@ impl ParamDefinition {
     pub fn default_value(&self) -> &Option<ParamValue> {
         &self.default_value
     }
+
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
 }
 
 #[derive(Debug, Error)]
@@ -130,7 +145,7 @@ impl InstanceId for HashMap<String, ParamValue> {
         let mut generator = IdGenerator::default();
 
         let mut sorted_params: Vec<(&String, &ParamValue)> = self.iter().collect();
-        sorted_params.sort_by(|lhs, rhs| (*lhs).0.cmp(rhs.0));
+        sorted_params.sort_by(|lhs, rhs| lhs.0.cmp(rhs.0));
 
         for (param_name, param_value) in sorted_params {
             generator.add("paramName", param_name.as_bytes());
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::account::AccountId;
+use crate::models::instance_id::InstanceId;
+use crate::models::instruments::Figi;
+use crate::models::namespaces;
+use crate::models::params::{ParamDefinition, ParamError, ParamValue};
+
+use crate::utils::id_generator::IdGenerator;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum PositionManagerOptions {
+    /// Target positions are computed for the real account
+    Realtime { account_id: AccountId },
+
+    /// Target positions are computed as portfolio weights only
+    Backtest {},
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PositionManagerInstanceDefinition {
+    position_manager_name: String,
+    strategies: Vec<Uuid>,
+    options: PositionManagerOptions,
+    params: HashMap<String, ParamValue>,
+}
+
+impl InstanceId for PositionManagerInstanceDefinition {
+    fn id(&self) -> Uuid {
+        let mut generator = IdGenerator::default();
+        generator.add("positionManagerName", self.position_manager_name.as_bytes());
+
+        let mut strategies = self.strategies.clone();
+        strategies.sort();
+
+        for strategy_id in strategies {
+            generator.add("strategyId", strategy_id.as_bytes());
+        }
+
+        match &self.options {
+            PositionManagerOptions::Realtime { account_id } => {
+                generator.add("realtime", account_id.0.as_bytes())
+            }
+            PositionManagerOptions::Backtest {} => generator.add("backtest", b""),
+        }
+
+        generator.add("params", self.params.id().as_bytes());
+
+        generator.generate(namespaces::get_position_manager_instance_ns())
+    }
+}
+
+impl PositionManagerInstanceDefinition {
+    pub fn position_manager_name(&self) -> &str {
+        &self.position_manager_name
+    }
+
+    pub fn strategies(&self) -> &[Uuid] {
+        &self.strategies
+    }
+
+    pub fn options(&self) -> &PositionManagerOptions {
+        &self.options
+    }
+
+    pub fn params(&self) -> &HashMap<String, ParamValue> {
+        &self.params
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PositionManagerDefinition {
+    params: Vec<ParamDefinition>,
+    position_manager_name: String,
+    position_manager_description: String,
+}
+
+impl PositionManagerDefinition {
+    pub fn new<N: ToString, D: ToString>(
+        params: Vec<ParamDefinition>,
+        position_manager_name: N,
+        position_manager_description: D,
+    ) -> Self {
+        Self {
+            params,
+            position_manager_name: position_manager_name.to_string(),
+            position_manager_description: position_manager_description.to_string(),
+        }
+    }
+
+    pub fn params(&self) -> &[ParamDefinition] {
+        &self.params
+    }
+}
+
+/// Latest signals of a single strategy instance
+pub type StrategySignals = HashMap<Figi, f64>;
+
+/// Target share of the portfolio value per instrument.
+/// Negative values stand for short positions.
+pub type TargetWeights = HashMap<Figi, f64>;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TargetPosition {
+    pub weight: f64,
+
+    /// Target position in lots; unknown when portfolio is not bound to an account
+    pub lots: Option<i64>,
+}
+
+pub trait PositionManager: Send + Sync + 'static {
+    /// Combines signals of managed strategy instances into target portfolio weights
+    fn target_weights(&self, signals: &HashMap<Uuid, StrategySignals>) -> TargetWeights;
+}
+
+/// Converts portfolio weights into lots given portfolio value, instrument prices and lot sizes.
+/// Instruments without known price or lot size get no position.
+pub fn weights_to_lots(
+    weights: &TargetWeights,
+    portfolio_value: f64,
+    prices: &HashMap<Figi, f64>,
+    lot_sizes: &HashMap<Figi, i64>,
+) -> HashMap<Figi, i64> {
+    weights
+        .iter()
+        .map(|(figi, weight)| {
+            let lot_price = match (prices.get(figi), lot_sizes.get(figi)) {
+                (Some(price), Some(lot)) if *price > 0.0 && *lot > 0 => price * (*lot as f64),
+                _ => return (figi.clone(), 0),
+            };
+
+            let lots = (weight * portfolio_value / lot_price).trunc() as i64;
+            (figi.clone(), lots)
+        })
+        .collect()
+}
+
+#[derive(Error, Debug)]
+pub enum InstantiatePositionManagerError {
+    #[error("Position manager `{0}` is not found")]
+    NotFound(String),
+    #[error("Strategy instance `{0}` is not found")]
+    StrategyNotFound(Uuid),
+    #[error("Position manager requires at least one strategy instance")]
+    NoStrategies,
+    #[error("Params validation failed")]
+    ParamValidationFailed {
+        #[from]
+        source: ParamError,
+    },
+}
+
+pub trait PositionManagerFactory: Sync + Send + 'static {
+    fn position_manager_name(&self) -> &'_ str;
+    fn definition(&self) -> &'_ PositionManagerDefinition;
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn PositionManager>, InstantiatePositionManagerError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weights_to_lots() {
+        let sber = Figi("SBER".to_owned());
+        let gazp = Figi("GAZP".to_owned());
+        let unknown = Figi("UNKNOWN".to_owned());
+
+        let weights: TargetWeights = [
+            (sber.clone(), 0.5),
+            (gazp.clone(), -0.25),
+            (unknown.clone(), 0.25),
+        ]
+        .into_iter()
+        .collect();
+
+        let prices = [(sber.clone(), 250.0), (gazp.clone(), 300.0)]
+            .into_iter()
+            .collect();
+        let lot_sizes = [(sber.clone(), 10), (gazp.clone(), 10)]
+            .into_iter()
+            .collect();
+
+        let lots = weights_to_lots(&weights, 100_000.0, &prices, &lot_sizes);
+
+        assert_eq!(lots.get(&sber), Some(&20));
+        assert_eq!(lots.get(&gazp), Some(&-8));
+        assert_eq!(lots.get(&unknown), Some(&0));
+    }
+}
@@ -0,0 +1,63 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Data a report is computed from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportSource {
+    /// Backtest of the instance signals
+    Backtest,
+
+    /// Orders executed on behalf of the instance
+    Orders,
+}
+
+/// Performance metrics of a strategy instance.
+/// Ratios are fractions, i.e. 0.1 stands for 10%.
+/// Metrics which can't be computed on the available data are omitted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceReport {
+    pub source: ReportSource,
+
+    pub time_from: DateTime<Utc>,
+    pub time_to: DateTime<Utc>,
+
+    pub initial_equity: f64,
+    pub final_equity: f64,
+
+    pub total_return: f64,
+    pub annualised_return: Option<f64>,
+
+    /// Annualised standard deviation of per-candle returns
+    pub volatility: Option<f64>,
+
+    /// Annualised, risk free rate is assumed to be zero
+    pub sharpe: Option<f64>,
+
+    /// Annualised, target return is assumed to be zero
+    pub sortino: Option<f64>,
+
+    /// Largest peak-to-trough equity decline
+    pub max_drawdown: f64,
+
+    /// Days from the peak preceding max drawdown to equity recovery
+    /// (or to the end of the period when equity hasn't recovered)
+    pub max_drawdown_duration_days: f64,
+
+    pub trades: usize,
+
+    /// Trades reducing or closing a position
+    pub closed_trades: usize,
+
+    /// Share of closed trades with positive profit, commissions included
+    pub win_rate: Option<f64>,
+
+    /// Gross profit over gross loss of closed trades
+    pub profit_factor: Option<f64>,
+
+    /// Share of time with open positions
+    pub exposure: f64,
+
+    /// Total traded value over average equity
+    pub turnover: f64,
+}
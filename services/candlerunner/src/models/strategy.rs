@@ -7,11 +7,13 @@ use thiserror::Error;
 use uuid::Uuid;
 
 use crate::models::account::AccountId;
+use crate::models::corporate_actions::PriceAdjustment;
 use crate::models::indicator::{ExtractIndicatorValue, Indicator};
 use crate::models::instance_id::InstanceId;
 use crate::models::instruments::Figi;
-use crate::models::market_data::{CandlePack, CandleResolution};
+use crate::models::market_data::{Candle, CandlePack, CandleResolution};
 use crate::models::namespaces;
+use crate::models::orders::OrderDirection;
 use crate::models::params::{ParamDefinition, ParamError, ParamValue};
 
 use crate::utils::id_generator::IdGenerator;
@@ -55,6 +57,42 @@ impl InstanceId for PlaceOrderSettings {
     }
 }
 
+impl PlaceOrderSettings {
+    pub fn account_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    pub fn stop_loss_offset(&self) -> u32 {
+        self.stop_loss_offset
+    }
+
+    pub fn take_profit_offset(&self) -> u32 {
+        self.take_profit_offset
+    }
+
+    pub fn interval_length(&self) -> u32 {
+        self.interval_length
+    }
+
+    /// Decides which order should be placed for the signal given current position.
+    /// Opens a single lot position on buy signal and closes the whole position on sell signal.
+    pub fn decide_order(&self, signal: f64, position_lots: i64) -> Option<(OrderDirection, i64)> {
+        if let Some(buy_threshold) = self.buy_threshold {
+            if signal >= buy_threshold && position_lots == 0 {
+                return Some((OrderDirection::Buy, 1));
+            }
+        }
+
+        if let Some(sell_threshold) = self.sell_threshold {
+            if signal <= sell_threshold && position_lots > 0 {
+                return Some((OrderDirection::Sell, position_lots));
+            }
+        }
+
+        None
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct StrategyInstanceDefinition {
@@ -64,6 +102,10 @@ pub struct StrategyInstanceDefinition {
     time_to: Option<DateTime<Utc>>,
     resolution: CandleResolution,
     place_order_settings: Option<PlaceOrderSettings>,
+
+    /// Corporate actions candles the strategy sees are adjusted for
+    #[serde(default)]
+    price_adjustment: PriceAdjustment,
 }
 
 impl InstanceId for StrategyInstanceDefinition {
@@ -71,7 +113,7 @@ impl InstanceId for StrategyInstanceDefinition {
         let mut generator = IdGenerator::default();
         generator.add("strategyName", self.strategy_name.as_bytes());
         generator.add("params", self.params().id().as_bytes());
-        generator.add("timeFrom", &self.time_from.timestamp().to_le_bytes());
+        generator.add("timeFrom", self.time_from.timestamp().to_le_bytes());
         generator.add_opt(
             "timeTo",
             self.time_to.map(|val| val.timestamp().to_le_bytes()),
@@ -86,11 +128,23 @@ impl InstanceId for StrategyInstanceDefinition {
                 .map(|val| val.id().as_bytes().to_owned()),
         );
 
+        // Added only when requested, so that ids of instances defined on raw prices are kept
+        if !self.price_adjustment.is_none() {
+            generator.add(
+                "priceAdjustment",
+                [
+                    self.price_adjustment.splits as u8,
+                    self.price_adjustment.dividends as u8,
+                ],
+            );
+        }
+
         generator.generate(namespaces::get_strategy_instance_ns())
     }
 }
 
 impl StrategyInstanceDefinition {
+    #[allow(dead_code)]
     pub fn new<N: ToString>(
         strategy_name: N,
         params: HashMap<String, ParamValue>,
@@ -98,6 +152,7 @@ impl StrategyInstanceDefinition {
         time_to: Option<DateTime<Utc>>,
         resolution: CandleResolution,
         place_order_settings: Option<PlaceOrderSettings>,
+        price_adjustment: PriceAdjustment,
     ) -> Self {
         Self {
             strategy_name: strategy_name.to_string(),
@@ -106,6 +161,7 @@ impl StrategyInstanceDefinition {
             time_to,
             resolution,
             place_order_settings,
+            price_adjustment,
         }
     }
 
@@ -132,6 +188,10 @@ impl StrategyInstanceDefinition {
     pub fn place_order_settings(&self) -> &Option<PlaceOrderSettings> {
         &self.place_order_settings
     }
+
+    pub fn price_adjustment(&self) -> PriceAdjustment {
+        self.price_adjustment
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -158,10 +218,12 @@ impl StrategyDefinition {
         &self.params
     }
 
+    #[allow(dead_code)]
     pub fn strategy_name(&self) -> &str {
         &self.strategy_name
     }
 
+    #[allow(dead_code)]
     pub fn strategy_description(&self) -> &str {
         &self.strategy_description
     }
@@ -169,25 +231,100 @@ impl StrategyDefinition {
 
 #[derive(Error, Debug)]
 pub enum StrategyExecutionError {
-    #[error("Failed to execute strategy; retry later")]
-    ExecutionFailure,
+    #[error("Failed to execute strategy: {0}")]
+    CriticalFailure(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone)]
+pub enum FailureKind {
+    /// `StrategyExecutionError::CriticalFailure`
+    Critical,
+    Panic,
+    Timeout,
+}
+
+/// Failure the execution stopped at
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionFailure {
+    pub kind: FailureKind,
+    pub message: String,
+    pub ts: DateTime<Utc>,
 
-    #[error("Failed to execute strategy")]
-    CriticalFailure,
+    /// Candle the strategy failed on, if it got to one
+    pub candle_ts: Option<DateTime<Utc>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Copy, Clone)]
 pub enum StrategyExecutionStatus {
     Running,
+
+    /// Not executed until resumed, progress is kept
+    Paused,
+
+    /// Stopped by request, only a reset brings the instance back
+    Stopped,
+
+    /// Executed up to `time_to`
     Finished,
     Failed,
 }
 
-#[derive(Serialize, Deserialize)]
+impl StrategyExecutionStatus {
+    fn can_change_to(self, status: StrategyExecutionStatus) -> bool {
+        use StrategyExecutionStatus::*;
+
+        matches!(
+            (self, status),
+            (Running, Paused | Stopped | Finished | Failed)
+                | (Paused, Running | Stopped)
+                | (Failed, Running | Stopped)
+        )
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Strategy instance status can not be changed from {from:?} to {to:?}")]
+pub struct InvalidStatusTransition {
+    pub from: StrategyExecutionStatus,
+    pub to: StrategyExecutionStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusTransition {
+    pub ts: DateTime<Utc>,
+    pub status: StrategyExecutionStatus,
+
+    /// Why the status was set, e.g. an execution error
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct StrategyExecution {
     status: StrategyExecutionStatus,
     last_execution_timestamp: DateTime<Utc>,
+
+    /// Status changes, oldest first. Executions stored before it was tracked have none
+    #[serde(default)]
+    history: Vec<StatusTransition>,
+
+    /// The latest failure, cleared once execution makes progress or is resumed
+    #[serde(default)]
+    failure: Option<ExecutionFailure>,
+
+    /// Incremented on every reset, so that results computed from earlier states are told apart
+    #[serde(default)]
+    generation: u32,
+}
+
+/// Version of a stored execution. Status changes extend the history
+/// and resets bump the generation, so a changed execution has another revision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionRevision {
+    pub history_len: usize,
+    pub generation: u32,
 }
 
 impl StrategyExecution {
@@ -195,6 +332,13 @@ impl StrategyExecution {
         Self {
             status,
             last_execution_timestamp: last_execution_time,
+            history: vec![StatusTransition {
+                ts: Utc::now(),
+                status,
+                reason: None,
+            }],
+            failure: None,
+            generation: 0,
         }
     }
 
@@ -206,8 +350,74 @@ impl StrategyExecution {
         self.last_execution_timestamp
     }
 
-    pub fn set_status(&mut self, status: StrategyExecutionStatus) {
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn revision(&self) -> ExecutionRevision {
+        ExecutionRevision {
+            history_len: self.history.len(),
+            generation: self.generation,
+        }
+    }
+
+    /// Fails the instance recording the failure until it is resumed
+    pub fn fail(
+        &mut self,
+        kind: FailureKind,
+        message: String,
+        candle_ts: Option<DateTime<Utc>>,
+        now: DateTime<Utc>,
+    ) -> Result<(), InvalidStatusTransition> {
+        self.transition(StrategyExecutionStatus::Failed, Some(message.clone()))?;
+
+        self.failure = Some(ExecutionFailure {
+            kind,
+            message,
+            ts: now,
+            candle_ts,
+        });
+
+        Ok(())
+    }
+
+    pub fn transition(
+        &mut self,
+        status: StrategyExecutionStatus,
+        reason: Option<String>,
+    ) -> Result<(), InvalidStatusTransition> {
+        if !self.status.can_change_to(status) {
+            return Err(InvalidStatusTransition {
+                from: self.status,
+                to: status,
+            });
+        }
+
+        if status == StrategyExecutionStatus::Running {
+            self.failure = None;
+        }
+
         self.status = status;
+        self.history.push(StatusTransition {
+            ts: Utc::now(),
+            status,
+            reason,
+        });
+
+        Ok(())
+    }
+
+    /// Restarts execution from `time_from` in any status, history is kept
+    pub fn reset(&mut self, time_from: DateTime<Utc>) {
+        self.status = StrategyExecutionStatus::Running;
+        self.last_execution_timestamp = time_from;
+        self.failure = None;
+        self.generation += 1;
+        self.history.push(StatusTransition {
+            ts: Utc::now(),
+            status: StrategyExecutionStatus::Running,
+            reason: Some("Reset".to_owned()),
+        });
     }
 
     pub fn set_last_execution_timestamp(&mut self, last_execution_timestamp: DateTime<Utc>) {
@@ -223,22 +433,54 @@ pub struct StrategyState {
 }
 
 impl StrategyState {
+    pub fn signals(&self) -> &HashMap<Figi, f64> {
+        &self.signals
+    }
+
     pub fn set_signal(&mut self, instrument: Figi, value: f64) {
         self.signals.insert(instrument, value);
     }
 
+    /// Returns last values of all indicators as stored, without knowing their types
+    pub fn indicator_values(&self) -> HashMap<uuid::Uuid, bson::Bson> {
+        self.indicators
+            .iter()
+            .filter_map(|(id, serialized)| {
+                let value = serialized.as_document()?.get("value")?;
+
+                match value {
+                    bson::Bson::Null => None,
+                    value => Some((*id, value.clone())),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the last value of the indicator without updating it
+    pub fn indicator_value<I: Indicator>(&self, indicator: &I) -> Option<I::ValueType> {
+        self.indicators
+            .get(&indicator.id())
+            .and_then(|serialized| bson::from_bson::<I::State>(serialized.to_owned()).ok())
+            .and_then(|state| state.extract_value())
+    }
+
+    /// Feeds the input to the indicator and returns its new value.
+    /// A stored state the indicator can not read fails the execution,
+    /// as starting the indicator over would change the signals silently.
     pub fn update_indicator<I: Indicator>(
         &mut self,
         indicator: &I,
         input: &I::Input,
-    ) -> Option<I::ValueType> {
+    ) -> Result<Option<I::ValueType>, StrategyExecutionError> {
         let id = indicator.id();
         let state = match self.indicators.get(&id) {
             Some(serialized) => {
-                bson::from_bson::<I::State>(serialized.to_owned()).unwrap_or_else(|err| {
-                    println!("Failed to deserialize indicator state: {}", err);
-                    I::State::default()
-                })
+                bson::from_bson::<I::State>(serialized.to_owned()).map_err(|err| {
+                    StrategyExecutionError::CriticalFailure(format!(
+                        "Failed to deserialize state of indicator {}: {}",
+                        id, err
+                    ))
+                })?
             }
             None => I::State::default(),
         };
@@ -246,27 +488,85 @@ impl StrategyState {
         let next_state = indicator.update(state, input);
         let value = next_state.extract_value();
 
-        match bson::to_bson(&next_state) {
-            Ok(serialized) => {
-                self.indicators.insert(id, serialized);
-            }
-            Err(err) => println!("Failed to serialize indicator state: {}", err),
+        let serialized = bson::to_bson(&next_state).map_err(|err| {
+            StrategyExecutionError::CriticalFailure(format!(
+                "Failed to serialize state of indicator {}: {}",
+                id, err
+            ))
+        })?;
+        self.indicators.insert(id, serialized);
+
+        Ok(value)
+    }
+}
+
+/// Data a strategy is executed on at a step
+#[derive(Debug, Clone)]
+pub struct ExecutionContext {
+    /// Start of the candle being stepped on, at the resolution of the instance
+    pub ts: DateTime<Utc>,
+
+    /// Candles of `Strategy::data_requirements` starting at `ts`
+    pub candles: CandlePack,
+
+    /// The latest candles of `Strategy::series_requirements` closed by the end of the stepped
+    /// candle, along with their starts. Candles still being formed are never passed,
+    /// so that a step does not see prices from its future.
+    pub series: HashMap<(Figi, CandleResolution), (DateTime<Utc>, Candle)>,
+}
+
+impl ExecutionContext {
+    pub fn new(ts: DateTime<Utc>, candles: CandlePack) -> Self {
+        Self {
+            ts,
+            candles,
+            series: Default::default(),
         }
+    }
+
+    pub fn candle(&self, figi: &Figi) -> Option<&Candle> {
+        self.candles.get(figi)
+    }
 
-        value
+    pub fn series_candle(&self, figi: &Figi, resolution: CandleResolution) -> Option<&Candle> {
+        self.series
+            .get(&(figi.clone(), resolution))
+            .map(|(_, candle)| candle)
     }
 }
 
 pub trait Strategy: Send + Sync + 'static {
+    /// Instruments stepped on at the resolution of the instance
     fn data_requirements(&self) -> &[Figi];
+
+    /// Coarser series read along, e.g. daily candles for a trend filter of hourly entries
+    fn series_requirements(&self) -> &[(Figi, CandleResolution)] {
+        &[]
+    }
+
     fn execute(
         &self,
-        ts: DateTime<Utc>,
-        candles_pack: CandlePack,
+        context: &ExecutionContext,
         state: StrategyState,
     ) -> Result<StrategyState, StrategyExecutionError>;
 }
 
+/// Number of candles of coarser series read before an instance range,
+/// so that its first steps see a closed candle despite non-trading days
+pub const SERIES_LOOKBACK_CANDLES: i32 = 7;
+
+/// Series the instance reads: its instruments at its resolution and the coarser series
+pub fn required_series(
+    strategy: &dyn Strategy,
+    resolution: CandleResolution,
+) -> impl Iterator<Item = (Figi, CandleResolution)> + '_ {
+    strategy
+        .data_requirements()
+        .iter()
+        .map(move |figi| (figi.clone(), resolution))
+        .chain(strategy.series_requirements().iter().cloned())
+}
+
 #[derive(Error, Debug)]
 pub enum InstantiateStrategyError {
     #[error("Strategy `{0}` is not found")]
@@ -278,6 +578,8 @@ pub enum InstantiateStrategyError {
         #[from]
         source: ParamError,
     },
+    #[error("Series of {0} can not be read along candles of {1}")]
+    UnsupportedResolution(CandleResolution, CandleResolution),
 }
 
 pub trait StrategyFactory: Sync + Send + 'static {
@@ -288,3 +590,193 @@ pub trait StrategyFactory: Sync + Send + 'static {
         params: &HashMap<String, ParamValue>,
     ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(buy_threshold: Option<f64>, sell_threshold: Option<f64>) -> PlaceOrderSettings {
+        serde_json::from_value(serde_json::json!({
+            "accountId": "account",
+            "buyThreshold": buy_threshold,
+            "sellThreshold": sell_threshold,
+            "stopLossOffset": 10,
+            "takeProfitOffset": 20,
+            "intervalLength": 5
+        }))
+        .unwrap()
+    }
+
+    fn instance_definition(
+        price_adjustment: Option<PriceAdjustment>,
+    ) -> StrategyInstanceDefinition {
+        let mut def = serde_json::json!({
+            "strategyName": "bollinger",
+            "params": {},
+            "timeFrom": "2022-01-03T00:00:00Z",
+            "timeTo": null,
+            "resolution": "oneHour",
+            "placeOrderSettings": null
+        });
+
+        if let Some(price_adjustment) = price_adjustment {
+            def["priceAdjustment"] = serde_json::to_value(price_adjustment).unwrap();
+        }
+
+        serde_json::from_value(def).unwrap()
+    }
+
+    #[test]
+    fn test_price_adjustment_id() {
+        let raw = instance_definition(None);
+        assert!(raw.price_adjustment().is_none());
+
+        // Instances defined before the setting keep their ids
+        assert_eq!(raw.id().to_string(), "f0a8c415-008a-5c8f-9aeb-255b76049cf3");
+        assert_eq!(
+            instance_definition(Some(PriceAdjustment::default())).id(),
+            raw.id()
+        );
+
+        let splits = instance_definition(Some(PriceAdjustment {
+            splits: true,
+            dividends: false,
+        }));
+        let dividends = instance_definition(Some(PriceAdjustment {
+            splits: true,
+            dividends: true,
+        }));
+
+        assert_ne!(splits.id(), raw.id());
+        assert_ne!(dividends.id(), splits.id());
+    }
+
+    #[test]
+    fn test_decide_order() {
+        let settings = settings(Some(0.5), Some(-0.5));
+
+        assert_eq!(
+            settings.decide_order(0.7, 0),
+            Some((OrderDirection::Buy, 1))
+        );
+        assert_eq!(settings.decide_order(0.7, 3), None);
+        assert_eq!(settings.decide_order(0.0, 3), None);
+        assert_eq!(
+            settings.decide_order(-0.5, 3),
+            Some((OrderDirection::Sell, 3))
+        );
+        assert_eq!(settings.decide_order(-0.9, 0), None);
+
+        let buy_only = self::settings(Some(0.5), None);
+        assert_eq!(buy_only.decide_order(-1.0, 3), None);
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        let time_from = Utc.with_ymd_and_hms(2022, 1, 3, 0, 0, 0).unwrap();
+        let mut execution = StrategyExecution::new(StrategyExecutionStatus::Running, time_from);
+
+        execution
+            .transition(StrategyExecutionStatus::Paused, None)
+            .unwrap();
+        assert!(execution
+            .transition(StrategyExecutionStatus::Finished, None)
+            .is_err());
+
+        execution
+            .transition(StrategyExecutionStatus::Stopped, None)
+            .unwrap();
+        assert!(execution
+            .transition(StrategyExecutionStatus::Running, None)
+            .is_err());
+
+        execution.reset(time_from);
+        assert_eq!(execution.status(), StrategyExecutionStatus::Running);
+
+        let statuses: Vec<_> = execution
+            .history
+            .iter()
+            .map(|transition| transition.status)
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                StrategyExecutionStatus::Running,
+                StrategyExecutionStatus::Paused,
+                StrategyExecutionStatus::Stopped,
+                StrategyExecutionStatus::Running,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_revision() {
+        let time_from = Utc.with_ymd_and_hms(2022, 1, 3, 0, 0, 0).unwrap();
+        let mut execution = StrategyExecution::new(StrategyExecutionStatus::Running, time_from);
+        let revision = execution.revision();
+
+        // Progress of the runner is not a change made through the API
+        execution.set_last_execution_timestamp(time_from + chrono::Duration::hours(1));
+        assert_eq!(execution.revision(), revision);
+
+        let mut paused = execution.clone();
+        paused
+            .transition(StrategyExecutionStatus::Paused, None)
+            .unwrap();
+        assert_ne!(paused.revision(), revision);
+
+        execution.reset(time_from);
+        assert_eq!(execution.revision().generation, revision.generation + 1);
+        assert_ne!(execution.revision(), paused.revision());
+    }
+
+    #[test]
+    fn test_unreadable_indicator_state() {
+        let sma = crate::indicators::Sma::new(Figi("FIGI".to_owned()), 3);
+        let candle = Candle {
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            volume: 1,
+        };
+
+        let mut state = StrategyState::default();
+        assert!(state.update_indicator(&sma, &candle).is_ok());
+
+        // State of another indicator version is not silently started over
+        state
+            .indicators
+            .insert(sma.id(), bson::Bson::String("unknown".to_owned()));
+        assert!(matches!(
+            state.update_indicator(&sma, &candle),
+            Err(StrategyExecutionError::CriticalFailure(_))
+        ));
+    }
+
+    #[test]
+    fn test_fail() {
+        let now = Utc.with_ymd_and_hms(2022, 1, 3, 10, 0, 0).unwrap();
+        let mut execution = StrategyExecution::new(StrategyExecutionStatus::Running, now);
+
+        execution
+            .fail(FailureKind::Critical, "Broken state".into(), Some(now), now)
+            .unwrap();
+
+        assert_eq!(execution.status(), StrategyExecutionStatus::Failed);
+        assert_eq!(
+            execution.failure.as_ref().map(|failure| failure.candle_ts),
+            Some(Some(now))
+        );
+
+        // A failed instance can not fail again until it is resumed
+        assert!(execution
+            .fail(FailureKind::Panic, "Panicked".into(), None, now)
+            .is_err());
+
+        execution
+            .transition(StrategyExecutionStatus::Running, None)
+            .unwrap();
+        assert!(execution.failure.is_none());
+    }
+}
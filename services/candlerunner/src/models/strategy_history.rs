@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::instruments::Figi;
+use crate::models::market_data::CandleResolution;
+use crate::models::strategy::StrategyState;
+
+const DEFAULT_LIMIT: usize = 500;
+const MAX_LIMIT: usize = 5000;
+
+/// Range of computed strategy states to read
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct StrategyHistoryQuery {
+    /// Defaults to `time_from` of the instance
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+
+    /// Keeps only the last state within each candle of the resolution,
+    /// downsampling is done by the database
+    pub resolution: Option<CandleResolution>,
+
+    /// Keeps only signals of the instrument
+    pub figi: Option<Figi>,
+
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPage<T> {
+    /// Number of points in the range after downsampling, before pagination
+    pub total: usize,
+    pub points: Vec<T>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalPoint {
+    pub ts: DateTime<Utc>,
+    pub signals: HashMap<Figi, f64>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IndicatorPoint {
+    pub ts: DateTime<Utc>,
+
+    /// Values keyed by indicator id, indicators without a value yet are omitted
+    pub indicators: HashMap<uuid::Uuid, bson::Bson>,
+}
+
+impl StrategyHistoryQuery {
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    pub fn signals(
+        &self,
+        total: usize,
+        states: Vec<(DateTime<Utc>, StrategyState)>,
+    ) -> HistoryPage<SignalPoint> {
+        let points = states
+            .into_iter()
+            .map(|(ts, state)| {
+                let signals = state
+                    .signals()
+                    .iter()
+                    .filter(|(figi, _)| self.figi.as_ref().is_none_or(|filter| filter == *figi))
+                    .map(|(figi, value)| (figi.clone(), *value))
+                    .collect();
+
+                SignalPoint { ts, signals }
+            })
+            .collect();
+
+        HistoryPage { total, points }
+    }
+
+    pub fn indicators(
+        &self,
+        total: usize,
+        states: Vec<(DateTime<Utc>, StrategyState)>,
+    ) -> HistoryPage<IndicatorPoint> {
+        let points = states
+            .into_iter()
+            .map(|(ts, state)| IndicatorPoint {
+                ts,
+                indicators: state.indicator_values(),
+            })
+            .collect();
+
+        HistoryPage { total, points }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_history() {
+        let figi = Figi("FIGI".to_owned());
+        let other = Figi("OTHER".to_owned());
+
+        let states: Vec<_> = (0..3)
+            .map(|minute| {
+                let mut state = StrategyState::default();
+                state.set_signal(figi.clone(), minute as f64);
+                state.set_signal(other.clone(), -1.0);
+
+                (
+                    Utc.with_ymd_and_hms(2022, 1, 3, 10, minute * 2, 0).unwrap(),
+                    state,
+                )
+            })
+            .collect();
+
+        let query = StrategyHistoryQuery {
+            figi: Some(figi.clone()),
+            limit: Some(MAX_LIMIT + 1),
+            ..Default::default()
+        };
+        assert_eq!(query.limit(), MAX_LIMIT);
+
+        let page = query.signals(10, states);
+        assert_eq!(page.total, 10);
+
+        let values: Vec<_> = page
+            .points
+            .iter()
+            .map(|point| (point.ts.minute(), point.signals.len(), point.signals[&figi]))
+            .collect();
+        assert_eq!(values, [(0, 1, 0.0), (2, 1, 1.0), (4, 1, 2.0)]);
+    }
+}
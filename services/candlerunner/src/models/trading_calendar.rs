@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Trading schedule of an exchange on a calendar day
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TradingDay {
+    pub is_trading_day: bool,
+
+    /// Start of the first trading session
+    pub start_time: Option<DateTime<Utc>>,
+
+    /// End of the last trading session
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+/// Trading days of an exchange. Days missing in the calendar are unknown
+/// and are treated as trading ones.
+#[derive(Debug, Clone, Default)]
+pub struct TradingCalendar {
+    days: BTreeMap<NaiveDate, TradingDay>,
+}
+
+impl TradingCalendar {
+    pub fn new(days: BTreeMap<NaiveDate, TradingDay>) -> Self {
+        Self { days }
+    }
+
+    pub fn day(&self, date: NaiveDate) -> Option<&TradingDay> {
+        self.days.get(&date)
+    }
+
+    /// Whether the exchange is known to be closed for the whole day
+    pub fn is_closed(&self, date: NaiveDate) -> bool {
+        self.day(date).is_some_and(|day| !day.is_trading_day)
+    }
+
+    /// Whether trading of the day is known to be over at `ts`
+    pub fn is_session_over(&self, date: NaiveDate, ts: DateTime<Utc>) -> bool {
+        self.day(date)
+            .is_some_and(|day| day.end_time.is_some_and(|end_time| ts >= end_time))
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::models::instruments::Figi;
+use crate::models::params::{ParamDefinition, ParamError, ParamType, ParamValue};
+use crate::models::position_manager::{
+    InstantiatePositionManagerError, PositionManager, PositionManagerDefinition,
+    PositionManagerFactory, StrategySignals, TargetWeights,
+};
+
+const PARAM_NAME_MAX_EXPOSURE: &str = "max_exposure";
+const PARAM_NAME_MIN_SIGNAL: &str = "min_signal";
+
+const DEFAULT_MAX_EXPOSURE: f64 = 1.0;
+const DEFAULT_MIN_SIGNAL: f64 = 0.0;
+
+/// Averages signals of all strategies per instrument
+/// and splits exposure equally between instruments.
+pub struct EqualWeight {
+    max_exposure: f64,
+    min_signal: f64,
+}
+
+impl EqualWeight {
+    pub fn new(max_exposure: f64, min_signal: f64) -> Self {
+        Self {
+            max_exposure,
+            min_signal,
+        }
+    }
+}
+
+impl PositionManager for EqualWeight {
+    fn target_weights(&self, signals: &HashMap<Uuid, StrategySignals>) -> TargetWeights {
+        let mut sums: HashMap<Figi, (f64, usize)> = Default::default();
+
+        for strategy_signals in signals.values() {
+            for (figi, signal) in strategy_signals {
+                let (sum, count) = sums.entry(figi.clone()).or_default();
+                *sum += signal.clamp(-1.0, 1.0);
+                *count += 1;
+            }
+        }
+
+        if sums.is_empty() {
+            return Default::default();
+        }
+
+        let share = self.max_exposure / sums.len() as f64;
+
+        sums.into_iter()
+            .map(|(figi, (sum, count))| {
+                let signal = sum / count as f64;
+
+                let weight = match signal.abs() < self.min_signal {
+                    true => 0.0,
+                    false => signal * share,
+                };
+
+                (figi, weight)
+            })
+            .collect()
+    }
+}
+
+pub struct EqualWeightFactory {
+    definition: PositionManagerDefinition,
+}
+
+impl Default for EqualWeightFactory {
+    fn default() -> Self {
+        Self {
+            definition: PositionManagerDefinition::new(
+                vec![
+                    ParamDefinition::new(
+                        PARAM_NAME_MAX_EXPOSURE,
+                        "Share of the portfolio value allowed to be invested",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_MAX_EXPOSURE)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_MIN_SIGNAL,
+                        "Averaged signals weaker than this value are ignored",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_MIN_SIGNAL)),
+                    ),
+                ],
+                "EqualWeight",
+                "Averages strategy signals per instrument and splits exposure equally between instruments",
+            ),
+        }
+    }
+}
+
+fn get_float_param(
+    params: &HashMap<String, ParamValue>,
+    name: &str,
+    default: f64,
+) -> Result<f64, ParamError> {
+    match params.get(name) {
+        Some(value) => value
+            .as_float()
+            .ok_or_else(|| ParamError::ParamTypeMismatch(name.to_owned())),
+        None => Ok(default),
+    }
+}
+
+impl PositionManagerFactory for EqualWeightFactory {
+    fn position_manager_name(&self) -> &str {
+        "EqualWeight"
+    }
+
+    fn definition(&self) -> &PositionManagerDefinition {
+        &self.definition
+    }
+
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn PositionManager>, InstantiatePositionManagerError> {
+        let max_exposure = get_float_param(params, PARAM_NAME_MAX_EXPOSURE, DEFAULT_MAX_EXPOSURE)?;
+        let min_signal = get_float_param(params, PARAM_NAME_MIN_SIGNAL, DEFAULT_MIN_SIGNAL)?;
+
+        if !(0.0..=1.0).contains(&max_exposure) {
+            return Err(ParamError::InvalidParam(PARAM_NAME_MAX_EXPOSURE.to_owned()).into());
+        }
+
+        Ok(Arc::new(EqualWeight::new(max_exposure, min_signal)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_weight() {
+        let sber = Figi("SBER".to_owned());
+        let gazp = Figi("GAZP".to_owned());
+
+        let signals: HashMap<Uuid, StrategySignals> = [
+            (
+                Uuid::from_u128(1),
+                [(sber.clone(), 1.0), (gazp.clone(), 0.1)]
+                    .into_iter()
+                    .collect(),
+            ),
+            (
+                Uuid::from_u128(2),
+                [(sber.clone(), 0.5)].into_iter().collect(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let weights = EqualWeight::new(0.8, 0.2).target_weights(&signals);
+
+        assert!((weights[&sber] - 0.3).abs() < 1e-9);
+        assert_eq!(weights[&gazp], 0.0);
+    }
+}
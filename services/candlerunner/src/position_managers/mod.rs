@@ -0,0 +1,3 @@
+mod equal_weight;
+
+pub use equal_weight::EqualWeightFactory;
@@ -1,6 +1,9 @@
 use warp::hyper::StatusCode;
 
-use crate::models::strategy::InstantiateStrategyError;
+use crate::models::backtest::InvalidBacktestSettings;
+use crate::models::corporate_actions::InvalidCorporateAction;
+use crate::models::position_manager::InstantiatePositionManagerError;
+use crate::models::strategy::{InstantiateStrategyError, InvalidStatusTransition};
 
 pub enum ServiceError {
     NotFound(String),
@@ -40,13 +43,45 @@ impl From<InstantiateStrategyError> for ServiceError {
             InstantiateStrategyError::FailedToInstantiate(_) => {
                 ServiceError::InternalError(err.to_string())
             }
-            InstantiateStrategyError::ParamValidationFailed { source: _ } => {
+            InstantiateStrategyError::ParamValidationFailed { source: _ }
+            | InstantiateStrategyError::UnsupportedResolution(_, _) => {
                 ServiceError::BadRequest(err.to_string())
             }
         }
     }
 }
 
+impl From<InstantiatePositionManagerError> for ServiceError {
+    fn from(err: InstantiatePositionManagerError) -> Self {
+        match err {
+            InstantiatePositionManagerError::NotFound(_) => ServiceError::NotFound(err.to_string()),
+            InstantiatePositionManagerError::StrategyNotFound(_)
+            | InstantiatePositionManagerError::NoStrategies
+            | InstantiatePositionManagerError::ParamValidationFailed { source: _ } => {
+                ServiceError::BadRequest(err.to_string())
+            }
+        }
+    }
+}
+
+impl From<InvalidBacktestSettings> for ServiceError {
+    fn from(err: InvalidBacktestSettings) -> Self {
+        ServiceError::BadRequest(err.to_string())
+    }
+}
+
+impl From<InvalidStatusTransition> for ServiceError {
+    fn from(err: InvalidStatusTransition) -> Self {
+        ServiceError::BadRequest(err.to_string())
+    }
+}
+
+impl From<InvalidCorporateAction> for ServiceError {
+    fn from(err: InvalidCorporateAction) -> Self {
+        ServiceError::BadRequest(err.to_string())
+    }
+}
+
 impl From<anyhow::Error> for ServiceError {
     fn from(err: anyhow::Error) -> Self {
         ServiceError::InternalError(err.to_string())
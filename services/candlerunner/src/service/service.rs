@@ -1,91 +1,852 @@
 use std::{collections::HashMap, net::SocketAddr};
 
+use chrono::prelude::*;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use std::time::Duration;
 use warp::{
-    hyper::{Method, StatusCode},
-    Filter,
+    hyper::{body::Bytes, Method, StatusCode},
+    Filter, Reply,
 };
 
 use component_store::ComponentStore;
 
+use crate::backtest::{build_report, replay_orders};
+use crate::candle_files::{self, CandleFileFormat};
 use crate::components;
 use crate::models::account::{AccountId, Environment};
-use crate::models::strategy::StrategyInstanceDefinition;
+use crate::models::backtest::{BacktestResult, BacktestSettings};
+use crate::models::corporate_actions::{CorporateAction, PriceAdjustment};
+use crate::models::instruments::Figi;
+use crate::models::market_data::CandleResolution;
+use crate::models::position_manager::PositionManagerInstanceDefinition;
+use crate::models::report::{PerformanceReport, ReportSource};
+use crate::models::strategy::{
+    StrategyExecution, StrategyExecutionStatus, StrategyInstanceDefinition, StrategyState,
+};
+use crate::models::strategy_history::StrategyHistoryQuery;
+
+use super::error::ServiceError;
+
+fn list_instruments_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(warp::reply::Json,)>> {
+    let instrument_cache = component_store
+        .resolve::<components::InstrumentCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `InstrumentsCache`"))?;
+
+    let list_instruments = warp::get()
+        .and(warp::path!("list-instruments"))
+        .map(move || {
+            let instrument_cache = instrument_cache.state();
+            let instruments: Vec<_> = instrument_cache.values().collect();
+            warp::reply::json(&instruments)
+        })
+        .boxed();
+
+    Ok(list_instruments)
+}
+
+fn search_instruments_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let instrument_cache = component_store
+        .resolve::<components::InstrumentCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `InstrumentsCache`"))?;
+
+    let search_instruments = warp::get()
+        .and(warp::path!("search-instruments"))
+        .and(warp::query())
+        .map(move |query: components::InstrumentQuery| {
+            let instrument_cache = instrument_cache.state();
+            warp::reply::json(&instrument_cache.search(&query))
+        })
+        .boxed();
+
+    Ok(search_instruments)
+}
+
+fn list_strategies_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_registry = component_store
+        .resolve::<components::StrategyRegistry>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyRegistry`"))?;
+
+    let list_strategies = warp::get()
+        .and(warp::path!("list-strategies"))
+        .map(move || {
+            let definitions: Vec<_> = strategy_registry.definitions().collect();
+            warp::reply::json(&definitions)
+        })
+        .boxed();
+
+    Ok(list_strategies)
+}
+
+fn instantiate_strategy_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_registry = component_store
+        .resolve::<components::StrategyRegistry>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyRegistry`"))?;
+
+    let strategy_cache = component_store
+        .resolve::<components::StrategyCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let instantiate_strategy = warp::post()
+        .and(warp::path!("instantiate-strategy"))
+        .and(warp::body::json())
+        .then(move |def: StrategyInstanceDefinition| {
+            let strategy_registry = strategy_registry.clone();
+            let mongo = mongo.clone();
+            let strategy_cache = strategy_cache.clone();
+
+            let view = async move {
+                strategy_registry
+                    .validate_instance_definition(&def)
+                    .map_err(ServiceError::from)?;
+
+                if let Err(err) = mongo.write_strategy_instance(&def).await {
+                    println!("Failed to write strategy instance to mongo: {}", err);
+                    return Err(ServiceError::InternalError(err.to_string()));
+                }
+
+                strategy_cache
+                    .force_update(Some(Duration::from_millis(500)))
+                    .await;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({})),
+                    StatusCode::OK,
+                ))
+            };
+
+            async move {
+                match view.await {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(instantiate_strategy)
+}
+
+fn list_strategy_instances_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_cache = component_store
+        .resolve::<components::StrategyCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+
+    let list_strategy_instances = warp::get()
+        .and(warp::path!("list-strategy-instances"))
+        .map(move || {
+            let strategy_cache = strategy_cache.state();
+
+            let payload: HashMap<_, _> = strategy_cache
+                .iter()
+                .map(|(instance_id, (def, _))| (*instance_id, def.clone()))
+                .collect();
+
+            warp::reply::json(&payload)
+        })
+        .boxed();
+
+    Ok(list_strategy_instances)
+}
+
+fn strategy_status_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_cache = component_store
+        .resolve::<components::StrategyCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let strategy_status = warp::get()
+        .and(warp::path!("strategy-instances" / Uuid / "status"))
+        .then(move |strategy_id: Uuid| {
+            let strategy_cache = strategy_cache.clone();
+            let mongo = mongo.clone();
+
+            let view = async move {
+                let def = strategy_cache
+                    .state()
+                    .get(&strategy_id)
+                    .map(|(def, _)| def.clone())
+                    .ok_or_else(|| {
+                        ServiceError::NotFound("Strategy instance not found".to_owned())
+                    })?;
+
+                // Instances not picked up by the runner yet are about to run
+                let execution = mongo
+                    .read_strategy_execution(&strategy_id)
+                    .await
+                    .map_err(ServiceError::from)?
+                    .unwrap_or_else(|| {
+                        StrategyExecution::new(StrategyExecutionStatus::Running, def.time_from())
+                    });
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&execution),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(strategy_status)
+}
+
+/// Pauses, resumes or stops the instance
+fn change_strategy_status_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_cache = component_store
+        .resolve::<components::StrategyCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let target_status = warp::path!("strategy-instances" / Uuid / "pause")
+        .map(|strategy_id| (strategy_id, StrategyExecutionStatus::Paused))
+        .or(warp::path!("strategy-instances" / Uuid / "resume")
+            .map(|strategy_id| (strategy_id, StrategyExecutionStatus::Running)))
+        .unify()
+        .or(warp::path!("strategy-instances" / Uuid / "stop")
+            .map(|strategy_id| (strategy_id, StrategyExecutionStatus::Stopped)))
+        .unify();
+
+    let change_strategy_status = warp::post()
+        .and(target_status)
+        .then(move |(strategy_id, status)| {
+            let strategy_cache = strategy_cache.clone();
+            let mongo = mongo.clone();
+
+            let view = async move {
+                let def = strategy_cache
+                    .state()
+                    .get(&strategy_id)
+                    .map(|(def, _)| def.clone())
+                    .ok_or_else(|| {
+                        ServiceError::NotFound("Strategy instance not found".to_owned())
+                    })?;
+
+                let mut execution = mongo
+                    .read_strategy_execution(&strategy_id)
+                    .await
+                    .map_err(ServiceError::from)?
+                    .unwrap_or_else(|| {
+                        StrategyExecution::new(StrategyExecutionStatus::Running, def.time_from())
+                    });
+
+                execution
+                    .transition(status, None)
+                    .map_err(ServiceError::from)?;
+
+                mongo
+                    .write_strategy_execution(&strategy_id, &execution)
+                    .await
+                    .map_err(ServiceError::from)?;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&execution),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(change_strategy_status)
+}
+
+/// Drops computed states, so that the instance is executed from `time_from` again
+fn reset_strategy_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_cache = component_store
+        .resolve::<components::StrategyCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let reset_strategy = warp::post()
+        .and(warp::path!("strategy-instances" / Uuid / "reset"))
+        .then(move |strategy_id: Uuid| {
+            let strategy_cache = strategy_cache.clone();
+            let mongo = mongo.clone();
+
+            let view = async move {
+                let def = strategy_cache
+                    .state()
+                    .get(&strategy_id)
+                    .map(|(def, _)| def.clone())
+                    .ok_or_else(|| {
+                        ServiceError::NotFound("Strategy instance not found".to_owned())
+                    })?;
+
+                let mut execution = mongo
+                    .read_strategy_execution(&strategy_id)
+                    .await
+                    .map_err(ServiceError::from)?
+                    .unwrap_or_else(|| {
+                        StrategyExecution::new(StrategyExecutionStatus::Running, def.time_from())
+                    });
+
+                execution.reset(def.time_from());
+
+                // Execution goes first for the runner to drop results computed meanwhile
+                mongo
+                    .write_strategy_execution(&strategy_id, &execution)
+                    .await
+                    .map_err(ServiceError::from)?;
+
+                mongo
+                    .reset_strategy_results(&strategy_id)
+                    .await
+                    .map_err(ServiceError::from)?;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&execution),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(reset_strategy)
+}
+
+fn delete_strategy_instance_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_cache = component_store
+        .resolve::<components::StrategyCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+
+    let position_manager_cache = component_store
+        .resolve::<components::PositionManagerCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `PositionManagerCache`"))?;
+
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let delete_strategy_instance = warp::delete()
+        .and(warp::path!("strategy-instances" / Uuid))
+        .then(move |strategy_id: Uuid| {
+            let strategy_cache = strategy_cache.clone();
+            let position_manager_cache = position_manager_cache.clone();
+            let mongo = mongo.clone();
+
+            let view = async move {
+                if !strategy_cache.state().contains_key(&strategy_id) {
+                    return Err(ServiceError::NotFound(
+                        "Strategy instance not found".to_owned(),
+                    ));
+                }
+
+                let is_managed = position_manager_cache
+                    .state()
+                    .values()
+                    .any(|(def, _)| def.strategies().contains(&strategy_id));
+
+                if is_managed {
+                    return Err(ServiceError::BadRequest(
+                        "Strategy instance is used by a position manager".to_owned(),
+                    ));
+                }
+
+                mongo
+                    .delete_strategy_instance(&strategy_id)
+                    .await
+                    .map_err(ServiceError::from)?;
+
+                strategy_cache
+                    .force_update(Some(Duration::from_millis(500)))
+                    .await;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({})),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(delete_strategy_instance)
+}
+
+fn run_backtest_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_cache = component_store
+        .resolve::<components::StrategyCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+
+    let backtest_runner = component_store
+        .resolve::<components::BacktestRunner>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `BacktestRunner`"))?;
+
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let run_backtest = warp::post()
+        .and(warp::path!("strategy-instances" / Uuid / "backtest"))
+        .and(warp::body::json())
+        .then(move |strategy_id: Uuid, settings: BacktestSettings| {
+            let strategy_cache = strategy_cache.clone();
+            let backtest_runner = backtest_runner.clone();
+            let mongo = mongo.clone();
+
+            let view = async move {
+                if !strategy_cache.state().contains_key(&strategy_id) {
+                    return Err(ServiceError::NotFound(
+                        "Strategy instance not found".to_owned(),
+                    ));
+                }
+
+                settings.validate().map_err(ServiceError::from)?;
+
+                mongo
+                    .write_backtest_settings(&strategy_id, &settings)
+                    .await
+                    .map_err(ServiceError::from)?;
+
+                backtest_runner
+                    .force_update(Some(Duration::from_millis(500)))
+                    .await;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({})),
+                    StatusCode::OK,
+                ))
+            };
+
+            async move {
+                match view.await {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(run_backtest)
+}
+
+/// Reads the backtest result unless it was computed before the instance was reset
+async fn read_current_backtest_result(
+    mongo: &components::Mongo,
+    strategy_id: &Uuid,
+) -> Result<Option<BacktestResult>, ServiceError> {
+    let generation = mongo
+        .read_strategy_execution(strategy_id)
+        .await?
+        .map(|execution| execution.generation())
+        .unwrap_or_default();
+
+    Ok(mongo
+        .read_backtest_result(strategy_id)
+        .await?
+        .filter(|result| result.generation == generation))
+}
+
+fn backtest_result_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let backtest_result = warp::get()
+        .and(warp::path!("strategy-instances" / Uuid / "backtest"))
+        .then(move |strategy_id: Uuid| {
+            let mongo = mongo.clone();
+
+            let view = async move {
+                let result = read_current_backtest_result(&mongo, &strategy_id)
+                    .await?
+                    .ok_or_else(|| {
+                        ServiceError::NotFound("Backtest result not found".to_owned())
+                    })?;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&result),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(backtest_result)
+}
+
+/// Report of the current backtest, `None` when the instance isn't backtested
+async fn read_backtest_report(
+    mongo: &components::Mongo,
+    strategy_id: &Uuid,
+) -> Result<Option<PerformanceReport>, ServiceError> {
+    let settings = match mongo.read_backtest_settings(strategy_id).await? {
+        Some((settings, _)) => settings,
+        None => return Ok(None),
+    };
+
+    let result = match read_current_backtest_result(mongo, strategy_id).await? {
+        Some(result) => result,
+        None => return Ok(None),
+    };
+
+    let report = build_report(ReportSource::Backtest, settings.initial_cash(), &result)
+        .ok_or_else(|| ServiceError::NotFound("Backtest has no equity data yet".to_owned()))?;
+
+    Ok(Some(report))
+}
+
+/// Report of orders executed for the instance, valued at candles up to now
+async fn read_orders_report(
+    strategy_cache: &components::StrategyCache,
+    instrument_cache: &components::InstrumentCache,
+    trading_calendar_cache: &components::TradingCalendarCache,
+    mongo: &components::Mongo,
+    strategy_id: &Uuid,
+) -> Result<PerformanceReport, ServiceError> {
+    let resolution = strategy_cache
+        .state()
+        .get(strategy_id)
+        .map(|(def, _)| def.resolution())
+        .ok_or_else(|| ServiceError::NotFound("Strategy instance not found".to_owned()))?;
+
+    let records = mongo.read_order_records(strategy_id).await?;
+
+    let first = records.first().ok_or_else(|| {
+        ServiceError::NotFound("Neither backtest result nor executed orders found".to_owned())
+    })?;
+
+    let mut figis: Vec<_> = records.iter().map(|record| record.figi.clone()).collect();
+    figis.sort();
+    figis.dedup();
+
+    let candles = components::read_market_data(
+        mongo,
+        &trading_calendar_cache.state(),
+        &figis,
+        first.ts,
+        Utc::now(),
+        resolution,
+        PriceAdjustment::default(),
+    )
+    .await?;
+
+    let (initial_equity, result) =
+        replay_orders(&records, &instrument_cache.state(), resolution, &candles)
+            .ok_or_else(|| ServiceError::NotFound("No orders are executed yet".to_owned()))?;
+
+    build_report(ReportSource::Orders, initial_equity, &result)
+        .ok_or_else(|| ServiceError::NotFound("No candles after executed orders yet".to_owned()))
+}
+
+fn strategy_report_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_cache = component_store
+        .resolve::<components::StrategyCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+
+    let instrument_cache = component_store
+        .resolve::<components::InstrumentCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `InstrumentsCache`"))?;
+
+    let trading_calendar_cache = component_store
+        .resolve::<components::TradingCalendarCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `TradingCalendarCache`"))?;
+
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let strategy_report = warp::get()
+        .and(warp::path!("strategy-instances" / Uuid / "report"))
+        .then(move |strategy_id: Uuid| {
+            let strategy_cache = strategy_cache.clone();
+            let instrument_cache = instrument_cache.clone();
+            let trading_calendar_cache = trading_calendar_cache.clone();
+            let mongo = mongo.clone();
+
+            let view = async move {
+                // Live instances without backtest are reported from their orders
+                let report = match read_backtest_report(&mongo, &strategy_id).await? {
+                    Some(report) => report,
+                    None => {
+                        read_orders_report(
+                            &strategy_cache,
+                            &instrument_cache,
+                            &trading_calendar_cache,
+                            &mongo,
+                            &strategy_id,
+                        )
+                        .await?
+                    }
+                };
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&report),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(strategy_report)
+}
+
+/// Reads states of the instance within the query range
+async fn read_strategy_history(
+    strategy_cache: &components::StrategyCache,
+    mongo: &components::Mongo,
+    strategy_id: &Uuid,
+    query: &StrategyHistoryQuery,
+) -> Result<(usize, Vec<(DateTime<Utc>, StrategyState)>), ServiceError> {
+    let def = strategy_cache
+        .state()
+        .get(strategy_id)
+        .map(|(def, _)| def.clone())
+        .ok_or_else(|| ServiceError::NotFound("Strategy instance not found".to_owned()))?;
+
+    let from = query.from.unwrap_or_else(|| def.time_from());
+
+    if query.to.is_some_and(|to| to <= from) {
+        return Err(ServiceError::BadRequest(
+            "`to` must be later than `from`".to_owned(),
+        ));
+    }
+
+    Ok(mongo
+        .read_strategy_history(
+            strategy_id,
+            from,
+            query.to,
+            query.resolution,
+            query.offset,
+            query.limit(),
+        )
+        .await?)
+}
+
+fn strategy_signals_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_cache = component_store
+        .resolve::<components::StrategyCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
 
-use super::error::ServiceError;
+    let strategy_signals = warp::get()
+        .and(warp::path!("strategy-instances" / Uuid / "signals"))
+        .and(warp::query())
+        .then(move |strategy_id: Uuid, query: StrategyHistoryQuery| {
+            let strategy_cache = strategy_cache.clone();
+            let mongo = mongo.clone();
 
-fn list_instruments_view(
+            let view = async move {
+                let (total, states) =
+                    read_strategy_history(&strategy_cache, &mongo, &strategy_id, &query).await?;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&query.signals(total, states)),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(strategy_signals)
+}
+
+fn strategy_indicators_view(
     component_store: &ComponentStore,
-) -> anyhow::Result<warp::filters::BoxedFilter<(warp::reply::Json,)>> {
-    let instrument_cache = component_store
-        .resolve::<components::InstrumentCache>()
-        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `InstrumentsCache`"))?;
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let strategy_cache = component_store
+        .resolve::<components::StrategyCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
 
-    let list_instruments = warp::get()
-        .and(warp::path!("list-instruments"))
-        .map(move || {
-            let instrument_cache = instrument_cache.state();
-            let instruments: Vec<_> = instrument_cache.values().collect();
-            warp::reply::json(&instruments)
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let strategy_indicators = warp::get()
+        .and(warp::path!("strategy-instances" / Uuid / "indicators"))
+        .and(warp::query())
+        .then(move |strategy_id: Uuid, query: StrategyHistoryQuery| {
+            let strategy_cache = strategy_cache.clone();
+            let mongo = mongo.clone();
+
+            let view = async move {
+                let (total, states) =
+                    read_strategy_history(&strategy_cache, &mongo, &strategy_id, &query).await?;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&query.indicators(total, states)),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
         })
         .boxed();
 
-    Ok(list_instruments)
+    Ok(strategy_indicators)
 }
 
-fn list_strategies_view(
+fn list_position_managers_view(
     component_store: &ComponentStore,
 ) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
-    let strategy_registry = component_store
-        .resolve::<components::StrategyRegistry>()
-        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyRegistry`"))?;
+    let position_manager_registry = component_store
+        .resolve::<components::PositionManagerRegistry>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `PositionManagerRegistry`"))?;
 
-    let list_strategies = warp::get()
-        .and(warp::path!("list-strategies"))
+    let list_position_managers = warp::get()
+        .and(warp::path!("list-position-managers"))
         .map(move || {
-            let definitions: Vec<_> = strategy_registry.definitions().collect();
+            let definitions: Vec<_> = position_manager_registry.definitions().collect();
             warp::reply::json(&definitions)
         })
         .boxed();
 
-    Ok(list_strategies)
+    Ok(list_position_managers)
 }
 
-fn instantiate_strategy_view(
+fn instantiate_position_manager_view(
     component_store: &ComponentStore,
 ) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
-    let strategy_registry = component_store
-        .resolve::<components::StrategyRegistry>()
-        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyRegistry`"))?;
+    let position_manager_registry = component_store
+        .resolve::<components::PositionManagerRegistry>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `PositionManagerRegistry`"))?;
 
-    let strategy_cache = component_store
-        .resolve::<components::StrategyCache>()
-        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+    let position_manager_cache = component_store
+        .resolve::<components::PositionManagerCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `PositionManagerCache`"))?;
 
     let mongo = component_store
         .resolve::<components::Mongo>()
         .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
 
-    let instantiate_strategy = warp::post()
-        .and(warp::path!("instantiate-strategy"))
+    let instantiate_position_manager = warp::post()
+        .and(warp::path!("instantiate-position-manager"))
         .and(warp::body::json())
-        .then(move |def: StrategyInstanceDefinition| {
-            let strategy_registry = strategy_registry.clone();
+        .then(move |def: PositionManagerInstanceDefinition| {
+            let position_manager_registry = position_manager_registry.clone();
             let mongo = mongo.clone();
-            let strategy_cache = strategy_cache.clone();
+            let position_manager_cache = position_manager_cache.clone();
 
             let view = async move {
-                strategy_registry
+                position_manager_registry
                     .validate_instance_definition(&def)
                     .map_err(ServiceError::from)?;
 
-                if let Err(err) = mongo.write_strategy_instance(&def).await {
-                    println!("Failed to write strategy instance to mongo: {}", err);
+                if let Err(err) = mongo.write_position_manager_instance(&def).await {
+                    println!("Failed to write position manager instance to mongo: {}", err);
                     return Err(ServiceError::InternalError(err.to_string()));
                 }
 
-                strategy_cache
+                position_manager_cache
                     .force_update(Some(Duration::from_millis(500)))
                     .await;
 
@@ -104,22 +865,22 @@ fn instantiate_strategy_view(
         })
         .boxed();
 
-    Ok(instantiate_strategy)
+    Ok(instantiate_position_manager)
 }
 
-fn list_strategy_instances_view(
+fn list_position_manager_instances_view(
     component_store: &ComponentStore,
 ) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
-    let strategy_cache = component_store
-        .resolve::<components::StrategyCache>()
-        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `StrategyCache`"))?;
+    let position_manager_cache = component_store
+        .resolve::<components::PositionManagerCache>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `PositionManagerCache`"))?;
 
-    let list_strategy_instances = warp::get()
-        .and(warp::path!("list-strategy-instances"))
+    let list_position_manager_instances = warp::get()
+        .and(warp::path!("list-position-manager-instances"))
         .map(move || {
-            let strategy_cache = strategy_cache.state();
+            let position_manager_cache = position_manager_cache.state();
 
-            let payload: HashMap<_, _> = strategy_cache
+            let payload: HashMap<_, _> = position_manager_cache
                 .iter()
                 .map(|(instance_id, (def, _))| (*instance_id, def.clone()))
                 .collect();
@@ -128,7 +889,36 @@ fn list_strategy_instances_view(
         })
         .boxed();
 
-    Ok(list_strategy_instances)
+    Ok(list_position_manager_instances)
+}
+
+fn target_positions_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let position_manager_runner = component_store
+        .resolve::<components::PositionManagerRunner>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `PositionManagerRunner`"))?;
+
+    let target_positions = warp::get()
+        .and(warp::path!(
+            "position-manager-instances" / Uuid / "target-positions"
+        ))
+        .map(move |instance_id: Uuid| {
+            let position_manager_runner = position_manager_runner.state();
+
+            let payload = match position_manager_runner.get(&instance_id) {
+                Some(p) => p,
+                None => {
+                    return ServiceError::NotFound("Position manager instance not found".to_owned())
+                        .into();
+                }
+            };
+
+            warp::reply::with_status(warp::reply::json(&payload), StatusCode::OK)
+        })
+        .boxed();
+
+    Ok(target_positions)
 }
 
 fn list_accounts_view(
@@ -142,7 +932,7 @@ fn list_accounts_view(
         .and(warp::path!("list-accounts"))
         .map(move || {
             let accounts_cache = accounts_cache.state();
-            let payload: Vec<_> = accounts_cache.iter().map(|(_, acc)| acc.clone()).collect();
+            let payload: Vec<_> = accounts_cache.values().cloned().collect();
 
             warp::reply::json(&payload)
         })
@@ -227,7 +1017,7 @@ fn close_sandbox_account_view(
     let close_sandbox_account = warp::post()
         .and(warp::path!("close-sandbox-account"))
         .and(warp::body::json())
-        .then(move |request: ListPositionsRequest| {
+        .then(move |request: CloseAccountRequest| {
             let accounts_cache = accounts_cache.clone();
             let tinkoff_client = tinkoff_client.clone();
 
@@ -305,22 +1095,285 @@ fn list_positions_view(
     Ok(list_positions)
 }
 
+fn data_quality_report_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let data_verifier = component_store
+        .resolve::<components::DataVerifier>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `DataVerifier`"))?;
+
+    let data_quality_report = warp::get()
+        .and(warp::path!("data-quality-report"))
+        .map(move || {
+            let report = data_verifier.state();
+
+            warp::reply::json(report.as_ref())
+        })
+        .boxed();
+
+    Ok(data_quality_report)
+}
+
+#[derive(Deserialize)]
+struct ExportCandlesQuery {
+    figi: String,
+    resolution: CandleResolution,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+
+    #[serde(default)]
+    split_adjusted: bool,
+
+    #[serde(default)]
+    dividend_adjusted: bool,
+
+    #[serde(default)]
+    format: CandleFileFormat,
+}
+
+fn export_candles_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let export_candles = warp::get()
+        .and(warp::path!("export-candles"))
+        .and(warp::query())
+        .then(move |query: ExportCandlesQuery| {
+            let mongo = mongo.clone();
+
+            let view = async move {
+                let candles = candle_files::export_candles(
+                    &mongo,
+                    &Figi(query.figi),
+                    query.resolution,
+                    query.from,
+                    query.to,
+                    PriceAdjustment {
+                        splits: query.split_adjusted,
+                        dividends: query.dividend_adjusted,
+                    },
+                )
+                .await
+                .map_err(ServiceError::from)?;
+
+                let mut data = Vec::new();
+                candle_files::write_candles(query.format, &mut data, &candles)
+                    .map_err(ServiceError::from)?;
+
+                Ok(
+                    warp::reply::with_header(data, "content-type", query.format.content_type())
+                        .into_response(),
+                )
+            };
+
+            async {
+                let reply: Result<warp::reply::Response, ServiceError> = view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => {
+                        warp::reply::WithStatus::<warp::reply::Json>::from(err).into_response()
+                    }
+                }
+            }
+        })
+        .boxed();
+
+    Ok(export_candles)
+}
+
+#[derive(Deserialize)]
+struct ImportCandlesQuery {
+    figi: String,
+    resolution: CandleResolution,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+
+    #[serde(default)]
+    format: CandleFileFormat,
+}
+
+fn import_candles_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let import_candles = warp::post()
+        .and(warp::path!("import-candles"))
+        .and(warp::query())
+        .and(warp::body::bytes())
+        .then(move |query: ImportCandlesQuery, body: Bytes| {
+            let mongo = mongo.clone();
+
+            let view = async move {
+                let candles = candle_files::read_candles(query.format, body)
+                    .map_err(|err| ServiceError::BadRequest(err.to_string()))?;
+
+                let range =
+                    candle_files::import_range(query.resolution, &candles, query.from, query.to)
+                        .map_err(|err| ServiceError::BadRequest(err.to_string()))?;
+
+                let imported = candle_files::import_candles(
+                    &mongo,
+                    &Figi(query.figi),
+                    query.resolution,
+                    candles,
+                    range,
+                )
+                .await
+                .map_err(ServiceError::from)?;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({ "imported": imported })),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(import_candles)
+}
+
+#[derive(Deserialize)]
+struct CorporateActionsQuery {
+    figi: String,
+}
+
+fn list_corporate_actions_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let list_corporate_actions = warp::get()
+        .and(warp::path!("corporate-actions"))
+        .and(warp::query())
+        .then(move |query: CorporateActionsQuery| {
+            let mongo = mongo.clone();
+
+            let view = async move {
+                let actions = mongo
+                    .read_corporate_actions(&[Figi(query.figi)])
+                    .await
+                    .map_err(ServiceError::from)?;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&actions),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(list_corporate_actions)
+}
+
+/// Stores actions not provided by the broker, e.g. splits
+fn write_corporate_actions_view(
+    component_store: &ComponentStore,
+) -> anyhow::Result<warp::filters::BoxedFilter<(impl warp::Reply,)>> {
+    let mongo = component_store
+        .resolve::<components::Mongo>()
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve `Mongo`"))?;
+
+    let write_corporate_actions = warp::post()
+        .and(warp::path!("corporate-actions"))
+        .and(warp::body::json())
+        .then(move |actions: Vec<CorporateAction>| {
+            let mongo = mongo.clone();
+
+            let view = async move {
+                for action in &actions {
+                    action.validate().map_err(ServiceError::from)?;
+                }
+
+                mongo
+                    .write_corporate_actions(&actions)
+                    .await
+                    .map_err(ServiceError::from)?;
+
+                Ok(warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({ "written": actions.len() })),
+                    StatusCode::OK,
+                ))
+            };
+
+            async {
+                let reply: Result<warp::reply::WithStatus<warp::reply::Json>, ServiceError> =
+                    view.await;
+
+                match reply {
+                    Ok(reply) => reply,
+                    Err(err) => warp::reply::WithStatus::<warp::reply::Json>::from(err),
+                }
+            }
+        })
+        .boxed();
+
+    Ok(write_corporate_actions)
+}
+
 pub async fn serve(addr: SocketAddr, component_store: &ComponentStore) -> anyhow::Result<()> {
     let cors = warp::cors()
-        .allow_methods(&[Method::GET, Method::POST, Method::OPTIONS])
+        .allow_methods(&[Method::GET, Method::POST, Method::DELETE, Method::OPTIONS])
         .allow_any_origin()
         .allow_headers(["access-control-allow-origin", "content-type"]);
 
     let routes = warp::any()
         .and(
             list_instruments_view(component_store)?
+                .or(search_instruments_view(component_store)?)
                 .or(list_strategies_view(component_store)?)
                 .or(list_strategy_instances_view(component_store)?)
                 .or(instantiate_strategy_view(component_store)?)
+                .or(strategy_status_view(component_store)?)
+                .or(change_strategy_status_view(component_store)?)
+                .or(reset_strategy_view(component_store)?)
+                .or(delete_strategy_instance_view(component_store)?)
+                .or(run_backtest_view(component_store)?)
+                .or(backtest_result_view(component_store)?)
+                .or(strategy_report_view(component_store)?)
+                .or(strategy_signals_view(component_store)?)
+                .or(strategy_indicators_view(component_store)?)
+                .or(list_position_managers_view(component_store)?)
+                .or(list_position_manager_instances_view(component_store)?)
+                .or(instantiate_position_manager_view(component_store)?)
+                .or(target_positions_view(component_store)?)
                 .or(list_accounts_view(component_store)?)
                 .or(open_sandbox_account_view(component_store)?)
                 .or(close_sandbox_account_view(component_store)?)
-                .or(list_positions_view(component_store)?),
+                .or(list_positions_view(component_store)?)
+                .or(data_quality_report_view(component_store)?)
+                .or(export_candles_view(component_store)?)
+                .or(import_candles_view(component_store)?)
+                .or(list_corporate_actions_view(component_store)?)
+                .or(write_corporate_actions_view(component_store)?),
         )
         .with(cors);
 
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::indicators::Bollinger;
+use crate::models::instruments::Figi;
+use crate::models::params::{ParamDefinition, ParamError, ParamType, ParamValue};
+use crate::models::strategy::{
+    ExecutionContext, InstantiateStrategyError, Strategy, StrategyDefinition,
+    StrategyExecutionError, StrategyFactory, StrategyState,
+};
+
+use super::{get_float_param, get_instrument_param, get_period_param};
+
+const PARAM_NAME_INSTRUMENT: &str = "instrument";
+const PARAM_NAME_PERIOD: &str = "period";
+const PARAM_NAME_WIDTH: &str = "width";
+const PARAM_NAME_SQUEEZE_BANDWIDTH: &str = "squeeze_bandwidth";
+
+const DEFAULT_PERIOD: usize = 20;
+const DEFAULT_WIDTH: f64 = 2.0;
+const DEFAULT_SQUEEZE_BANDWIDTH: f64 = 0.05;
+
+/// Buys when close breaks above the upper band right after the bands
+/// have been squeezed, holds the instrument until close falls below the middle band
+pub struct BollingerSqueeze {
+    figi: Figi,
+    bollinger: Bollinger,
+    squeeze_bandwidth: f64,
+    data_requirements: [Figi; 1],
+}
+
+impl BollingerSqueeze {
+    pub fn new(figi: Figi, period: usize, width: f64, squeeze_bandwidth: f64) -> Self {
+        Self {
+            figi: figi.clone(),
+            bollinger: Bollinger::new(figi.clone(), period, width),
+            squeeze_bandwidth,
+            data_requirements: [figi],
+        }
+    }
+}
+
+impl Strategy for BollingerSqueeze {
+    fn data_requirements(&self) -> &[Figi] {
+        &self.data_requirements
+    }
+
+    fn execute(
+        &self,
+        context: &ExecutionContext,
+        mut state: StrategyState,
+    ) -> Result<StrategyState, StrategyExecutionError> {
+        let candle = match context.candle(&self.figi) {
+            Some(candle) => candle,
+            None => return Ok(state),
+        };
+
+        let prev_bands = state.indicator_value(&self.bollinger);
+        let bands = state.update_indicator(&self.bollinger, candle)?;
+
+        let (prev_bands, bands) = match (prev_bands, bands) {
+            (Some(prev_bands), Some(bands)) => (prev_bands, bands),
+            _ => return Ok(state),
+        };
+
+        let squeezed = prev_bands.bandwidth() < self.squeeze_bandwidth;
+
+        let signal = if squeezed && candle.close > prev_bands.upper {
+            1.0
+        } else if candle.close < bands.middle {
+            0.0
+        } else {
+            state.signals().get(&self.figi).copied().unwrap_or(0.0)
+        };
+
+        state.set_signal(self.figi.to_owned(), signal);
+        Ok(state)
+    }
+}
+
+pub struct BollingerSqueezeFactory {
+    definition: StrategyDefinition,
+}
+
+impl Default for BollingerSqueezeFactory {
+    fn default() -> Self {
+        Self {
+            definition: StrategyDefinition::new(
+                vec![
+                    ParamDefinition::new(
+                        PARAM_NAME_INSTRUMENT,
+                        "Instrument to trade",
+                        ParamType::Instrument,
+                        None,
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_PERIOD,
+                        "Length of moving average in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_WIDTH,
+                        "Distance from middle to outer bands in standard deviations",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_WIDTH)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_SQUEEZE_BANDWIDTH,
+                        "Bands are squeezed when their distance relative to middle band is below this value",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_SQUEEZE_BANDWIDTH)),
+                    ),
+                ],
+                "BollingerSqueeze",
+                "Buys instrument on breakout after a period of low volatility",
+            ),
+        }
+    }
+}
+
+impl StrategyFactory for BollingerSqueezeFactory {
+    fn strategy_name(&self) -> &str {
+        "BollingerSqueeze"
+    }
+
+    fn definition(&self) -> &StrategyDefinition {
+        &self.definition
+    }
+
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError> {
+        let figi = get_instrument_param(params, PARAM_NAME_INSTRUMENT)?;
+        let period = get_period_param(params, PARAM_NAME_PERIOD, DEFAULT_PERIOD)?;
+        let width = get_float_param(params, PARAM_NAME_WIDTH, DEFAULT_WIDTH)?;
+        let squeeze_bandwidth = get_float_param(
+            params,
+            PARAM_NAME_SQUEEZE_BANDWIDTH,
+            DEFAULT_SQUEEZE_BANDWIDTH,
+        )?;
+
+        if width <= 0.0 {
+            return Err(ParamError::InvalidParam(PARAM_NAME_WIDTH.to_owned()).into());
+        }
+
+        if squeeze_bandwidth <= 0.0 {
+            return Err(ParamError::InvalidParam(PARAM_NAME_SQUEEZE_BANDWIDTH.to_owned()).into());
+        }
+
+        Ok(Arc::new(BollingerSqueeze::new(
+            figi,
+            period,
+            width,
+            squeeze_bandwidth,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::strategies::test_data::run;
+
+    #[test]
+    fn test_bollinger_squeeze() {
+        let figi = Figi("FIGI".to_owned());
+        let strategy = BollingerSqueeze::new(figi.clone(), 3, 2.0, 0.05);
+
+        let signals = run(
+            &strategy,
+            &figi,
+            &[10.0, 10.0, 10.0, 11.0, 11.5, 10.5, 12.0],
+        );
+
+        assert_eq!(
+            signals,
+            vec![None, None, None, Some(1.0), Some(1.0), Some(0.0), Some(0.0)]
+        );
+    }
+}
@@ -1,16 +1,15 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use chrono::prelude::*;
-
 use crate::models::instruments::Figi;
-use crate::models::market_data::CandlePack;
-use crate::models::params::{ParamDefinition, ParamError, ParamType, ParamValue};
+use crate::models::params::{ParamDefinition, ParamType, ParamValue};
 use crate::models::strategy::{
-    InstantiateStrategyError, Strategy, StrategyDefinition, StrategyExecutionError,
-    StrategyFactory, StrategyState,
+    ExecutionContext, InstantiateStrategyError, Strategy, StrategyDefinition,
+    StrategyExecutionError, StrategyFactory, StrategyState,
 };
 
+use super::get_instrument_param;
+
 const PARAM_NAME_INSTRUMENT: &str = "instrument";
 
 pub struct BuyAndHold {
@@ -34,8 +33,7 @@ impl Strategy for BuyAndHold {
 
     fn execute(
         &self,
-        _ts: DateTime<Utc>,
-        _candles: CandlePack,
+        _context: &ExecutionContext,
         mut state: StrategyState,
     ) -> Result<StrategyState, StrategyExecutionError> {
         state.set_signal(self.figi.to_owned(), 1.0f64);
@@ -77,14 +75,8 @@ impl StrategyFactory for BuyAndHoldFactory {
         &self,
         params: &HashMap<String, ParamValue>,
     ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError> {
-        let instrument_param = params
-            .get(PARAM_NAME_INSTRUMENT)
-            .ok_or_else(|| ParamError::ParamMissing(PARAM_NAME_INSTRUMENT.to_string()))?;
-
-        let figi = instrument_param
-            .as_instrument()
-            .ok_or_else(|| ParamError::ParamTypeMismatch(PARAM_NAME_INSTRUMENT.to_owned()))?;
+        let figi = get_instrument_param(params, PARAM_NAME_INSTRUMENT)?;
 
-        Ok(Arc::new(BuyAndHold::new(figi.to_owned())))
+        Ok(Arc::new(BuyAndHold::new(figi)))
     }
 }
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::indicators::Donchian;
+use crate::models::instruments::Figi;
+use crate::models::params::{ParamDefinition, ParamType, ParamValue};
+use crate::models::strategy::{
+    ExecutionContext, InstantiateStrategyError, Strategy, StrategyDefinition,
+    StrategyExecutionError, StrategyFactory, StrategyState,
+};
+
+use super::{get_instrument_param, get_period_param};
+
+const PARAM_NAME_INSTRUMENT: &str = "instrument";
+const PARAM_NAME_ENTRY_PERIOD: &str = "entry_period";
+const PARAM_NAME_EXIT_PERIOD: &str = "exit_period";
+
+const DEFAULT_ENTRY_PERIOD: usize = 20;
+const DEFAULT_EXIT_PERIOD: usize = 10;
+
+/// Buys when close breaks above the highest high of the entry channel
+/// and sells when it breaks below the lowest low of the exit channel.
+/// Channels are taken from the candles preceding the current one.
+pub struct DonchianBreakout {
+    figi: Figi,
+    entry: Donchian,
+    exit: Donchian,
+    data_requirements: [Figi; 1],
+}
+
+impl DonchianBreakout {
+    pub fn new(figi: Figi, entry_period: usize, exit_period: usize) -> Self {
+        Self {
+            figi: figi.clone(),
+            entry: Donchian::new(figi.clone(), entry_period),
+            exit: Donchian::new(figi.clone(), exit_period),
+            data_requirements: [figi],
+        }
+    }
+}
+
+impl Strategy for DonchianBreakout {
+    fn data_requirements(&self) -> &[Figi] {
+        &self.data_requirements
+    }
+
+    fn execute(
+        &self,
+        context: &ExecutionContext,
+        mut state: StrategyState,
+    ) -> Result<StrategyState, StrategyExecutionError> {
+        let candle = match context.candle(&self.figi) {
+            Some(candle) => candle,
+            None => return Ok(state),
+        };
+
+        let entry = state.indicator_value(&self.entry);
+        let exit = state.indicator_value(&self.exit);
+
+        state.update_indicator(&self.entry, candle)?;
+        state.update_indicator(&self.exit, candle)?;
+
+        let entry = match entry {
+            Some(entry) => entry,
+            None => return Ok(state),
+        };
+
+        let signal = if candle.close > entry.upper {
+            1.0
+        } else if exit.is_some_and(|exit| candle.close < exit.lower) {
+            0.0
+        } else {
+            state.signals().get(&self.figi).copied().unwrap_or(0.0)
+        };
+
+        state.set_signal(self.figi.to_owned(), signal);
+        Ok(state)
+    }
+}
+
+pub struct DonchianBreakoutFactory {
+    definition: StrategyDefinition,
+}
+
+impl Default for DonchianBreakoutFactory {
+    fn default() -> Self {
+        Self {
+            definition: StrategyDefinition::new(
+                vec![
+                    ParamDefinition::new(
+                        PARAM_NAME_INSTRUMENT,
+                        "Instrument to trade",
+                        ParamType::Instrument,
+                        None,
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_ENTRY_PERIOD,
+                        "Length of channel used for entries in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_ENTRY_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_EXIT_PERIOD,
+                        "Length of channel used for exits in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_EXIT_PERIOD as i64)),
+                    ),
+                ],
+                "DonchianBreakout",
+                "Buys instrument on breakout above recent highs and sells it on breakdown below recent lows",
+            ),
+        }
+    }
+}
+
+impl StrategyFactory for DonchianBreakoutFactory {
+    fn strategy_name(&self) -> &str {
+        "DonchianBreakout"
+    }
+
+    fn definition(&self) -> &StrategyDefinition {
+        &self.definition
+    }
+
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError> {
+        let figi = get_instrument_param(params, PARAM_NAME_INSTRUMENT)?;
+        let entry_period = get_period_param(params, PARAM_NAME_ENTRY_PERIOD, DEFAULT_ENTRY_PERIOD)?;
+        let exit_period = get_period_param(params, PARAM_NAME_EXIT_PERIOD, DEFAULT_EXIT_PERIOD)?;
+
+        Ok(Arc::new(DonchianBreakout::new(
+            figi,
+            entry_period,
+            exit_period,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::strategies::test_data::run;
+
+    #[test]
+    fn test_donchian_breakout() {
+        let figi = Figi("FIGI".to_owned());
+        let strategy = DonchianBreakout::new(figi.clone(), 3, 2);
+
+        let signals = run(
+            &strategy,
+            &figi,
+            &[10.0, 10.0, 10.0, 11.0, 10.8, 10.0, 10.2],
+        );
+
+        assert_eq!(
+            signals,
+            vec![None, None, None, Some(1.0), Some(1.0), Some(0.0), Some(0.0)]
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::indicators::{Ema, Sma, Wma};
+use crate::models::instruments::Figi;
+use crate::models::market_data::{Candle, CandleResolution};
+use crate::models::params::{ParamDefinition, ParamError, ParamType, ParamValue};
+use crate::models::strategy::{
+    ExecutionContext, InstantiateStrategyError, Strategy, StrategyDefinition,
+    StrategyExecutionError, StrategyFactory, StrategyState,
+};
+
+use super::{get_boolean_param, get_instrument_param, get_period_param};
+
+const PARAM_NAME_INSTRUMENT: &str = "instrument";
+const PARAM_NAME_FAST_PERIOD: &str = "fast_period";
+const PARAM_NAME_SLOW_PERIOD: &str = "slow_period";
+const PARAM_NAME_EXPONENTIAL: &str = "exponential";
+const PARAM_NAME_WEIGHTED: &str = "weighted";
+const PARAM_NAME_DAILY_TREND_FILTER: &str = "daily_trend_filter";
+
+const DEFAULT_FAST_PERIOD: usize = 10;
+const DEFAULT_SLOW_PERIOD: usize = 30;
+const DEFAULT_EXPONENTIAL: bool = false;
+const DEFAULT_WEIGHTED: bool = false;
+const DEFAULT_DAILY_TREND_FILTER: bool = false;
+
+#[derive(Debug, Copy, Clone)]
+pub enum AverageType {
+    Simple,
+    Exponential,
+    Weighted,
+}
+
+enum MovingAverage {
+    Simple(Sma),
+    Exponential(Ema),
+    Weighted(Wma),
+}
+
+impl MovingAverage {
+    fn new(figi: Figi, period: usize, average_type: AverageType) -> Self {
+        match average_type {
+            AverageType::Simple => Self::Simple(Sma::new(figi, period)),
+            AverageType::Exponential => Self::Exponential(Ema::new(figi, period)),
+            AverageType::Weighted => Self::Weighted(Wma::new(figi, period)),
+        }
+    }
+
+    fn update(
+        &self,
+        state: &mut StrategyState,
+        candle: &Candle,
+    ) -> Result<Option<f64>, StrategyExecutionError> {
+        match self {
+            Self::Simple(sma) => state.update_indicator(sma, candle),
+            Self::Exponential(ema) => state.update_indicator(ema, candle),
+            Self::Weighted(wma) => state.update_indicator(wma, candle),
+        }
+    }
+}
+
+/// Holds the instrument while fast moving average is above slow one.
+/// With the daily trend filter it also has to trade above the close of the last closed day.
+pub struct MaCrossover {
+    figi: Figi,
+    fast: MovingAverage,
+    slow: MovingAverage,
+    data_requirements: [Figi; 1],
+    series_requirements: Vec<(Figi, CandleResolution)>,
+}
+
+impl MaCrossover {
+    pub fn new(
+        figi: Figi,
+        fast_period: usize,
+        slow_period: usize,
+        average_type: AverageType,
+    ) -> Self {
+        Self {
+            figi: figi.clone(),
+            fast: MovingAverage::new(figi.clone(), fast_period, average_type),
+            slow: MovingAverage::new(figi.clone(), slow_period, average_type),
+            data_requirements: [figi],
+            series_requirements: vec![],
+        }
+    }
+
+    pub fn with_daily_trend_filter(mut self) -> Self {
+        self.series_requirements = vec![(self.figi.clone(), CandleResolution::OneDay)];
+        self
+    }
+
+    fn is_trend_up(&self, context: &ExecutionContext, candle: &Candle) -> bool {
+        match self.series_requirements.is_empty() {
+            true => true,
+            false => context
+                .series_candle(&self.figi, CandleResolution::OneDay)
+                .is_some_and(|daily| candle.close > daily.close),
+        }
+    }
+}
+
+impl Strategy for MaCrossover {
+    fn data_requirements(&self) -> &[Figi] {
+        &self.data_requirements
+    }
+
+    fn series_requirements(&self) -> &[(Figi, CandleResolution)] {
+        &self.series_requirements
+    }
+
+    fn execute(
+        &self,
+        context: &ExecutionContext,
+        mut state: StrategyState,
+    ) -> Result<StrategyState, StrategyExecutionError> {
+        let candle = match context.candle(&self.figi) {
+            Some(candle) => candle,
+            None => return Ok(state),
+        };
+
+        let fast = self.fast.update(&mut state, candle)?;
+        let slow = self.slow.update(&mut state, candle)?;
+
+        if let (Some(fast), Some(slow)) = (fast, slow) {
+            let signal = match fast > slow && self.is_trend_up(context, candle) {
+                true => 1.0,
+                false => 0.0,
+            };
+
+            state.set_signal(self.figi.to_owned(), signal);
+        }
+
+        Ok(state)
+    }
+}
+
+pub struct MaCrossoverFactory {
+    definition: StrategyDefinition,
+}
+
+impl Default for MaCrossoverFactory {
+    fn default() -> Self {
+        Self {
+            definition: StrategyDefinition::new(
+                vec![
+                    ParamDefinition::new(
+                        PARAM_NAME_INSTRUMENT,
+                        "Instrument to trade",
+                        ParamType::Instrument,
+                        None,
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_FAST_PERIOD,
+                        "Length of fast moving average in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_FAST_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_SLOW_PERIOD,
+                        "Length of slow moving average in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_SLOW_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_EXPONENTIAL,
+                        "Use exponential moving averages instead of simple ones",
+                        ParamType::Boolean,
+                        Some(ParamValue::Boolean(DEFAULT_EXPONENTIAL)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_WEIGHTED,
+                        "Use linearly weighted moving averages instead of simple ones",
+                        ParamType::Boolean,
+                        Some(ParamValue::Boolean(DEFAULT_WEIGHTED)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_DAILY_TREND_FILTER,
+                        "Hold only above the close of the last closed day, requires an intraday resolution",
+                        ParamType::Boolean,
+                        Some(ParamValue::Boolean(DEFAULT_DAILY_TREND_FILTER)),
+                    ),
+                ],
+                "MaCrossover",
+                "Buys instrument when fast moving average crosses above slow one and sells when it crosses back below",
+            ),
+        }
+    }
+}
+
+impl StrategyFactory for MaCrossoverFactory {
+    fn strategy_name(&self) -> &str {
+        "MaCrossover"
+    }
+
+    fn definition(&self) -> &StrategyDefinition {
+        &self.definition
+    }
+
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError> {
+        let figi = get_instrument_param(params, PARAM_NAME_INSTRUMENT)?;
+        let fast_period = get_period_param(params, PARAM_NAME_FAST_PERIOD, DEFAULT_FAST_PERIOD)?;
+        let slow_period = get_period_param(params, PARAM_NAME_SLOW_PERIOD, DEFAULT_SLOW_PERIOD)?;
+        let exponential = get_boolean_param(params, PARAM_NAME_EXPONENTIAL, DEFAULT_EXPONENTIAL)?;
+        let weighted = get_boolean_param(params, PARAM_NAME_WEIGHTED, DEFAULT_WEIGHTED)?;
+        let daily_trend_filter = get_boolean_param(
+            params,
+            PARAM_NAME_DAILY_TREND_FILTER,
+            DEFAULT_DAILY_TREND_FILTER,
+        )?;
+
+        if fast_period >= slow_period {
+            return Err(ParamError::InvalidParam(PARAM_NAME_FAST_PERIOD.to_owned()).into());
+        }
+
+        let average_type = match (exponential, weighted) {
+            (false, false) => AverageType::Simple,
+            (true, false) => AverageType::Exponential,
+            (false, true) => AverageType::Weighted,
+            (true, true) => {
+                return Err(ParamError::InvalidParam(PARAM_NAME_WEIGHTED.to_owned()).into())
+            }
+        };
+
+        let strategy = MaCrossover::new(figi, fast_period, slow_period, average_type);
+
+        Ok(Arc::new(match daily_trend_filter {
+            true => strategy.with_daily_trend_filter(),
+            false => strategy,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::strategies::test_data::run;
+
+    #[test]
+    fn test_ma_crossover() {
+        let figi = Figi("FIGI".to_owned());
+        let strategy = MaCrossover::new(figi.clone(), 2, 4, AverageType::Simple);
+
+        let signals = run(
+            &strategy,
+            &figi,
+            &[10.0, 10.0, 10.0, 10.0, 11.0, 12.0, 11.0, 9.0, 8.0],
+        );
+
+        assert_eq!(
+            signals,
+            vec![
+                None,
+                None,
+                None,
+                Some(0.0),
+                Some(1.0),
+                Some(1.0),
+                Some(1.0),
+                Some(0.0),
+                Some(0.0)
+            ]
+        );
+    }
+}
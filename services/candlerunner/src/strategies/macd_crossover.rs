@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::indicators::Macd;
+use crate::models::instruments::Figi;
+use crate::models::params::{ParamDefinition, ParamError, ParamType, ParamValue};
+use crate::models::strategy::{
+    ExecutionContext, InstantiateStrategyError, Strategy, StrategyDefinition,
+    StrategyExecutionError, StrategyFactory, StrategyState,
+};
+
+use super::{get_instrument_param, get_period_param};
+
+const PARAM_NAME_INSTRUMENT: &str = "instrument";
+const PARAM_NAME_FAST_PERIOD: &str = "fast_period";
+const PARAM_NAME_SLOW_PERIOD: &str = "slow_period";
+const PARAM_NAME_SIGNAL_PERIOD: &str = "signal_period";
+
+const DEFAULT_FAST_PERIOD: usize = 12;
+const DEFAULT_SLOW_PERIOD: usize = 26;
+const DEFAULT_SIGNAL_PERIOD: usize = 9;
+
+/// Holds the instrument while MACD line is above its signal line
+pub struct MacdCrossover {
+    figi: Figi,
+    macd: Macd,
+    data_requirements: [Figi; 1],
+}
+
+impl MacdCrossover {
+    pub fn new(figi: Figi, fast_period: usize, slow_period: usize, signal_period: usize) -> Self {
+        Self {
+            figi: figi.clone(),
+            macd: Macd::new(figi.clone(), fast_period, slow_period, signal_period),
+            data_requirements: [figi],
+        }
+    }
+}
+
+impl Strategy for MacdCrossover {
+    fn data_requirements(&self) -> &[Figi] {
+        &self.data_requirements
+    }
+
+    fn execute(
+        &self,
+        context: &ExecutionContext,
+        mut state: StrategyState,
+    ) -> Result<StrategyState, StrategyExecutionError> {
+        let candle = match context.candle(&self.figi) {
+            Some(candle) => candle,
+            None => return Ok(state),
+        };
+
+        if let Some(macd) = state.update_indicator(&self.macd, candle)? {
+            let signal = match macd.histogram > 0.0 {
+                true => 1.0,
+                false => 0.0,
+            };
+
+            state.set_signal(self.figi.to_owned(), signal);
+        }
+
+        Ok(state)
+    }
+}
+
+pub struct MacdCrossoverFactory {
+    definition: StrategyDefinition,
+}
+
+impl Default for MacdCrossoverFactory {
+    fn default() -> Self {
+        Self {
+            definition: StrategyDefinition::new(
+                vec![
+                    ParamDefinition::new(
+                        PARAM_NAME_INSTRUMENT,
+                        "Instrument to trade",
+                        ParamType::Instrument,
+                        None,
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_FAST_PERIOD,
+                        "Length of fast exponential moving average in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_FAST_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_SLOW_PERIOD,
+                        "Length of slow exponential moving average in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_SLOW_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_SIGNAL_PERIOD,
+                        "Length of MACD signal line in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_SIGNAL_PERIOD as i64)),
+                    ),
+                ],
+                "MacdCrossover",
+                "Buys instrument when MACD crosses above its signal line and sells when it crosses back below",
+            ),
+        }
+    }
+}
+
+impl StrategyFactory for MacdCrossoverFactory {
+    fn strategy_name(&self) -> &str {
+        "MacdCrossover"
+    }
+
+    fn definition(&self) -> &StrategyDefinition {
+        &self.definition
+    }
+
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError> {
+        let figi = get_instrument_param(params, PARAM_NAME_INSTRUMENT)?;
+        let fast_period = get_period_param(params, PARAM_NAME_FAST_PERIOD, DEFAULT_FAST_PERIOD)?;
+        let slow_period = get_period_param(params, PARAM_NAME_SLOW_PERIOD, DEFAULT_SLOW_PERIOD)?;
+        let signal_period =
+            get_period_param(params, PARAM_NAME_SIGNAL_PERIOD, DEFAULT_SIGNAL_PERIOD)?;
+
+        if fast_period >= slow_period {
+            return Err(ParamError::InvalidParam(PARAM_NAME_FAST_PERIOD.to_owned()).into());
+        }
+
+        Ok(Arc::new(MacdCrossover::new(
+            figi,
+            fast_period,
+            slow_period,
+            signal_period,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::strategies::test_data::run;
+
+    #[test]
+    fn test_macd_crossover() {
+        let figi = Figi("FIGI".to_owned());
+        let strategy = MacdCrossover::new(figi.clone(), 2, 3, 2);
+
+        let signals = run(
+            &strategy,
+            &figi,
+            &[10.0, 10.0, 10.0, 10.0, 11.0, 12.0, 11.0, 10.0],
+        );
+
+        assert_eq!(
+            signals,
+            vec![
+                None,
+                None,
+                None,
+                Some(0.0),
+                Some(1.0),
+                Some(1.0),
+                Some(0.0),
+                Some(0.0)
+            ]
+        );
+    }
+}
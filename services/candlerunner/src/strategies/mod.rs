@@ -1,3 +1,140 @@
+mod bollinger_squeeze;
 mod buy_and_hold;
+mod donchian_breakout;
+mod ma_crossover;
+mod macd_crossover;
+mod momentum_rotation;
+mod pairs_trading;
+mod rsi_mean_reversion;
+mod stochastic_reversal;
+mod vwap_reversion;
 
-pub use buy_and_hold::{BuyAndHold, BuyAndHoldFactory};
+use std::collections::HashMap;
+
+use crate::models::instruments::Figi;
+use crate::models::params::{ParamError, ParamValue};
+
+pub use bollinger_squeeze::BollingerSqueezeFactory;
+pub use buy_and_hold::BuyAndHoldFactory;
+pub use donchian_breakout::DonchianBreakoutFactory;
+pub use ma_crossover::MaCrossoverFactory;
+pub use macd_crossover::MacdCrossoverFactory;
+pub use momentum_rotation::MomentumRotationFactory;
+pub use pairs_trading::PairsTradingFactory;
+pub use rsi_mean_reversion::RsiMeanReversionFactory;
+pub use stochastic_reversal::StochasticReversalFactory;
+pub use vwap_reversion::VwapReversionFactory;
+
+fn get_instrument_param(
+    params: &HashMap<String, ParamValue>,
+    name: &str,
+) -> Result<Figi, ParamError> {
+    get_opt_instrument_param(params, name)?.ok_or_else(|| ParamError::ParamMissing(name.to_owned()))
+}
+
+fn get_opt_instrument_param(
+    params: &HashMap<String, ParamValue>,
+    name: &str,
+) -> Result<Option<Figi>, ParamError> {
+    match params.get(name) {
+        Some(value) => value
+            .as_instrument()
+            .map(|figi| Some(figi.to_owned()))
+            .ok_or_else(|| ParamError::ParamTypeMismatch(name.to_owned())),
+        None => Ok(None),
+    }
+}
+
+fn get_float_param(
+    params: &HashMap<String, ParamValue>,
+    name: &str,
+    default: f64,
+) -> Result<f64, ParamError> {
+    match params.get(name) {
+        Some(value) => value
+            .as_float()
+            .ok_or_else(|| ParamError::ParamTypeMismatch(name.to_owned())),
+        None => Ok(default),
+    }
+}
+
+fn get_boolean_param(
+    params: &HashMap<String, ParamValue>,
+    name: &str,
+    default: bool,
+) -> Result<bool, ParamError> {
+    match params.get(name) {
+        Some(value) => value
+            .as_boolean()
+            .ok_or_else(|| ParamError::ParamTypeMismatch(name.to_owned())),
+        None => Ok(default),
+    }
+}
+
+/// Reads positive integer parameter, e.g. length of indicator window
+fn get_period_param(
+    params: &HashMap<String, ParamValue>,
+    name: &str,
+    default: usize,
+) -> Result<usize, ParamError> {
+    let period = match params.get(name) {
+        Some(value) => value
+            .as_integer()
+            .ok_or_else(|| ParamError::ParamTypeMismatch(name.to_owned()))?,
+        None => return Ok(default),
+    };
+
+    match period > 0 {
+        true => Ok(period as usize),
+        false => Err(ParamError::InvalidParam(name.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod test_data {
+    use std::collections::HashMap;
+
+    use chrono::prelude::*;
+
+    use crate::models::instruments::Figi;
+    use crate::models::market_data::{Candle, CandlePack};
+    use crate::models::strategy::{ExecutionContext, Strategy, StrategyState};
+
+    pub fn candle(close: f64) -> Candle {
+        Candle {
+            open: close,
+            high: close + 0.5,
+            low: close - 0.5,
+            close,
+            volume: 1000,
+        }
+    }
+
+    /// Executes strategy over a single instrument close prices
+    /// and collects signal after every candle
+    pub fn run(strategy: &dyn Strategy, figi: &Figi, closes: &[f64]) -> Vec<Option<f64>> {
+        let packs = closes
+            .iter()
+            .map(|close| [(figi.clone(), candle(*close))].into_iter().collect())
+            .collect::<Vec<CandlePack>>();
+
+        run_packs(strategy, packs)
+            .into_iter()
+            .map(|signals| signals.get(figi).copied())
+            .collect()
+    }
+
+    pub fn run_packs(strategy: &dyn Strategy, packs: Vec<CandlePack>) -> Vec<HashMap<Figi, f64>> {
+        let mut state = StrategyState::default();
+        let mut signals = Vec::new();
+
+        for (i, pack) in packs.into_iter().enumerate() {
+            let ts = Utc.timestamp_opt(i as i64 * 60, 0).unwrap();
+            let context = ExecutionContext::new(ts, pack);
+            state = strategy.execute(&context, state).unwrap();
+            signals.push(state.signals().clone());
+        }
+
+        signals
+    }
+}
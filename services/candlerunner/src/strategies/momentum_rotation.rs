@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::indicators::Roc;
+use crate::models::instruments::Figi;
+use crate::models::params::{ParamDefinition, ParamError, ParamType, ParamValue};
+use crate::models::strategy::{
+    ExecutionContext, InstantiateStrategyError, Strategy, StrategyDefinition,
+    StrategyExecutionError, StrategyFactory, StrategyState,
+};
+
+use super::{get_instrument_param, get_opt_instrument_param, get_period_param};
+
+const PARAM_NAMES_INSTRUMENT: [&str; 5] = [
+    "instrument_1",
+    "instrument_2",
+    "instrument_3",
+    "instrument_4",
+    "instrument_5",
+];
+
+/// Number of instruments which have to be specified
+const REQUIRED_INSTRUMENTS: usize = 2;
+
+const PARAM_NAME_LOOKBACK: &str = "lookback";
+const PARAM_NAME_TOP_COUNT: &str = "top_count";
+
+const DEFAULT_LOOKBACK: usize = 20;
+const DEFAULT_TOP_COUNT: usize = 1;
+
+/// Holds `top_count` instruments with the highest positive rate of change
+/// over the last `lookback` candles
+pub struct MomentumRotation {
+    instruments: Vec<Figi>,
+    momentums: Vec<Roc>,
+    top_count: usize,
+}
+
+impl MomentumRotation {
+    pub fn new(instruments: Vec<Figi>, lookback: usize, top_count: usize) -> Self {
+        Self {
+            momentums: instruments
+                .iter()
+                .map(|figi| Roc::new(figi.clone(), lookback))
+                .collect(),
+            instruments,
+            top_count,
+        }
+    }
+}
+
+impl Strategy for MomentumRotation {
+    fn data_requirements(&self) -> &[Figi] {
+        &self.instruments
+    }
+
+    fn execute(
+        &self,
+        context: &ExecutionContext,
+        mut state: StrategyState,
+    ) -> Result<StrategyState, StrategyExecutionError> {
+        let mut ranking = Vec::with_capacity(self.instruments.len());
+
+        for (figi, momentum) in self.instruments.iter().zip(&self.momentums) {
+            let value = match context.candle(figi) {
+                Some(candle) => state.update_indicator(momentum, candle)?,
+                None => state.indicator_value(momentum),
+            };
+
+            ranking.extend(value.map(|value| (figi, value)));
+        }
+
+        // Rotate only when every instrument can be compared
+        if ranking.len() < self.instruments.len() {
+            return Ok(state);
+        }
+
+        ranking.sort_by(|lhs, rhs| rhs.1.total_cmp(&lhs.1).then_with(|| lhs.0.cmp(rhs.0)));
+
+        for (position, (figi, value)) in ranking.into_iter().enumerate() {
+            let signal = match position < self.top_count && value > 0.0 {
+                true => 1.0,
+                false => 0.0,
+            };
+
+            state.set_signal(figi.to_owned(), signal);
+        }
+
+        Ok(state)
+    }
+}
+
+pub struct MomentumRotationFactory {
+    definition: StrategyDefinition,
+}
+
+impl Default for MomentumRotationFactory {
+    fn default() -> Self {
+        let instruments = PARAM_NAMES_INSTRUMENT.iter().enumerate().map(|(i, name)| {
+            let definition = ParamDefinition::new(
+                name,
+                "Instrument to rotate between",
+                ParamType::Instrument,
+                None,
+            );
+
+            match i < REQUIRED_INSTRUMENTS {
+                true => definition,
+                false => definition.optional(),
+            }
+        });
+
+        Self {
+            definition: StrategyDefinition::new(
+                instruments
+                    .chain([
+                        ParamDefinition::new(
+                            PARAM_NAME_LOOKBACK,
+                            "Number of candles momentum is measured over",
+                            ParamType::Integer,
+                            Some(ParamValue::Integer(DEFAULT_LOOKBACK as i64)),
+                        ),
+                        ParamDefinition::new(
+                            PARAM_NAME_TOP_COUNT,
+                            "Number of instruments held at once",
+                            ParamType::Integer,
+                            Some(ParamValue::Integer(DEFAULT_TOP_COUNT as i64)),
+                        ),
+                    ])
+                    .collect(),
+                "MomentumRotation",
+                "Holds instruments with the strongest positive momentum and rotates out of the others",
+            ),
+        }
+    }
+}
+
+impl StrategyFactory for MomentumRotationFactory {
+    fn strategy_name(&self) -> &str {
+        "MomentumRotation"
+    }
+
+    fn definition(&self) -> &StrategyDefinition {
+        &self.definition
+    }
+
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError> {
+        let mut instruments: Vec<Figi> = Vec::new();
+
+        for (i, name) in PARAM_NAMES_INSTRUMENT.iter().enumerate() {
+            let figi = match i < REQUIRED_INSTRUMENTS {
+                true => get_instrument_param(params, name)?,
+                false => match get_opt_instrument_param(params, name)? {
+                    Some(figi) => figi,
+                    None => continue,
+                },
+            };
+
+            if instruments.contains(&figi) {
+                return Err(ParamError::InvalidParam(name.to_string()).into());
+            }
+
+            instruments.push(figi);
+        }
+
+        let lookback = get_period_param(params, PARAM_NAME_LOOKBACK, DEFAULT_LOOKBACK)?;
+        let top_count = get_period_param(params, PARAM_NAME_TOP_COUNT, DEFAULT_TOP_COUNT)?;
+
+        if top_count > instruments.len() {
+            return Err(ParamError::InvalidParam(PARAM_NAME_TOP_COUNT.to_owned()).into());
+        }
+
+        Ok(Arc::new(MomentumRotation::new(
+            instruments,
+            lookback,
+            top_count,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::strategies::test_data::{candle, run_packs};
+
+    #[test]
+    fn test_momentum_rotation() {
+        let figis: Vec<Figi> = ["A", "B", "C"]
+            .iter()
+            .map(|figi| Figi(figi.to_string()))
+            .collect();
+        let strategy = MomentumRotation::new(figis.clone(), 1, 1);
+
+        let closes = [
+            [10.0, 10.0, 10.0],
+            [11.0, 10.0, 9.0],
+            [11.0, 12.0, 9.0],
+            [11.0, 12.0, 8.0],
+        ];
+
+        let packs = closes
+            .iter()
+            .map(|row| {
+                figis
+                    .iter()
+                    .cloned()
+                    .zip(row.iter().map(|close| candle(*close)))
+                    .collect()
+            })
+            .collect();
+
+        let signals: Vec<Vec<Option<f64>>> = run_packs(&strategy, packs)
+            .into_iter()
+            .map(|signals| {
+                figis
+                    .iter()
+                    .map(|figi| signals.get(figi).copied())
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(
+            signals,
+            vec![
+                vec![None, None, None],
+                vec![Some(1.0), Some(0.0), Some(0.0)],
+                vec![Some(0.0), Some(1.0), Some(0.0)],
+                vec![Some(0.0), Some(0.0), Some(0.0)],
+            ]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::indicators::Spread;
+use crate::models::instruments::Figi;
+use crate::models::params::{ParamDefinition, ParamError, ParamType, ParamValue};
+use crate::models::strategy::{
+    ExecutionContext, InstantiateStrategyError, Strategy, StrategyDefinition,
+    StrategyExecutionError, StrategyFactory, StrategyState,
+};
+
+use super::{get_float_param, get_instrument_param, get_period_param};
+
+const PARAM_NAME_FIRST_INSTRUMENT: &str = "first_instrument";
+const PARAM_NAME_SECOND_INSTRUMENT: &str = "second_instrument";
+const PARAM_NAME_PERIOD: &str = "period";
+const PARAM_NAME_ENTRY_Z_SCORE: &str = "entry_z_score";
+const PARAM_NAME_EXIT_Z_SCORE: &str = "exit_z_score";
+
+const DEFAULT_PERIOD: usize = 60;
+const DEFAULT_ENTRY_Z_SCORE: f64 = 2.0;
+const DEFAULT_EXIT_Z_SCORE: f64 = 0.5;
+
+/// Trades the spread between two instruments: buys the first instrument and sells
+/// the second one when the spread is unusually low and vice versa when it is unusually high.
+/// Both positions are closed once the spread returns close to its mean.
+pub struct PairsTrading {
+    first: Figi,
+    second: Figi,
+    spread: Spread,
+    entry_z_score: f64,
+    exit_z_score: f64,
+    data_requirements: [Figi; 2],
+}
+
+impl PairsTrading {
+    pub fn new(
+        first: Figi,
+        second: Figi,
+        period: usize,
+        entry_z_score: f64,
+        exit_z_score: f64,
+    ) -> Self {
+        Self {
+            spread: Spread::new(first.clone(), second.clone(), period),
+            entry_z_score,
+            exit_z_score,
+            data_requirements: [first.clone(), second.clone()],
+            first,
+            second,
+        }
+    }
+}
+
+impl Strategy for PairsTrading {
+    fn data_requirements(&self) -> &[Figi] {
+        &self.data_requirements
+    }
+
+    fn execute(
+        &self,
+        context: &ExecutionContext,
+        mut state: StrategyState,
+    ) -> Result<StrategyState, StrategyExecutionError> {
+        // Spread is only meaningful for prices observed at the same time,
+        // so candles with a missing leg are skipped and signals are kept as they are
+        let prices = match (context.candle(&self.first), context.candle(&self.second)) {
+            (Some(first), Some(second)) => (first.close, second.close),
+            _ => return Ok(state),
+        };
+
+        let z_score = match state.update_indicator(&self.spread, &prices)? {
+            Some(value) => value.z_score,
+            None => return Ok(state),
+        };
+
+        let signal = if z_score > self.entry_z_score {
+            -1.0
+        } else if z_score < -self.entry_z_score {
+            1.0
+        } else if z_score.abs() < self.exit_z_score {
+            0.0
+        } else {
+            state.signals().get(&self.first).copied().unwrap_or(0.0)
+        };
+
+        state.set_signal(self.first.to_owned(), signal);
+        state.set_signal(self.second.to_owned(), -signal);
+        Ok(state)
+    }
+}
+
+pub struct PairsTradingFactory {
+    definition: StrategyDefinition,
+}
+
+impl Default for PairsTradingFactory {
+    fn default() -> Self {
+        Self {
+            definition: StrategyDefinition::new(
+                vec![
+                    ParamDefinition::new(
+                        PARAM_NAME_FIRST_INSTRUMENT,
+                        "First instrument of the pair",
+                        ParamType::Instrument,
+                        None,
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_SECOND_INSTRUMENT,
+                        "Second instrument of the pair, used to hedge the first one",
+                        ParamType::Instrument,
+                        None,
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_PERIOD,
+                        "Number of candles hedge ratio and spread statistics are estimated over",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_ENTRY_Z_SCORE,
+                        "Spread z-score at which positions are opened",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_ENTRY_Z_SCORE)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_EXIT_Z_SCORE,
+                        "Spread z-score at which positions are closed",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_EXIT_Z_SCORE)),
+                    ),
+                ],
+                "PairsTrading",
+                "Trades mean reversion of the spread between two related instruments",
+            ),
+        }
+    }
+}
+
+impl StrategyFactory for PairsTradingFactory {
+    fn strategy_name(&self) -> &str {
+        "PairsTrading"
+    }
+
+    fn definition(&self) -> &StrategyDefinition {
+        &self.definition
+    }
+
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError> {
+        let first = get_instrument_param(params, PARAM_NAME_FIRST_INSTRUMENT)?;
+        let second = get_instrument_param(params, PARAM_NAME_SECOND_INSTRUMENT)?;
+        let period = get_period_param(params, PARAM_NAME_PERIOD, DEFAULT_PERIOD)?;
+        let entry_z_score =
+            get_float_param(params, PARAM_NAME_ENTRY_Z_SCORE, DEFAULT_ENTRY_Z_SCORE)?;
+        let exit_z_score = get_float_param(params, PARAM_NAME_EXIT_Z_SCORE, DEFAULT_EXIT_Z_SCORE)?;
+
+        if first == second {
+            return Err(ParamError::InvalidParam(PARAM_NAME_SECOND_INSTRUMENT.to_owned()).into());
+        }
+
+        if period < 2 {
+            return Err(ParamError::InvalidParam(PARAM_NAME_PERIOD.to_owned()).into());
+        }
+
+        if entry_z_score <= 0.0 {
+            return Err(ParamError::InvalidParam(PARAM_NAME_ENTRY_Z_SCORE.to_owned()).into());
+        }
+
+        if !(0.0..entry_z_score).contains(&exit_z_score) {
+            return Err(ParamError::InvalidParam(PARAM_NAME_EXIT_Z_SCORE.to_owned()).into());
+        }
+
+        Ok(Arc::new(PairsTrading::new(
+            first,
+            second,
+            period,
+            entry_z_score,
+            exit_z_score,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::models::market_data::CandlePack;
+    use crate::strategies::test_data::{candle, run_packs};
+
+    #[test]
+    fn test_pairs_trading() {
+        let first = Figi("FIRST".to_owned());
+        let second = Figi("SECOND".to_owned());
+        let strategy = PairsTrading::new(first.clone(), second.clone(), 5, 1.5, 0.5);
+
+        let first_closes = [
+            Some(20.0),
+            Some(20.5),
+            Some(20.2),
+            Some(20.5),
+            Some(20.8),
+            Some(20.4),
+            Some(19.2),
+            None,
+            Some(19.6),
+            Some(20.6),
+            Some(21.3),
+            Some(20.4),
+        ];
+        let second_closes = [
+            10.0, 10.2, 10.1, 10.3, 10.4, 10.2, 10.3, 10.4, 10.4, 10.3, 10.5, 10.4,
+        ];
+
+        let packs = first_closes
+            .iter()
+            .zip(second_closes)
+            .map(|(first_close, second_close)| {
+                let mut pack: CandlePack = [(second.clone(), candle(second_close))]
+                    .into_iter()
+                    .collect();
+                if let Some(first_close) = first_close {
+                    pack.insert(first.clone(), candle(*first_close));
+                }
+                pack
+            })
+            .collect();
+
+        let signals = run_packs(&strategy, packs);
+
+        for signals in &signals {
+            assert_eq!(
+                signals.get(&first).map(|signal| -signal),
+                signals.get(&second).copied()
+            );
+        }
+
+        assert_eq!(
+            signals
+                .iter()
+                .map(|signals| signals.get(&first).copied())
+                .collect::<Vec<_>>(),
+            vec![
+                None,
+                None,
+                None,
+                None,
+                Some(0.0),
+                Some(0.0),
+                Some(1.0),
+                Some(1.0),
+                Some(1.0),
+                Some(1.0),
+                Some(1.0),
+                Some(0.0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::indicators::Rsi;
+use crate::models::instruments::Figi;
+use crate::models::params::{ParamDefinition, ParamError, ParamType, ParamValue};
+use crate::models::strategy::{
+    ExecutionContext, InstantiateStrategyError, Strategy, StrategyDefinition,
+    StrategyExecutionError, StrategyFactory, StrategyState,
+};
+
+use super::{get_float_param, get_instrument_param, get_period_param};
+
+const PARAM_NAME_INSTRUMENT: &str = "instrument";
+const PARAM_NAME_PERIOD: &str = "period";
+const PARAM_NAME_OVERSOLD: &str = "oversold";
+const PARAM_NAME_OVERBOUGHT: &str = "overbought";
+
+const DEFAULT_PERIOD: usize = 14;
+const DEFAULT_OVERSOLD: f64 = 30.0;
+const DEFAULT_OVERBOUGHT: f64 = 70.0;
+
+/// Buys when RSI drops below oversold level and holds
+/// the instrument until RSI rises above overbought level
+pub struct RsiMeanReversion {
+    figi: Figi,
+    rsi: Rsi,
+    oversold: f64,
+    overbought: f64,
+    data_requirements: [Figi; 1],
+}
+
+impl RsiMeanReversion {
+    pub fn new(figi: Figi, period: usize, oversold: f64, overbought: f64) -> Self {
+        Self {
+            figi: figi.clone(),
+            rsi: Rsi::new(figi.clone(), period),
+            oversold,
+            overbought,
+            data_requirements: [figi],
+        }
+    }
+}
+
+impl Strategy for RsiMeanReversion {
+    fn data_requirements(&self) -> &[Figi] {
+        &self.data_requirements
+    }
+
+    fn execute(
+        &self,
+        context: &ExecutionContext,
+        mut state: StrategyState,
+    ) -> Result<StrategyState, StrategyExecutionError> {
+        let candle = match context.candle(&self.figi) {
+            Some(candle) => candle,
+            None => return Ok(state),
+        };
+
+        let rsi = match state.update_indicator(&self.rsi, candle)? {
+            Some(rsi) => rsi,
+            None => return Ok(state),
+        };
+
+        let prev_signal = state.signals().get(&self.figi).copied().unwrap_or(0.0);
+
+        let signal = if rsi < self.oversold {
+            1.0
+        } else if rsi > self.overbought {
+            0.0
+        } else {
+            prev_signal
+        };
+
+        state.set_signal(self.figi.to_owned(), signal);
+        Ok(state)
+    }
+}
+
+pub struct RsiMeanReversionFactory {
+    definition: StrategyDefinition,
+}
+
+impl Default for RsiMeanReversionFactory {
+    fn default() -> Self {
+        Self {
+            definition: StrategyDefinition::new(
+                vec![
+                    ParamDefinition::new(
+                        PARAM_NAME_INSTRUMENT,
+                        "Instrument to trade",
+                        ParamType::Instrument,
+                        None,
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_PERIOD,
+                        "Length of RSI in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_OVERSOLD,
+                        "RSI level below which instrument is bought",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_OVERSOLD)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_OVERBOUGHT,
+                        "RSI level above which instrument is sold",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_OVERBOUGHT)),
+                    ),
+                ],
+                "RsiMeanReversion",
+                "Buys oversold instrument and sells it once it becomes overbought",
+            ),
+        }
+    }
+}
+
+impl StrategyFactory for RsiMeanReversionFactory {
+    fn strategy_name(&self) -> &str {
+        "RsiMeanReversion"
+    }
+
+    fn definition(&self) -> &StrategyDefinition {
+        &self.definition
+    }
+
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError> {
+        let figi = get_instrument_param(params, PARAM_NAME_INSTRUMENT)?;
+        let period = get_period_param(params, PARAM_NAME_PERIOD, DEFAULT_PERIOD)?;
+        let oversold = get_float_param(params, PARAM_NAME_OVERSOLD, DEFAULT_OVERSOLD)?;
+        let overbought = get_float_param(params, PARAM_NAME_OVERBOUGHT, DEFAULT_OVERBOUGHT)?;
+
+        if !(0.0..=100.0).contains(&oversold) {
+            return Err(ParamError::InvalidParam(PARAM_NAME_OVERSOLD.to_owned()).into());
+        }
+
+        if !(oversold..=100.0).contains(&overbought) {
+            return Err(ParamError::InvalidParam(PARAM_NAME_OVERBOUGHT.to_owned()).into());
+        }
+
+        Ok(Arc::new(RsiMeanReversion::new(
+            figi, period, oversold, overbought,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::strategies::test_data::run;
+
+    #[test]
+    fn test_rsi_mean_reversion() {
+        let figi = Figi("FIGI".to_owned());
+        let strategy = RsiMeanReversion::new(figi.clone(), 2, 30.0, 70.0);
+
+        let signals = run(&strategy, &figi, &[10.0, 9.0, 8.0, 8.5, 10.0, 10.2, 9.0]);
+
+        assert_eq!(
+            signals,
+            vec![
+                None,
+                None,
+                Some(1.0),
+                Some(1.0),
+                Some(0.0),
+                Some(0.0),
+                Some(1.0)
+            ]
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::indicators::Stochastic;
+use crate::models::instruments::Figi;
+use crate::models::params::{ParamDefinition, ParamError, ParamType, ParamValue};
+use crate::models::strategy::{
+    ExecutionContext, InstantiateStrategyError, Strategy, StrategyDefinition,
+    StrategyExecutionError, StrategyFactory, StrategyState,
+};
+
+use super::{get_float_param, get_instrument_param, get_period_param};
+
+const PARAM_NAME_INSTRUMENT: &str = "instrument";
+const PARAM_NAME_K_PERIOD: &str = "k_period";
+const PARAM_NAME_D_PERIOD: &str = "d_period";
+const PARAM_NAME_OVERSOLD: &str = "oversold";
+const PARAM_NAME_OVERBOUGHT: &str = "overbought";
+
+const DEFAULT_K_PERIOD: usize = 14;
+const DEFAULT_D_PERIOD: usize = 3;
+const DEFAULT_OVERSOLD: f64 = 20.0;
+const DEFAULT_OVERBOUGHT: f64 = 80.0;
+
+/// Buys when %K crosses above %D out of the oversold zone and holds
+/// the instrument until %K crosses below %D out of the overbought zone
+pub struct StochasticReversal {
+    figi: Figi,
+    stochastic: Stochastic,
+    oversold: f64,
+    overbought: f64,
+    data_requirements: [Figi; 1],
+}
+
+impl StochasticReversal {
+    pub fn new(
+        figi: Figi,
+        k_period: usize,
+        d_period: usize,
+        oversold: f64,
+        overbought: f64,
+    ) -> Self {
+        Self {
+            figi: figi.clone(),
+            stochastic: Stochastic::new(figi.clone(), k_period, d_period),
+            oversold,
+            overbought,
+            data_requirements: [figi],
+        }
+    }
+}
+
+impl Strategy for StochasticReversal {
+    fn data_requirements(&self) -> &[Figi] {
+        &self.data_requirements
+    }
+
+    fn execute(
+        &self,
+        context: &ExecutionContext,
+        mut state: StrategyState,
+    ) -> Result<StrategyState, StrategyExecutionError> {
+        let candle = match context.candle(&self.figi) {
+            Some(candle) => candle,
+            None => return Ok(state),
+        };
+
+        let prev = state.indicator_value(&self.stochastic);
+        let value = state.update_indicator(&self.stochastic, candle)?;
+
+        let (prev, value) = match (prev, value) {
+            (Some(prev), Some(value)) => (prev, value),
+            _ => return Ok(state),
+        };
+
+        let crossed_above = prev.k <= prev.d && value.k > value.d;
+        let crossed_below = prev.k >= prev.d && value.k < value.d;
+
+        let signal = if crossed_above && prev.d < self.oversold {
+            1.0
+        } else if crossed_below && prev.d > self.overbought {
+            0.0
+        } else {
+            state.signals().get(&self.figi).copied().unwrap_or(0.0)
+        };
+
+        state.set_signal(self.figi.to_owned(), signal);
+        Ok(state)
+    }
+}
+
+pub struct StochasticReversalFactory {
+    definition: StrategyDefinition,
+}
+
+impl Default for StochasticReversalFactory {
+    fn default() -> Self {
+        Self {
+            definition: StrategyDefinition::new(
+                vec![
+                    ParamDefinition::new(
+                        PARAM_NAME_INSTRUMENT,
+                        "Instrument to trade",
+                        ParamType::Instrument,
+                        None,
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_K_PERIOD,
+                        "Length of %K high-low range in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_K_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_D_PERIOD,
+                        "Length of %D average of %K in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_D_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_OVERSOLD,
+                        "%D level below which crossing above it buys the instrument",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_OVERSOLD)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_OVERBOUGHT,
+                        "%D level above which crossing below it sells the instrument",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_OVERBOUGHT)),
+                    ),
+                ],
+                "StochasticReversal",
+                "Buys instrument turning up from oversold and sells it turning down from overbought",
+            ),
+        }
+    }
+}
+
+impl StrategyFactory for StochasticReversalFactory {
+    fn strategy_name(&self) -> &str {
+        "StochasticReversal"
+    }
+
+    fn definition(&self) -> &StrategyDefinition {
+        &self.definition
+    }
+
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError> {
+        let figi = get_instrument_param(params, PARAM_NAME_INSTRUMENT)?;
+        let k_period = get_period_param(params, PARAM_NAME_K_PERIOD, DEFAULT_K_PERIOD)?;
+        let d_period = get_period_param(params, PARAM_NAME_D_PERIOD, DEFAULT_D_PERIOD)?;
+        let oversold = get_float_param(params, PARAM_NAME_OVERSOLD, DEFAULT_OVERSOLD)?;
+        let overbought = get_float_param(params, PARAM_NAME_OVERBOUGHT, DEFAULT_OVERBOUGHT)?;
+
+        if !(0.0..=100.0).contains(&oversold) {
+            return Err(ParamError::InvalidParam(PARAM_NAME_OVERSOLD.to_owned()).into());
+        }
+
+        if !(oversold..=100.0).contains(&overbought) {
+            return Err(ParamError::InvalidParam(PARAM_NAME_OVERBOUGHT.to_owned()).into());
+        }
+
+        Ok(Arc::new(StochasticReversal::new(
+            figi, k_period, d_period, oversold, overbought,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::strategies::test_data::run;
+
+    #[test]
+    fn test_stochastic_reversal() {
+        let figi = Figi("FIGI".to_owned());
+        let strategy = StochasticReversal::new(figi.clone(), 3, 2, 30.0, 70.0);
+
+        let signals = run(
+            &strategy,
+            &figi,
+            &[10.0, 9.0, 8.0, 7.0, 6.0, 6.5, 7.5, 8.5, 9.5, 10.0, 9.0],
+        );
+
+        assert_eq!(
+            signals,
+            vec![
+                None,
+                None,
+                None,
+                None,
+                Some(0.0),
+                Some(1.0),
+                Some(1.0),
+                Some(1.0),
+                Some(1.0),
+                Some(0.0),
+                Some(0.0)
+            ]
+        );
+    }
+}
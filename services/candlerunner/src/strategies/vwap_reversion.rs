@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::indicators::{Atr, Obv, Vwap};
+use crate::models::instruments::Figi;
+use crate::models::params::{ParamDefinition, ParamError, ParamType, ParamValue};
+use crate::models::strategy::{
+    ExecutionContext, InstantiateStrategyError, Strategy, StrategyDefinition,
+    StrategyExecutionError, StrategyFactory, StrategyState,
+};
+
+use super::{get_float_param, get_instrument_param, get_period_param};
+
+const PARAM_NAME_INSTRUMENT: &str = "instrument";
+const PARAM_NAME_PERIOD: &str = "period";
+const PARAM_NAME_ATR_PERIOD: &str = "atr_period";
+const PARAM_NAME_DEVIATION: &str = "deviation";
+
+const DEFAULT_PERIOD: usize = 20;
+const DEFAULT_ATR_PERIOD: usize = 14;
+const DEFAULT_DEVIATION: f64 = 2.0;
+
+/// Buys when close is more than `deviation` average true ranges below VWAP
+/// on a candle adding to on-balance volume, i.e. once it closes up,
+/// and holds the instrument until close recovers to VWAP
+pub struct VwapReversion {
+    figi: Figi,
+    vwap: Vwap,
+    atr: Atr,
+    obv: Obv,
+    deviation: f64,
+    data_requirements: [Figi; 1],
+}
+
+impl VwapReversion {
+    pub fn new(figi: Figi, period: usize, atr_period: usize, deviation: f64) -> Self {
+        Self {
+            figi: figi.clone(),
+            vwap: Vwap::new(figi.clone(), period),
+            atr: Atr::new(figi.clone(), atr_period),
+            obv: Obv::new(figi.clone()),
+            deviation,
+            data_requirements: [figi],
+        }
+    }
+}
+
+impl Strategy for VwapReversion {
+    fn data_requirements(&self) -> &[Figi] {
+        &self.data_requirements
+    }
+
+    fn execute(
+        &self,
+        context: &ExecutionContext,
+        mut state: StrategyState,
+    ) -> Result<StrategyState, StrategyExecutionError> {
+        let candle = match context.candle(&self.figi) {
+            Some(candle) => candle,
+            None => return Ok(state),
+        };
+
+        let prev_obv = state.indicator_value(&self.obv);
+        let obv = state.update_indicator(&self.obv, candle)?;
+        let vwap = state.update_indicator(&self.vwap, candle)?;
+        let atr = state.update_indicator(&self.atr, candle)?;
+
+        let (vwap, atr) = match (vwap, atr) {
+            (Some(vwap), Some(atr)) => (vwap, atr),
+            _ => return Ok(state),
+        };
+
+        let accumulated = matches!((prev_obv, obv), (Some(prev_obv), Some(obv)) if obv > prev_obv);
+
+        let signal = if candle.close < vwap - self.deviation * atr && accumulated {
+            1.0
+        } else if candle.close >= vwap {
+            0.0
+        } else {
+            state.signals().get(&self.figi).copied().unwrap_or(0.0)
+        };
+
+        state.set_signal(self.figi.to_owned(), signal);
+        Ok(state)
+    }
+}
+
+pub struct VwapReversionFactory {
+    definition: StrategyDefinition,
+}
+
+impl Default for VwapReversionFactory {
+    fn default() -> Self {
+        Self {
+            definition: StrategyDefinition::new(
+                vec![
+                    ParamDefinition::new(
+                        PARAM_NAME_INSTRUMENT,
+                        "Instrument to trade",
+                        ParamType::Instrument,
+                        None,
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_PERIOD,
+                        "Length of VWAP in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_ATR_PERIOD,
+                        "Length of average true range in candles",
+                        ParamType::Integer,
+                        Some(ParamValue::Integer(DEFAULT_ATR_PERIOD as i64)),
+                    ),
+                    ParamDefinition::new(
+                        PARAM_NAME_DEVIATION,
+                        "Distance below VWAP to buy at in average true ranges",
+                        ParamType::Float,
+                        Some(ParamValue::Float(DEFAULT_DEVIATION)),
+                    ),
+                ],
+                "VwapReversion",
+                "Buys instrument stretched below VWAP once it closes up and sells it back at VWAP",
+            ),
+        }
+    }
+}
+
+impl StrategyFactory for VwapReversionFactory {
+    fn strategy_name(&self) -> &str {
+        "VwapReversion"
+    }
+
+    fn definition(&self) -> &StrategyDefinition {
+        &self.definition
+    }
+
+    fn create(
+        &self,
+        params: &HashMap<String, ParamValue>,
+    ) -> Result<Arc<dyn Strategy>, InstantiateStrategyError> {
+        let figi = get_instrument_param(params, PARAM_NAME_INSTRUMENT)?;
+        let period = get_period_param(params, PARAM_NAME_PERIOD, DEFAULT_PERIOD)?;
+        let atr_period = get_period_param(params, PARAM_NAME_ATR_PERIOD, DEFAULT_ATR_PERIOD)?;
+        let deviation = get_float_param(params, PARAM_NAME_DEVIATION, DEFAULT_DEVIATION)?;
+
+        if deviation < 0.0 {
+            return Err(ParamError::InvalidParam(PARAM_NAME_DEVIATION.to_owned()).into());
+        }
+
+        Ok(Arc::new(VwapReversion::new(
+            figi, period, atr_period, deviation,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::strategies::test_data::run;
+
+    #[test]
+    fn test_vwap_reversion() {
+        let figi = Figi("FIGI".to_owned());
+        let strategy = VwapReversion::new(figi.clone(), 5, 2, 0.5);
+
+        // Closes at 8 and 7 are far enough below VWAP, but only 7.5 closes up
+        let signals = run(
+            &strategy,
+            &figi,
+            &[10.0, 10.0, 10.0, 10.0, 10.0, 8.0, 7.0, 7.5, 8.5, 9.5],
+        );
+
+        assert_eq!(
+            signals,
+            vec![
+                None,
+                None,
+                None,
+                None,
+                Some(0.0),
+                Some(0.0),
+                Some(0.0),
+                Some(1.0),
+                Some(0.0),
+                Some(0.0)
+            ]
+        );
+    }
+}